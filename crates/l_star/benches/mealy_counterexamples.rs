@@ -0,0 +1,83 @@
+// Compares the two ways of working counterexamples into a Mealy learner's table on a random
+// 80-state target, with an oracle that finds counterexamples by random walks, so that they are
+// long as in practice. Run with `cargo bench --bench mealy_counterexamples`.
+
+use std::time::Instant;
+
+use l_star::alphabet::Alphabet;
+use l_star::automaton::{MealyMachine, MealyState};
+use l_star::learner::CounterexampleHandling;
+use l_star::mealy_learner::MealyLearner;
+use l_star::teacher::{EquivalenceAnswer, MealyTeacher};
+
+const STATES: usize = 80;
+const INPUTS: [char; 2] = ['a', 'b'];
+const WALK_LEN: usize = 100;
+const WALKS: usize = 2000;
+
+// SplitMix64, so that every run benchmarks the same target and walks.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) % bound as u64) as usize
+    }
+}
+
+fn random_target(rng: &mut Rng) -> MealyMachine<usize, char, u8> {
+    let mut target = MealyMachine::new(MealyState::new(0));
+    for state in 0..STATES {
+        for input in INPUTS {
+            let next = rng.below(STATES);
+            let output = rng.below(3) as u8;
+            target.add_transition(&MealyState::new(state), &MealyState::new(next), &input, &output);
+        }
+    }
+    target
+}
+
+// Answers equivalence queries with the first of `WALKS` random words the hypothesis gets
+// wrong, cut after the first wrong output, and only falls back to an exact check when none is.
+struct RandomWalkTeacher {
+    target: MealyMachine<usize, char, u8>,
+    rng: Rng,
+}
+
+impl MealyTeacher<char, u8> for RandomWalkTeacher {
+
+    fn output_query(&mut self, word: &[char]) -> Vec<u8> {
+        self.target.run(word)
+    }
+
+    fn equivalence_query(&mut self, hypothesis: &MealyMachine<Vec<char>, char, u8>) -> EquivalenceAnswer<char> {
+        for _ in 0..WALKS {
+            let word: Vec<char> = (0..WALK_LEN).map(|_| INPUTS[self.rng.below(INPUTS.len())]).collect();
+            let (expected, actual) = (self.target.run(&word), hypothesis.run(&word));
+            if let Some(end) = (0..word.len()).find(|end| expected.get(*end) != actual.get(*end)) {
+                return Err(word[..=end].to_vec());
+            }
+        }
+        match self.target.distinguishing_word(hypothesis) {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+}
+
+fn main() {
+    let target = random_target(&mut Rng(1));
+    println!("{:<10} {:>14} {:>19} {:>7} {:>10}", "handling", "output queries", "equivalence queries", "states", "time");
+    for handling in [CounterexampleHandling::Prefixes, CounterexampleHandling::Suffixes] {
+        let teacher = RandomWalkTeacher { target: target.clone(), rng: Rng(2) };
+        let mut learner = MealyLearner::new(Alphabet::new(INPUTS), Box::new(teacher)).with_counterexample_handling(handling);
+        let start = Instant::now();
+        let learned = learner.learn().expect("the target answers every query");
+        let elapsed = start.elapsed();
+        assert!(learned.is_equivalent(&target));
+        println!("{:<10} {:>14} {:>19} {:>7} {:>10.1?}", format!("{:?}", handling), learner.output_queries(), learner.equivalence_queries(), learned.get_states().len(), elapsed);
+    }
+}
@@ -0,0 +1,48 @@
+// What taking membership query words as slices saves over the owned `Vec` the teacher used to
+// be handed: the queries of a learning run over `String` symbols are answered once as slices
+// and once each cloned first, as every query used to be. Run with
+// `cargo bench --bench membership_slices`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use l_star::alphabet::Alphabet;
+use l_star::learner::Learner;
+use l_star::teacher::Teacher;
+use l_star::teachers::dfa_teacher::DfaTeacher;
+use l_star::teachers::recording_teacher::{RecordingTeacher, TraceEvent};
+use l_star::teachers::regex_teacher::RegexTeacher;
+
+const ROUNDS: usize = 200;
+
+fn time(words: &[Vec<String>], mut answer: impl FnMut(&[String]) -> bool) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for word in words {
+            black_box(answer(black_box(word)));
+        }
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let alphabet = Alphabet::new(["alpha".to_string(), "beta".to_string()]);
+    let regex = RegexTeacher::new_anchored("(alpha|beta)*alpha(alpha|beta){4}").unwrap();
+    let teacher = DfaTeacher::new(&regex.to_automaton(&alphabet).unwrap());
+
+    let recording = RecordingTeacher::new(DfaTeacher::new(&regex.to_automaton(&alphabet).unwrap()));
+    Learner::new(alphabet, Box::new(recording.clone())).learn().expect("the target answers every query");
+    let words: Vec<Vec<String>> = recording.trace().events.into_iter()
+        .filter_map(|event| match event {
+            TraceEvent::Membership { word, .. } => Some(word),
+            TraceEvent::Equivalence { .. } => None,
+        })
+        .collect();
+
+    let sliced = time(&words, |word| teacher.membership_query(word));
+    let cloned = time(&words, |word| teacher.membership_query(&black_box(word.to_vec())));
+    let queries = words.len() * ROUNDS;
+    println!("{} membership queries of a run, answered {} times", words.len(), ROUNDS);
+    println!("{:<8} {:>10.1?} {:>8.0} ns/query", "slice", sliced, sliced.as_nanos() as f64 / queries as f64);
+    println!("{:<8} {:>10.1?} {:>8.0} ns/query", "cloned", cloned, cloned.as_nanos() as f64 / queries as f64);
+}
@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+
+/// A finite set of symbols with a stable position for each, so that per-symbol data can live in
+/// plain vectors indexed by `index_of`.
+#[derive(Clone, Debug)]
+pub struct Alphabet<T> {
+    symbols: Vec<T>,
+    index: HashMap<T, usize>,
+}
+
+impl<T: Eq + Hash + Clone> Alphabet<T> {
+    /// Symbols keep the order in which they first appear; later duplicates are ignored.
+    pub fn new(symbols: impl IntoIterator<Item = T>) -> Self {
+        let mut alphabet = Alphabet {
+            symbols: Vec::new(),
+            index: HashMap::new(),
+        };
+        for symbol in symbols {
+            alphabet.insert(symbol);
+        }
+        alphabet
+    }
+
+    /// Like `new`, but a symbol given twice is reported instead of ignored.
+    pub fn try_new(symbols: impl IntoIterator<Item = T>) -> Result<Self, DuplicateSymbol<T>> {
+        let mut alphabet = Alphabet::new([]);
+        for symbol in symbols {
+            if alphabet.contains(&symbol) {
+                return Err(DuplicateSymbol(symbol));
+            }
+            alphabet.insert(symbol);
+        }
+        Ok(alphabet)
+    }
+
+    /// Adds `symbol` at the end if it is new, returning its index either way.
+    pub fn insert(&mut self, symbol: T) -> usize {
+        if let Some(index) = self.index.get(&symbol) {
+            return *index;
+        }
+        self.index.insert(symbol.clone(), self.symbols.len());
+        self.symbols.push(symbol);
+        self.symbols.len() - 1
+    }
+
+    pub fn index_of(&self, symbol: &T) -> Option<usize> {
+        self.index.get(symbol).copied()
+    }
+
+    pub fn contains(&self, symbol: &T) -> bool {
+        self.index.contains_key(symbol)
+    }
+
+    pub fn symbol(&self, index: usize) -> &T {
+        &self.symbols[index]
+    }
+
+    pub fn symbols(&self) -> &[T] {
+        &self.symbols
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.symbols.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+impl<T: Eq + Hash + Clone> FromIterator<T> for Alphabet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(symbols: I) -> Self {
+        Alphabet::new(symbols)
+    }
+}
+
+/// Symbols are sorted, since a set has no order of its own to keep.
+impl<T: Eq + Hash + Clone + Ord> From<HashSet<T>> for Alphabet<T> {
+    fn from(symbols: HashSet<T>) -> Self {
+        let mut symbols: Vec<T> = symbols.into_iter().collect();
+        symbols.sort();
+        Alphabet::new(symbols)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Alphabet<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.symbols.iter()
+    }
+}
+
+
+/// The symbol that `Alphabet::try_new` saw more than once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateSymbol<T>(pub T);
+
+impl<T: Debug> fmt::Display for DuplicateSymbol<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "symbol {:?} appears more than once in the alphabet", self.0)
+    }
+}
+
+impl<T: Debug> std::error::Error for DuplicateSymbol<T> {}
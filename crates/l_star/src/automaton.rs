@@ -1,15 +1,40 @@
-use std::collections::{HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Display};
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+
+use crate::alphabet::Alphabet;
+use crate::nfa::Nfa;
+
+mod builder;
+mod dense;
+mod learnlib;
+mod mealy;
+mod moore;
+mod regular_expression;
+mod state_id;
+mod strings;
+#[cfg(feature = "render")]
+pub mod render;
+mod symbolic;
+
+pub use builder::{AutomatonBuilder, BuildError};
+pub use dense::DenseAutomaton;
+pub use learnlib::LearnLibDotError;
+pub use mealy::{MealyMachine, MealyState};
+pub use moore::{MooreMachine, MooreState};
+pub use state_id::{StateId, StateNames};
+pub use symbolic::{ByteClass, CharClass, SymbolClass};
 
 
 #[derive(Clone, Debug)]
 pub struct DfaState<StateId, TransitionLabel> {
     state_id: StateId,
     is_accepting: bool,
+    #[deprecated(note = "read through `transition`/`transitions` and modify through `Automaton::add_transition`; the field will become private")]
     pub transitions: HashMap<TransitionLabel, StateId>,
 }
 
+#[allow(deprecated)]
 impl<StateId, TransitionLabel> DfaState<StateId, TransitionLabel> {
     pub fn new(state_id: StateId, is_accepting: bool) -> Self {
         DfaState {
@@ -30,6 +55,39 @@ impl<StateId, TransitionLabel> DfaState<StateId, TransitionLabel> {
     pub fn set_accepting(&mut self, is_accepting: bool) {
         self.is_accepting = is_accepting;
     }
+
+    /// Outgoing edges as `(label, target)` pairs, in no particular order.
+    pub fn transitions(&self) -> impl Iterator<Item = (&TransitionLabel, &StateId)> {
+        self.transitions.iter()
+    }
+
+    /// Outgoing edges sorted by label, for output that has to be the same on every run.
+    pub fn transitions_sorted(&self) -> Vec<(&TransitionLabel, &StateId)> where TransitionLabel: Ord {
+        let mut transitions: Vec<(&TransitionLabel, &StateId)> = self.transitions.iter().collect();
+        transitions.sort_by(|a, b| a.0.cmp(b.0));
+        transitions
+    }
+}
+
+#[allow(deprecated)]
+impl<StateId: PartialEq, TransitionLabel: Eq + Hash> PartialEq for DfaState<StateId, TransitionLabel> {
+    fn eq(&self, other: &Self) -> bool {
+        self.state_id == other.state_id && self.is_accepting == other.is_accepting && self.transitions == other.transitions
+    }
+}
+
+impl<StateId: Eq, TransitionLabel: Eq + Hash> Eq for DfaState<StateId, TransitionLabel> {}
+
+#[allow(deprecated)]
+impl<StateId, TransitionLabel: Eq + Hash> DfaState<StateId, TransitionLabel> {
+    pub fn transition(&self, transition_label: &TransitionLabel) -> Option<&StateId> {
+        self.transitions.get(transition_label)
+    }
+
+    // Only `Automaton` adds edges, so that targets always exist in the automaton.
+    fn insert_transition(&mut self, transition_label: TransitionLabel, target: StateId) {
+        self.transitions.insert(transition_label, target);
+    }
 }
 
 
@@ -50,13 +108,63 @@ where
 
 
 
-#[derive(Clone, Debug)]
-pub struct Automaton<StateId: Eq + Hash + Clone + Debug, TransitionLabel: Eq + Hash + Clone + Default> {
+/// How a state id is written in exported diagrams.
+pub trait StateLabel {
+    fn state_label(&self) -> String;
+}
+
+// Access strings from the learner are shown as their joined symbols.
+impl<T: Display> StateLabel for Vec<T> {
+    fn state_label(&self) -> String {
+        self.iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
+
+macro_rules! display_state_label {
+    ($($t:ty),*) => {
+        $(
+            impl StateLabel for $t {
+                fn state_label(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+display_state_label!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, char, bool, String, &str);
+
+
+/// Two automata are equal when they have the same states, under the same ids, with the same
+/// edges and the same initial state. `Hash` agrees with this regardless of insertion order.
+/// This is deliberately structural: two DFAs for the same language but with other ids or an
+/// unminimized shape compare unequal, so compare learned automata with `language_eq` rather
+/// than `assert_eq!`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Automaton<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone> {
     states: HashMap<StateId, DfaState<StateId, TransitionLabel>>,
     initial_state: StateId,
 }
 
-impl <T: Eq + Hash + Clone + Debug + Display, StateId: Eq + Hash + Clone + Debug + IntoIterator<Item = T>, TransitionLabel: Eq + Hash + Clone + Debug + Default> Automaton<StateId, TransitionLabel> {
+// Hashes the canonical form: states sorted by id, each with its edges sorted by label.
+impl<StateId: Eq + Hash + Clone + Ord, TransitionLabel: Eq + Hash + Clone + Ord> Hash for Automaton<StateId, TransitionLabel> {
+    fn hash<H: std::hash::Hasher>(&self, hasher: &mut H) {
+        self.initial_state.hash(hasher);
+        let mut states: Vec<&DfaState<StateId, TransitionLabel>> = self.states.values().collect();
+        states.sort_by(|a, b| a.state_id.cmp(&b.state_id));
+        states.len().hash(hasher);
+        for state in states {
+            state.state_id.hash(hasher);
+            state.is_accepting.hash(hasher);
+            state.transitions_sorted().hash(hasher);
+        }
+    }
+}
+
+impl<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone> Automaton<StateId, TransitionLabel> {
 
     pub fn new(initial_state: DfaState<StateId, TransitionLabel>) -> Self {
         let mut states: HashMap<StateId, DfaState<StateId, TransitionLabel>> = HashMap::new();
@@ -72,6 +180,22 @@ impl <T: Eq + Hash + Clone + Debug + Display, StateId: Eq + Hash + Clone + Debug
         &self.states
     }
 
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Number of edges, summed over the transitions leaving each state.
+    pub fn transition_count(&self) -> usize {
+        self.states.values().map(|state| state.transitions().count()).sum()
+    }
+
+    /// Whether every state has an edge for every symbol of `alphabet`; see `complete`.
+    pub fn is_total(&self, alphabet: &Alphabet<TransitionLabel>) -> bool {
+        self.states.values().all(|state| alphabet.iter().all(|symbol| state.transition(symbol).is_some()))
+    }
+
+    /// Adds or replaces the edge `from --transition_label--> to`, inserting either state if it
+    /// is not yet part of the automaton.
     pub fn add_transition(&mut self, from: &DfaState<StateId, TransitionLabel>, to: &DfaState<StateId, TransitionLabel>, transition_label: &TransitionLabel) {
         // Ensure both states exist
         if !self.states.contains_key(&from.state_id) {
@@ -82,7 +206,7 @@ impl <T: Eq + Hash + Clone + Debug + Display, StateId: Eq + Hash + Clone + Debug
         }
 
         if let Some(from_state) = self.states.get_mut(&from.state_id) {
-                from_state.transitions.insert(transition_label.clone(), to.state_id.clone());
+                from_state.insert_transition(transition_label.clone(), to.state_id.clone());
         }
     }
 
@@ -104,36 +228,713 @@ impl <T: Eq + Hash + Clone + Debug + Display, StateId: Eq + Hash + Clone + Debug
         self.initial_state = state.state_id.clone();
     }
 
-    pub fn to_dot(&self) -> String {
-        let mut dot = String::from("digraph DFA {\n");
+    /// Runs `word` from the initial state. A missing transition rejects the word.
+    pub fn accepts(&self, word: &[TransitionLabel]) -> bool {
+        let mut state = &self.states[&self.initial_state];
+        for label in word {
+            match state.transition(label).and_then(|target| self.states.get(target)) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        state.is_accepting()
+    }
+
+    /// The states visited while reading `word`, starting with the initial state. The trace
+    /// ends early if the word runs into a missing transition.
+    pub fn trace(&self, word: &[TransitionLabel]) -> Vec<&StateId> {
+        let mut trace = vec![&self.initial_state];
+        let mut state = &self.states[&self.initial_state];
+        for label in word {
+            match state.transition(label).and_then(|target| self.states.get(target)) {
+                Some(next) => {
+                    trace.push(&next.state_id);
+                    state = next;
+                }
+                None => break,
+            }
+        }
+        trace
+    }
+
+    /// The same automaton with every transition label replaced by `f(label)`; states, their ids
+    /// and accepting flags are kept. If `f` sends two labels leaving one state to the same value,
+    /// only one of those edges survives.
+    pub fn map_labels<U: Eq + Hash + Clone, F: Fn(&TransitionLabel) -> U>(&self, f: F) -> Automaton<StateId, U> {
+        let states = self.states.iter().map(|(state_id, state)| {
+            let mut mapped = DfaState::new(state_id.clone(), state.is_accepting());
+            for (label, target) in state.transitions() {
+                mapped.insert_transition(f(label), target.clone());
+            }
+            (state_id.clone(), mapped)
+        }).collect();
+
+        Automaton {
+            states,
+            initial_state: self.initial_state.clone(),
+        }
+    }
+
+    // Numbers the states from `offset` upwards, initial state first, so that several automata
+    // can be laid out side by side in one NFA.
+    fn number_states(&self, offset: usize) -> HashMap<StateId, usize> {
+        let mut ids: HashMap<StateId, usize> = HashMap::from([(self.initial_state.clone(), offset)]);
+        for state_id in self.states.keys() {
+            let next_id = offset + ids.len();
+            ids.entry(state_id.clone()).or_insert(next_id);
+        }
+        ids
+    }
+
+    fn copy_into_nfa(&self, nfa: &mut Nfa<usize, TransitionLabel>, ids: &HashMap<StateId, usize>) {
+        for (state_id, state) in &self.states {
+            nfa.add_state(ids[state_id], state.is_accepting());
+            nfa.set_accepting(&ids[state_id], state.is_accepting());
+            for (label, target) in state.transitions() {
+                nfa.add_transition(&ids[state_id], &ids[target], label);
+            }
+        }
+    }
 
-        // Mark accepting states
+    /// The minimal DFA for the same language, with states numbered breadth-first from the
+    /// initial state 0. Dead states are dropped, so missing transitions reject.
+    pub fn minimize(&self) -> Automaton<usize, TransitionLabel> {
+        DenseAutomaton::from(self).minimize().to_automaton()
+    }
+
+    /// Automaton accepting the words accepted by both `self` and `other`.
+    pub fn intersection<OtherId: Eq + Hash + Clone>(&self, other: &Automaton<OtherId, TransitionLabel>) -> Automaton<usize, TransitionLabel> {
+        DenseAutomaton::from(self).product(&DenseAutomaton::from(other), |left, right| left && right).to_automaton()
+    }
+
+    /// Automaton accepting the words accepted by `self` or `other`.
+    pub fn union<OtherId: Eq + Hash + Clone>(&self, other: &Automaton<OtherId, TransitionLabel>) -> Automaton<usize, TransitionLabel> {
+        DenseAutomaton::from(self).product(&DenseAutomaton::from(other), |left, right| left || right).to_automaton()
+    }
+
+    /// Automaton accepting the words accepted by `self` but not by `other`.
+    pub fn difference<OtherId: Eq + Hash + Clone>(&self, other: &Automaton<OtherId, TransitionLabel>) -> Automaton<usize, TransitionLabel> {
+        DenseAutomaton::from(self).product(&DenseAutomaton::from(other), |left, right| left && !right).to_automaton()
+    }
+
+    /// The same language as a total automaton over `alphabet`, together with this automaton's
+    /// labels: every missing transition goes to an added rejecting sink, which spells out the
+    /// "missing transition rejects" rule of `accepts`. States are numbered as in
+    /// `DenseAutomaton::from_automaton`, the sink last.
+    pub fn complete(&self, alphabet: &Alphabet<TransitionLabel>) -> Automaton<usize, TransitionLabel> {
+        DenseAutomaton::from_automaton(self, alphabet).complete().to_automaton()
+    }
+
+    /// Automaton accepting the words over `alphabet`, together with this automaton's labels,
+    /// that `self` rejects, including those running into a missing transition: `complete` with
+    /// accepting and rejecting swapped. Words with a symbol outside that alphabet are rejected
+    /// by both, so they are the one exception to `complement(a).accepts(w) == !accepts(w)`.
+    pub fn complement(&self, alphabet: &Alphabet<TransitionLabel>) -> Automaton<usize, TransitionLabel> {
+        DenseAutomaton::from_automaton(self, alphabet).complement().to_automaton()
+    }
+
+    /// Automaton accepting the words accepted by exactly one of `self` and `other`. It is empty
+    /// iff the two are equivalent, and its `shortest_accepted` word is a shortest
+    /// counterexample between them.
+    pub fn symmetric_difference<OtherId: Eq + Hash + Clone>(&self, other: &Automaton<OtherId, TransitionLabel>) -> Automaton<usize, TransitionLabel> {
+        DenseAutomaton::from(self).product(&DenseAutomaton::from(other), |left, right| left != right).to_automaton()
+    }
+
+    /// Whether no word is accepted, i.e. no accepting state is reachable.
+    pub fn is_empty(&self) -> bool {
+        DenseAutomaton::from(self).is_empty()
+    }
+
+    /// Number of accepted words, or `None` if the language is infinite.
+    pub fn language_size(&self) -> Option<u64> {
+        DenseAutomaton::from(self).language_size()
+    }
+
+    /// A shortest accepted word, or `None` if the language is empty.
+    pub fn shortest_accepted(&self) -> Option<Vec<TransitionLabel>> {
+        DenseAutomaton::from(self).shortest_accepted()
+    }
+
+    /// Whether both automata accept the same language; the same as `equivalent`, for when `==`
+    /// would be the natural thing to write.
+    pub fn language_eq(&self, other: &Self) -> bool {
+        self.equivalent(other)
+    }
+
+    /// Whether both automata accept the same language (Hopcroft–Karp).
+    pub fn equivalent<OtherId: Eq + Hash + Clone>(&self, other: &Automaton<OtherId, TransitionLabel>) -> bool {
+        DenseAutomaton::from(self).equivalent(&DenseAutomaton::from(other))
+    }
+
+    /// A shortest word accepted by exactly one of the two automata, or `None` if they accept
+    /// the same language.
+    pub fn distinguishing_word<OtherId: Eq + Hash + Clone>(&self, other: &Automaton<OtherId, TransitionLabel>) -> Option<Vec<TransitionLabel>> {
+        DenseAutomaton::from(self).distinguishing_word(&DenseAutomaton::from(other))
+    }
+
+    /// The shortest access word of every reachable state, breadth-first with labels taken in
+    /// `alphabet` order (labels missing from it last).
+    pub fn state_cover(&self, alphabet: &Alphabet<TransitionLabel>) -> Vec<Vec<TransitionLabel>> {
+        DenseAutomaton::from_automaton(self, alphabet).state_cover()
+    }
+
+    /// Every shortest access word extended by every label, after the state cover itself; see
+    /// `DenseAutomaton::transition_cover`.
+    pub fn transition_cover(&self, alphabet: &Alphabet<TransitionLabel>) -> Vec<Vec<TransitionLabel>> {
+        DenseAutomaton::from_automaton(self, alphabet).transition_cover()
+    }
+
+    /// A set of words that tells apart every two reachable states accepting different
+    /// languages, the implicit sink included; see `DenseAutomaton::characterizing_set`.
+    pub fn characterizing_set(&self, alphabet: &Alphabet<TransitionLabel>) -> Vec<Vec<TransitionLabel>> {
+        DenseAutomaton::from_automaton(self, alphabet).characterizing_set()
+    }
+
+    /// Automaton for the words `uv` with `u` accepted by `self` and `v` by `other`.
+    pub fn concat<OtherId: Eq + Hash + Clone>(&self, other: &Automaton<OtherId, TransitionLabel>) -> Automaton<Vec<usize>, TransitionLabel> {
+        let left = self.number_states(0);
+        let right = other.number_states(left.len());
+
+        let mut nfa: Nfa<usize, TransitionLabel> = Nfa::new(left[&self.initial_state], false);
+        self.copy_into_nfa(&mut nfa, &left);
+        other.copy_into_nfa(&mut nfa, &right);
+
+        // Leaving the left operand in an accepting state hands over to the right one; only
+        // the right operand decides acceptance, even when either side accepts the empty word.
         for (state_id, state) in &self.states {
-            let state_id_str = state.serialize_state_id("");
             if state.is_accepting() {
-                dot.push_str(&format!("    {:?} [shape=doublecircle];\n", &state_id_str));
-            } else {
-                dot.push_str(&format!("    {:?};\n", &state_id_str));
+                nfa.set_accepting(&left[state_id], false);
+                nfa.add_epsilon_transition(&left[state_id], &right[&other.initial_state]);
+            }
+        }
+        nfa.determinize()
+    }
+
+    /// Automaton for the Kleene star of the language: the empty word plus any concatenation of
+    /// accepted words.
+    pub fn star(&self) -> Automaton<Vec<usize>, TransitionLabel> {
+        // A fresh accepting start state rather than marking the old initial state accepting,
+        // which would wrongly accept words that loop back to it without completing a word.
+        let start = 0;
+        let ids = self.number_states(1);
+
+        let mut nfa: Nfa<usize, TransitionLabel> = Nfa::new(start, true);
+        self.copy_into_nfa(&mut nfa, &ids);
+        nfa.add_epsilon_transition(&start, &ids[&self.initial_state]);
+
+        for (state_id, state) in &self.states {
+            if state.is_accepting() {
+                nfa.add_epsilon_transition(&ids[state_id], &start);
             }
         }
+        nfa.determinize()
+    }
 
+    /// Automaton for the reversal of the language: `a1 .. an` is accepted exactly when
+    /// `an .. a1` is accepted here.
+    pub fn reverse(&self) -> Automaton<Vec<usize>, TransitionLabel> {
+        // Every edge turned around, starting in every old accepting state, and accepting where
+        // the old automaton started.
+        let ids = self.number_states(0);
 
+        let accepting = self.states.values().filter(|state| state.is_accepting()).map(|state| ids[&state.state_id]);
+        let mut nfa: Nfa<usize, TransitionLabel> = Nfa::from_initial_states(accepting);
+        for (state_id, state) in &self.states {
+            nfa.add_state(ids[state_id], false);
+            nfa.set_accepting(&ids[state_id], *state_id == self.initial_state);
+            for (label, target) in state.transitions() {
+                nfa.add_transition(&ids[target], &ids[state_id], label);
+            }
+        }
+        nfa.determinize()
+    }
+
+    /// The reachable part with every state named by a shortest word leading to it.
+    pub(crate) fn with_access_words(&self) -> Automaton<Vec<TransitionLabel>, TransitionLabel> {
+        let mut names: HashMap<&StateId, Vec<TransitionLabel>> = HashMap::from([(&self.initial_state, Vec::new())]);
+        let mut order = vec![&self.initial_state];
+        let mut index = 0;
+        while index < order.len() {
+            for (label, target) in self.states[order[index]].transitions() {
+                if !names.contains_key(target) {
+                    let mut name = names[order[index]].clone();
+                    name.push(label.clone());
+                    names.insert(target, name);
+                    order.push(target);
+                }
+            }
+            index += 1;
+        }
+
+        let named = |state_id: &StateId| DfaState::new(names[state_id].clone(), self.states[state_id].is_accepting());
+        let mut automaton = Automaton::new(named(&self.initial_state));
+        for state_id in order {
+            automaton.add_state(named(state_id));
+            for (label, target) in self.states[state_id].transitions() {
+                automaton.add_transition(&named(state_id), &named(target), label);
+            }
+        }
+        automaton
+    }
+}
+
+impl<StateId: Eq + Hash + Clone + Ord, TransitionLabel: Eq + Hash + Clone> Automaton<StateId, TransitionLabel> {
+
+    /// The `(state, symbol)` pairs without an outgoing edge, listed by ascending state id and
+    /// for each state in alphabet order, so the list is the same on every run.
+    pub fn missing_transitions(&self, alphabet: &Alphabet<TransitionLabel>) -> Vec<(StateId, TransitionLabel)> {
+        let mut states: Vec<&DfaState<StateId, TransitionLabel>> = self.states.values().collect();
+        states.sort_by(|a, b| a.state_id.cmp(&b.state_id));
+        states.into_iter()
+            .flat_map(|state| alphabet.iter()
+                .filter(|symbol| state.transition(symbol).is_none())
+                .map(|symbol| (state.state_id.clone(), symbol.clone())))
+            .collect()
+    }
+}
+
+#[cfg(any(test, feature = "testutil"))]
+impl<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone + Debug> Automaton<StateId, TransitionLabel> {
+    /// Panics unless every word is accepted exactly when its label is `true`, listing all the
+    /// words that are not.
+    #[track_caller]
+    pub fn assert_language_eq(&self, words_and_labels: &[(&[TransitionLabel], bool)]) {
+        let wrong: Vec<String> = words_and_labels.iter()
+            .filter(|(word, expected)| self.accepts(word) != *expected)
+            .map(|(word, expected)| format!("  {:?} should be {}", word, if *expected { "accepted" } else { "rejected" }))
+            .collect();
+        if !wrong.is_empty() {
+            panic!("{} of {} words misclassified:\n{}", wrong.len(), words_and_labels.len(), wrong.join("\n"));
+        }
+    }
+}
+
+impl<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone + Ord> Automaton<StateId, TransitionLabel> {
+
+    /// The same automaton with states numbered breadth-first from the initial state 0,
+    /// following edges in label order. Unreachable states are dropped. Two automata that only
+    /// differ in state ids give equal results, so `a.minimize().canonicalize() ==
+    /// b.minimize().canonicalize()` exactly when `a` and `b` accept the same language.
+    pub fn canonicalize(&self) -> Automaton<usize, TransitionLabel> {
+        let mut order: Vec<&StateId> = vec![&self.initial_state];
+        let mut ids: HashMap<&StateId, usize> = HashMap::from([(&self.initial_state, 0)]);
+        let mut index = 0;
+        while index < order.len() {
+            for (_, target) in self.states[order[index]].transitions_sorted() {
+                if !ids.contains_key(target) {
+                    ids.insert(target, order.len());
+                    order.push(target);
+                }
+            }
+            index += 1;
+        }
+
+        let state = |state_id: &StateId| DfaState::new(ids[state_id], self.states[state_id].is_accepting());
+        let mut automaton = Automaton::new(state(&self.initial_state));
+        for state_id in &order {
+            automaton.add_state(state(state_id));
+            for (label, target) in self.states[*state_id].transitions() {
+                automaton.add_transition(&state(state_id), &state(target), label);
+            }
+        }
+        automaton
+    }
+
+    /// A hash of the language: automata accepting the same language hash the same, so
+    /// different hashes prove different languages, e.g. to deduplicate many automata before
+    /// comparing them pairwise. It hashes `minimize().canonicalize()` with FNV-1a rather than
+    /// `DefaultHasher`, whose algorithm may change between Rust releases, so it can be stored
+    /// and compared across runs and builds as long as the labels' `Hash` stays the same.
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        self.minimize().canonicalize().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+// 64-bit FNV-1a, a fixed algorithm for `canonical_hash`. Integers are fed in little-endian
+// order and `usize` as 64 bits, so the hash is the same on every platform.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// How `to_dot_opts` draws an automaton. The default is what `to_dot` writes.
+#[derive(Debug, Clone)]
+pub struct DotOptions<TransitionLabel> {
+    hide_trap_states: bool,
+    colors: bool,
+    completion: Option<Alphabet<TransitionLabel>>,
+}
+
+impl<TransitionLabel> Default for DotOptions<TransitionLabel> {
+    fn default() -> Self {
+        DotOptions { hide_trap_states: false, colors: false, completion: None }
+    }
+}
+
+impl<TransitionLabel> DotOptions<TransitionLabel> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leaves out rejecting states that cannot reach an accepting one, and the edges into them.
+    /// The initial state is always drawn.
+    pub fn with_hidden_trap_states(mut self, hide: bool) -> Self {
+        self.hide_trap_states = hide;
+        self
+    }
+
+    /// Fills accepting states green and rejecting states red.
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Draws the missing transitions over `alphabet`, and those into hidden trap states, as
+    /// dashed edges to one shared sink.
+    pub fn with_completion(mut self, alphabet: Alphabet<TransitionLabel>) -> Self {
+        self.completion = Some(alphabet);
+        self
+    }
+}
+
+impl<StateId: Eq + Hash + Clone + StateLabel, TransitionLabel: Eq + Hash + Clone + Display> Automaton<StateId, TransitionLabel> {
+
+    /// Graphviz source for the automaton. Labels are written with `Display`, so symbol classes
+    /// show as their ranges.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_opts(&DotOptions::default())
+    }
+
+    pub fn to_dot_opts(&self, options: &DotOptions<TransitionLabel>) -> String {
+        self.to_dot_with_labels(options, &HashMap::new())
+    }
+
+    /// Like `to_dot_opts`, with `labels[state]` written under the id of each state that has
+    /// one, e.g. its row from `ObservationTable::row_signatures`.
+    pub fn to_dot_with_labels(&self, options: &DotOptions<TransitionLabel>, labels: &HashMap<StateId, String>) -> String {
+        let mut dot = String::from("digraph DFA {\n");
         let initial_state = self.get_initial_state().unwrap();
+        let hidden = if options.hide_trap_states { self.trap_states() } else { HashSet::new() };
+        let shown = |state_id: &StateId| !hidden.contains(state_id) || *state_id == initial_state.state_id;
+
+        // Mark accepting states, in label order so that the output is stable
+        let mut states: Vec<(String, &DfaState<StateId, TransitionLabel>)> = self.states.values()
+            .filter(|state| shown(&state.state_id))
+            .map(|state| (state.state_id.state_label(), state))
+            .collect();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        for (state_id_str, state) in &states {
+            let mut attributes = Vec::new();
+            if let Some(label) = labels.get(&state.state_id) {
+                attributes.push(format!("label = {:?}", format!("{}\n{}", state_id_str, label)));
+            }
+            if state.is_accepting() {
+                attributes.push("shape=doublecircle".to_string());
+            }
+            if options.colors {
+                attributes.push("style=filled".to_string());
+                attributes.push(if state.is_accepting() { "fillcolor=\"#c8e6c9\"" } else { "fillcolor=\"#ffcdd2\"" }.to_string());
+            }
+            if attributes.is_empty() {
+                dot.push_str(&format!("    {:?};\n", state_id_str));
+            } else {
+                dot.push_str(&format!("    {:?} [{}];\n", state_id_str, attributes.join(", ")));
+            }
+        }
+
         // Initial state arrow
-        dot.push_str(&format!("    __start__ [shape=point];\n    __start__ -> {:?} [label = {:?}];\n", initial_state.serialize_state_id(""), TransitionLabel::default()));
+        dot.push_str(&format!("    __start__ [shape=point];\n    __start__ -> {:?};\n", initial_state.state_id.state_label()));
 
         // Transitions
-        for (state_id, state) in &self.states {
-            for (label, target) in &state.transitions {
-                let target_state = self.states.get(target).unwrap();
-                dot.push_str(&format!(
-                    "    {:?} -> {:?} [label = {:?}];\n",
-                    &state.serialize_state_id(""), target_state.serialize_state_id(""), label
-                ));
+        write_dot_edges(&mut dot, states.iter().flat_map(|(state_id_str, state)| {
+            state.transitions()
+                .filter(|(_, target)| shown(target))
+                .map(|(label, target)| (state_id_str.clone(), target.state_label(), label.to_string()))
+        }));
+
+        // The completion: every symbol without a drawn edge goes to the shared sink
+        if let Some(alphabet) = &options.completion {
+            let mut completion = Vec::new();
+            for (state_id_str, state) in &states {
+                for symbol in alphabet.iter() {
+                    if state.transition(symbol).is_none_or(|target| !shown(target)) {
+                        completion.push((state_id_str.clone(), "__sink__".to_string(), symbol.to_string()));
+                    }
+                }
+            }
+            if !completion.is_empty() {
+                dot.push_str("    __sink__ [label=\"\", style=dashed];\n");
+                completion.extend(alphabet.iter().map(|symbol| ("__sink__".to_string(), "__sink__".to_string(), symbol.to_string())));
+                write_dot_edges_with(&mut dot, completion, ", style=dashed");
             }
         }
 
         dot.push_str("}\n");
         dot
     }
+
+    // Rejecting states from which no accepting state can be reached.
+    fn trap_states(&self) -> HashSet<StateId> {
+        let mut live: HashSet<&StateId> = self.states.values()
+            .filter(|state| state.is_accepting())
+            .map(|state| &state.state_id)
+            .collect();
+        loop {
+            let before = live.len();
+            for state in self.states.values() {
+                if state.transitions().any(|(_, target)| live.contains(target)) {
+                    live.insert(&state.state_id);
+                }
+            }
+            if live.len() == before {
+                break;
+            }
+        }
+        self.states.keys().filter(|state_id| !live.contains(state_id)).cloned().collect()
+    }
+}
+
+
+// Writes one DOT edge per (source, target) pair, labelled with all of its symbols sorted and
+// comma-separated, so that a large alphabet does not turn into a bundle of parallel arrows.
+fn write_dot_edges(dot: &mut String, edges: impl IntoIterator<Item = (String, String, String)>) {
+    write_dot_edges_with(dot, edges, "");
+}
+
+// The same, with `attributes` appended to every edge's attribute list.
+fn write_dot_edges_with(dot: &mut String, edges: impl IntoIterator<Item = (String, String, String)>, attributes: &str) {
+    let mut merged: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+    for (source, target, label) in edges {
+        merged.entry((source, target)).or_default().push(label);
+    }
+    for ((source, target), mut labels) in merged {
+        labels.sort();
+        dot.push_str(&format!("    {:?} -> {:?} [label = {:?}{}];\n", source, target, labels.join(", "), attributes));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::DefaultHasher;
+
+    // A trie over `words`, each state named by the prefix that reaches it.
+    fn finite(words: &[&str]) -> Automaton<String, char> {
+        let mut automaton = Automaton::new(DfaState::new(String::new(), words.contains(&"")));
+        for word in words {
+            let mut prefix = String::new();
+            for symbol in word.chars() {
+                let from = DfaState::new(prefix.clone(), words.contains(&prefix.as_str()));
+                prefix.push(symbol);
+                let to = DfaState::new(prefix.clone(), words.contains(&prefix.as_str()));
+                automaton.add_transition(&from, &to, &symbol);
+            }
+        }
+        automaton
+    }
+
+    fn accepts(automaton: &Automaton<Vec<usize>, char>, word: &str) -> bool {
+        automaton.accepts(&word.chars().collect::<Vec<_>>())
+    }
+
+    fn hash_of<A: Hash>(value: &A) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // A counter modulo 16 on `a` that resets on `b`, with its states and edges added in
+    // ascending or descending order.
+    fn counter(descending: bool) -> Automaton<usize, char> {
+        let mut edges: Vec<(usize, usize, char)> = (0..16).flat_map(|count| [(count, (count + 1) % 16, 'a'), (count, 0, 'b')]).collect();
+        if descending {
+            edges.reverse();
+        }
+        let mut automaton = Automaton::new(DfaState::new(0, true));
+        for (from, to, label) in edges {
+            automaton.add_transition(&DfaState::new(from, from == 0), &DfaState::new(to, to == 0), &label);
+        }
+        automaton
+    }
+
+    #[test]
+    fn hash_ignores_insertion_order() {
+        let ascending = counter(false);
+        let descending = counter(true);
+        assert_eq!(ascending, descending);
+        assert_eq!(hash_of(&ascending), hash_of(&descending));
+
+        let mut other = counter(false);
+        other.add_transition(&DfaState::new(3, false), &DfaState::new(3, false), &'b');
+        let seen: HashSet<Automaton<usize, char>> = [ascending, descending, other].into_iter().collect();
+        assert_eq!(seen.len(), 2);
+    }
+
+    #[test]
+    fn canonical_hash_is_fixed() {
+        let mut fnv = Fnv1a::default();
+        fnv.write(b"a");
+        assert_eq!(fnv.finish(), 0xaf63_dc4c_8601_ec8c);
+
+        // Other ids, same language, same hash; and the value itself is pinned, since stored
+        // hashes have to stay valid whichever Rust built them.
+        let original = counter(false);
+        let renamed_state = |id: &usize| DfaState::new(id * 7 + 3, *id == 0);
+        let mut renamed = Automaton::new(renamed_state(&0));
+        for (id, state) in original.get_states() {
+            for (label, target) in state.transitions() {
+                renamed.add_transition(&renamed_state(id), &renamed_state(target), label);
+            }
+        }
+        assert_eq!(renamed.canonical_hash(), original.canonical_hash());
+        assert_eq!(original.canonical_hash(), 12652378599802911780);
+    }
+
+    #[test]
+    fn concat_with_the_empty_word_on_either_side() {
+        let optional_a = finite(&["", "a"]);
+        let b = finite(&["b"]);
+
+        let left = optional_a.concat(&b);
+        assert!(accepts(&left, "b"));
+        assert!(accepts(&left, "ab"));
+        assert!(!accepts(&left, ""));
+        assert!(!accepts(&left, "a"));
+
+        let right = b.concat(&optional_a);
+        assert!(accepts(&right, "b"));
+        assert!(accepts(&right, "ba"));
+        assert!(!accepts(&right, ""));
+        assert!(!accepts(&right, "a"));
+    }
+
+    #[test]
+    fn concat_of_two_empty_words_is_the_empty_word() {
+        let epsilon = finite(&[""]);
+        let both = epsilon.concat(&epsilon);
+        assert!(accepts(&both, ""));
+        assert!(!accepts(&both, "a"));
+    }
+
+    #[test]
+    fn concat_with_the_empty_language_is_empty() {
+        let nothing = finite(&[]);
+        let optional_a = finite(&["", "a"]);
+        assert!(optional_a.concat(&nothing).words_up_to(3).is_empty());
+        assert!(nothing.concat(&optional_a).words_up_to(3).is_empty());
+    }
+
+    #[test]
+    fn star_accepts_the_empty_word_only_once_looped() {
+        // `(aa)*ab`, looping from `a` back to the start: the old initial state is revisited by
+        // `aa`, which must not count as a completed word.
+        let mut looping = finite(&["ab"]);
+        looping.add_transition(&DfaState::new("a".to_string(), false), &DfaState::new(String::new(), false), &'a');
+
+        let star = looping.star();
+        assert!(accepts(&star, ""));
+        assert!(accepts(&star, "ab"));
+        assert!(accepts(&star, "abaaab"));
+        assert!(!accepts(&star, "aa"));
+        assert!(!accepts(&star, "aba"));
+    }
+
+    #[test]
+    fn star_of_the_empty_word_and_of_nothing() {
+        for words in [&[""][..], &[]] {
+            let star = finite(words).star();
+            assert!(accepts(&star, ""));
+            assert!(!accepts(&star, "a"));
+        }
+    }
+
+    // Every word over `symbols` of at most `max_len` symbols.
+    fn all_words(symbols: &[char], max_len: usize) -> Vec<Vec<char>> {
+        let mut words = vec![Vec::new()];
+        let mut layer = vec![Vec::new()];
+        for _ in 0..max_len {
+            layer = layer.iter()
+                .flat_map(|word: &Vec<char>| symbols.iter().map(move |symbol| [word.as_slice(), &[*symbol]].concat()))
+                .collect();
+            words.extend(layer.iter().cloned());
+        }
+        words
+    }
+
+    #[test]
+    fn complete_adds_a_sink_and_keeps_the_language() {
+        let alphabet = Alphabet::new(['a', 'b', 'c']);
+        let partial = finite(&["", "ab"]);
+        assert!(!partial.is_total(&alphabet));
+
+        let complete = partial.complete(&alphabet);
+        assert!(complete.is_total(&alphabet));
+        assert_eq!(complete.state_count(), partial.state_count() + 1);
+        for word in all_words(&['a', 'b', 'c'], 4) {
+            assert_eq!(complete.accepts(&word), partial.accepts(&word), "{:?}", word);
+        }
+        // Already total: no second sink.
+        assert_eq!(complete.complete(&alphabet).state_count(), complete.state_count());
+    }
+
+    #[test]
+    fn missing_transitions_come_in_state_then_alphabet_order() {
+        let alphabet = Alphabet::new(['c', 'b', 'a']);
+        let missing: Vec<(String, char)> = vec![
+            ("".into(), 'c'), ("".into(), 'b'),
+            ("a".into(), 'c'), ("a".into(), 'a'),
+            ("ab".into(), 'c'), ("ab".into(), 'b'), ("ab".into(), 'a'),
+        ];
+        // The states sit in a `HashMap`, so a fresh automaton each time varies its order.
+        for _ in 0..8 {
+            assert_eq!(finite(&["", "ab"]).missing_transitions(&alphabet), missing);
+        }
+    }
+
+    #[test]
+    fn complement_flips_every_word_over_the_alphabet() {
+        let alphabet = Alphabet::new(['a', 'b', 'c']);
+        let partial = finite(&["", "ab"]);
+        let complement = partial.complement(&alphabet);
+        for word in all_words(&['a', 'b', 'c'], 4) {
+            assert_eq!(complement.accepts(&word), !partial.accepts(&word), "{:?}", word);
+        }
+        assert!(complement.complement(&alphabet).equivalent(&partial));
+        // Outside the alphabet both reject.
+        assert!(!partial.accepts(&['d']));
+        assert!(!complement.accepts(&['d']));
+    }
 }
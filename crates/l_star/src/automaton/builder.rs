@@ -0,0 +1,196 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+use super::{Automaton, DfaState};
+
+
+/// Builds an automaton over named states by hand, for reference automata in tests and examples,
+/// e.g. `AutomatonBuilder::new().state("q0").initial().accepting().transition("q0", 'a', "q0")`.
+/// `initial` and `accepting` apply to the state most recently declared with `state`. Mistakes
+/// are reported by `build` rather than panicking on the spot.
+#[derive(Clone, Debug)]
+pub struct AutomatonBuilder<L> {
+    states: Vec<(String, bool)>,
+    initial_states: Vec<String>,
+    transitions: Vec<(String, L, String)>,
+    error: Option<BuildError>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    NoInitialState,
+    MultipleInitialStates(String, String),
+    DuplicateState(String),
+    /// A transition, or the initial or accepting states, mention a state that does not exist.
+    UnknownState(String),
+    /// Two transitions leave `state` on the same label; `label` is its `Debug` form.
+    DuplicateTransition { state: String, label: String },
+    /// `initial` or `accepting` was called before any `state`.
+    NoCurrentState,
+    /// Every violation found, when there is more than one.
+    Multiple(Vec<BuildError>),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::NoInitialState => write!(f, "no state was marked initial"),
+            BuildError::MultipleInitialStates(first, second) => write!(f, "both {:?} and {:?} are marked initial", first, second),
+            BuildError::DuplicateState(state) => write!(f, "state {:?} is declared twice", state),
+            BuildError::UnknownState(state) => write!(f, "state {:?} is referenced but never declared", state),
+            BuildError::DuplicateTransition { state, label } => write!(f, "state {:?} has two transitions on {}", state, label),
+            BuildError::NoCurrentState => write!(f, "initial() or accepting() called before any state()"),
+            BuildError::Multiple(errors) => {
+                let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(f, "{}", messages.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl<L> Default for AutomatonBuilder<L> {
+    fn default() -> Self {
+        AutomatonBuilder {
+            states: Vec::new(),
+            initial_states: Vec::new(),
+            transitions: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+impl<L: Eq + Hash + Clone + Debug> AutomatonBuilder<L> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a non-accepting state and makes it the target of `initial` and `accepting`.
+    pub fn state(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if self.states.iter().any(|(existing, _)| *existing == name) {
+            self.fail(BuildError::DuplicateState(name.clone()));
+        }
+        self.states.push((name, false));
+        self
+    }
+
+    pub fn initial(mut self) -> Self {
+        match self.states.last() {
+            Some((name, _)) => self.initial_states.push(name.clone()),
+            None => self.fail(BuildError::NoCurrentState),
+        }
+        self
+    }
+
+    pub fn accepting(mut self) -> Self {
+        match self.states.last_mut() {
+            Some((_, is_accepting)) => *is_accepting = true,
+            None => self.fail(BuildError::NoCurrentState),
+        }
+        self
+    }
+
+    pub fn transition(mut self, from: impl Into<String>, label: L, to: impl Into<String>) -> Self {
+        self.transitions.push((from.into(), label, to.into()));
+        self
+    }
+
+    pub fn build(self) -> Result<Automaton<String, L>, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let initial = match self.initial_states.as_slice() {
+            [] => return Err(BuildError::NoInitialState),
+            [initial] => initial.clone(),
+            [first, second, ..] => return Err(BuildError::MultipleInitialStates(first.clone(), second.clone())),
+        };
+
+        let accepting: HashSet<&String> = self.states.iter().filter(|(_, is_accepting)| *is_accepting).map(|(name, _)| name).collect();
+        let state = |name: &String| DfaState::new(name.clone(), accepting.contains(name));
+
+        let mut automaton = Automaton::new(state(&initial));
+        for (name, _) in &self.states {
+            automaton.add_state(state(name));
+        }
+
+        let mut edges: HashSet<(&String, &L)> = HashSet::new();
+        for (from, label, to) in &self.transitions {
+            for name in [from, to] {
+                if automaton.get_state(name).is_none() {
+                    return Err(BuildError::UnknownState(name.clone()));
+                }
+            }
+            if !edges.insert((from, label)) {
+                return Err(BuildError::DuplicateTransition { state: from.clone(), label: format!("{:?}", label) });
+            }
+            automaton.add_transition(&state(from), &state(to), label);
+        }
+        Ok(automaton)
+    }
+
+    // Keeps the first mistake, which is usually the one that explains the rest.
+    fn fail(&mut self, error: BuildError) {
+        self.error.get_or_insert(error);
+    }
+}
+
+
+impl<L: Eq + Hash + Clone + Debug> Automaton<usize, L> {
+
+    /// Builds an automaton from `(from, label, to)` rows. The states are the ids mentioned in
+    /// `table`, plus `initial` when `table` is empty; an initial or accepting id outside of
+    /// them, or two rows with the same `(from, label)`, is an error. All violations are
+    /// reported together.
+    pub fn from_table(initial: usize, accepting: &[usize], table: &[(usize, L, usize)]) -> Result<Automaton<usize, L>, BuildError> {
+        let mut states: BTreeSet<usize> = table.iter().flat_map(|(from, _, to)| [*from, *to]).collect();
+        let mut errors = Vec::new();
+        if states.is_empty() {
+            states.insert(initial);
+        } else if !states.contains(&initial) {
+            errors.push(BuildError::UnknownState(initial.to_string()));
+        }
+        for state_id in accepting {
+            if !states.contains(state_id) {
+                errors.push(BuildError::UnknownState(state_id.to_string()));
+            }
+        }
+
+        let mut edges: HashSet<(usize, &L)> = HashSet::new();
+        for (from, label, _) in table {
+            if !edges.insert((*from, label)) {
+                errors.push(BuildError::DuplicateTransition { state: from.to_string(), label: format!("{:?}", label) });
+            }
+        }
+
+        match errors.len() {
+            0 => {}
+            1 => return Err(errors.remove(0)),
+            _ => return Err(BuildError::Multiple(errors)),
+        }
+
+        let state = |state_id: usize| DfaState::new(state_id, accepting.contains(&state_id));
+        let mut automaton = Automaton::new(state(initial));
+        for state_id in states {
+            automaton.add_state(state(state_id));
+        }
+        for (from, label, to) in table {
+            automaton.add_transition(&state(*from), &state(*to), label);
+        }
+        Ok(automaton)
+    }
+    /// The automaton accepting exactly `word`, with states 0 to `word.len()`. A starting point
+    /// for building reference automata with `concat`, `star`, `union` and friends.
+    pub fn literal(word: &[L]) -> Automaton<usize, L> {
+        let state = |state_id: usize| DfaState::new(state_id, state_id == word.len());
+        let mut automaton = Automaton::new(state(0));
+        for (index, label) in word.iter().enumerate() {
+            automaton.add_state(state(index + 1));
+            automaton.add_transition(&state(index), &state(index + 1), label);
+        }
+        automaton
+    }
+}
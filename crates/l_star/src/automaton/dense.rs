@@ -0,0 +1,582 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use super::{Automaton, DfaState};
+use crate::alphabet::Alphabet;
+
+
+/// A compact DFA over `usize` states: one row of `Option<u32>` targets per state, indexed by
+/// symbol position in the alphabet. A `None` target is a missing transition, which rejects.
+///
+/// The set-based `Automaton` is convenient to build and inspect; the algorithms that walk every
+/// state and symbol (minimization, products, equivalence) run on this form instead.
+#[derive(Clone, Debug)]
+pub struct DenseAutomaton<L> {
+    alphabet: Alphabet<L>,
+    transitions: Vec<Vec<Option<u32>>>,
+    accepting: Vec<bool>,
+    initial_state: usize,
+}
+
+fn dense_id(state: usize) -> u32 {
+    u32::try_from(state).expect("dense automata are limited to u32::MAX states")
+}
+
+impl<L: Eq + Hash + Clone> DenseAutomaton<L> {
+
+    /// Converts `automaton`, indexing symbols by `alphabet`. Labels missing from `alphabet` are
+    /// appended to it. The initial state becomes state 0.
+    pub fn from_automaton<StateId: Eq + Hash + Clone>(automaton: &Automaton<StateId, L>, alphabet: &Alphabet<L>) -> Self {
+        let mut alphabet = alphabet.clone();
+        let mut ids: HashMap<&StateId, usize> = HashMap::from([(&automaton.initial_state, 0)]);
+        for state_id in automaton.states.keys() {
+            let next_id = ids.len();
+            ids.entry(state_id).or_insert(next_id);
+        }
+
+        let mut transitions = vec![Vec::new(); ids.len()];
+        let mut accepting = vec![false; ids.len()];
+        for (state_id, state) in &automaton.states {
+            let id = ids[state_id];
+            accepting[id] = state.is_accepting();
+            for (label, target) in state.transitions() {
+                let symbol = alphabet.insert(label.clone());
+                if transitions[id].len() <= symbol {
+                    transitions[id].resize(symbol + 1, None);
+                }
+                transitions[id][symbol] = Some(dense_id(ids[target]));
+            }
+        }
+        for row in &mut transitions {
+            row.resize(alphabet.len(), None);
+        }
+
+        DenseAutomaton {
+            alphabet,
+            transitions,
+            accepting,
+            initial_state: 0,
+        }
+    }
+
+    pub fn to_automaton(&self) -> Automaton<usize, L> {
+        let state = |id: usize| DfaState::new(id, self.accepting[id]);
+
+        let mut automaton = Automaton::new(state(self.initial_state));
+        for id in 0..self.state_count() {
+            automaton.add_state(state(id));
+        }
+        for (id, row) in self.transitions.iter().enumerate() {
+            for (symbol, target) in row.iter().enumerate() {
+                if let Some(target) = target {
+                    automaton.add_transition(&state(id), &state(*target as usize), self.alphabet.symbol(symbol));
+                }
+            }
+        }
+        automaton
+    }
+
+    pub fn alphabet(&self) -> &Alphabet<L> {
+        &self.alphabet
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.accepting.len()
+    }
+
+    pub fn initial_state(&self) -> usize {
+        self.initial_state
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    pub fn successor(&self, state: usize, symbol: usize) -> Option<usize> {
+        self.transitions[state][symbol].map(|target| target as usize)
+    }
+
+    /// The same language with the implicit sink made a real state: every missing transition
+    /// goes to one added rejecting state that loops on every symbol. Nothing is added if no
+    /// transition is missing.
+    pub fn complete(&self) -> Self {
+        let mut complete = self.clone();
+        if complete.transitions.iter().flatten().all(Option::is_some) {
+            return complete;
+        }
+        let sink = dense_id(complete.state_count());
+        for target in complete.transitions.iter_mut().flatten() {
+            target.get_or_insert(sink);
+        }
+        complete.transitions.push(vec![Some(sink); complete.alphabet.len()]);
+        complete.accepting.push(false);
+        complete
+    }
+
+    /// The complement over our alphabet: `complete`, with accepting and rejecting swapped.
+    pub fn complement(&self) -> Self {
+        let mut complement = self.complete();
+        complement.accepting.iter_mut().for_each(|accepting| *accepting = !*accepting);
+        complement
+    }
+
+    pub fn accepts(&self, word: &[L]) -> bool {
+        let mut state = self.initial_state;
+        for label in word {
+            match self.alphabet.index_of(label).and_then(|symbol| self.successor(state, symbol)) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        self.accepting[state]
+    }
+
+    // The same automaton re-indexed over `alphabet`, which must contain ours.
+    fn over(&self, alphabet: &Alphabet<L>) -> Self {
+        let transitions = self.transitions.iter()
+            .map(|row| alphabet.iter()
+                .map(|label| self.alphabet.index_of(label).and_then(|symbol| row[symbol]))
+                .collect())
+            .collect();
+
+        DenseAutomaton {
+            alphabet: alphabet.clone(),
+            transitions,
+            accepting: self.accepting.clone(),
+            initial_state: self.initial_state,
+        }
+    }
+
+    fn with_shared_alphabet(&self, other: &Self) -> (Self, Self) {
+        let alphabet = Alphabet::new(self.alphabet.iter().chain(other.alphabet.iter()).cloned());
+        (self.over(&alphabet), other.over(&alphabet))
+    }
+
+    fn reachable(&self) -> Vec<usize> {
+        let mut seen = vec![false; self.state_count()];
+        let mut order = vec![self.initial_state];
+        seen[self.initial_state] = true;
+
+        let mut index = 0;
+        while index < order.len() {
+            for target in self.transitions[order[index]].iter().flatten() {
+                if !seen[*target as usize] {
+                    seen[*target as usize] = true;
+                    order.push(*target as usize);
+                }
+            }
+            index += 1;
+        }
+        order
+    }
+
+    // States from which some accepting state can be reached.
+    fn live(&self) -> Vec<bool> {
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); self.state_count()];
+        for (state, row) in self.transitions.iter().enumerate() {
+            for target in row.iter().flatten() {
+                predecessors[*target as usize].push(state);
+            }
+        }
+
+        let mut live = self.accepting.clone();
+        let mut stack: Vec<usize> = (0..self.state_count()).filter(|state| live[*state]).collect();
+        while let Some(state) = stack.pop() {
+            for predecessor in &predecessors[state] {
+                if !live[*predecessor] {
+                    live[*predecessor] = true;
+                    stack.push(*predecessor);
+                }
+            }
+        }
+        live
+    }
+
+    /*
+    Minimization of a partial DFA: drop unreachable states and states that cannot reach an
+    accepting one (transitions into them become missing, i.e. the implicit sink), then refine
+    the partition {accepting, rejecting} until every block agrees on the block reached under
+    each symbol. Blocks are numbered in breadth-first order from the initial state.
+     */
+    pub fn minimize(&self) -> Self {
+        let live = self.live();
+        let states: Vec<usize> = self.reachable().into_iter().filter(|state| live[*state]).collect();
+
+        if states.is_empty() {
+            return DenseAutomaton {
+                alphabet: self.alphabet.clone(),
+                transitions: vec![vec![None; self.alphabet.len()]],
+                accepting: vec![false],
+                initial_state: 0,
+            };
+        }
+
+        let target = |state: usize, symbol: usize| self.successor(state, symbol).filter(|target| live[*target]);
+
+        let mut block_of: HashMap<usize, usize> = states.iter().map(|state| (*state, self.accepting[*state] as usize)).collect();
+        let mut block_count = states.iter().map(|state| self.accepting[*state]).collect::<HashSet<_>>().len();
+        loop {
+            let mut signatures: HashMap<(usize, Vec<Option<usize>>), usize> = HashMap::new();
+            let mut refined: HashMap<usize, usize> = HashMap::new();
+            for state in &states {
+                let successors = (0..self.alphabet.len())
+                    .map(|symbol| target(*state, symbol).map(|target| block_of[&target]))
+                    .collect();
+                let next_block = signatures.len();
+                let block = *signatures.entry((block_of[state], successors)).or_insert(next_block);
+                refined.insert(*state, block);
+            }
+
+            let stable = signatures.len() == block_count;
+            block_count = signatures.len();
+            block_of = refined;
+            if stable {
+                break;
+            }
+        }
+
+        // `states` is in breadth-first order, so numbering blocks by first member keeps that order.
+        let mut numbering: HashMap<usize, usize> = HashMap::new();
+        let mut representatives: Vec<usize> = Vec::new();
+        for state in &states {
+            let next_id = numbering.len();
+            if let Entry::Vacant(entry) = numbering.entry(block_of[state]) {
+                entry.insert(next_id);
+                representatives.push(*state);
+            }
+        }
+
+        let transitions = representatives.iter()
+            .map(|state| (0..self.alphabet.len())
+                .map(|symbol| target(*state, symbol).map(|target| dense_id(numbering[&block_of[&target]])))
+                .collect())
+            .collect();
+
+        DenseAutomaton {
+            alphabet: self.alphabet.clone(),
+            transitions,
+            accepting: representatives.iter().map(|state| self.accepting[*state]).collect(),
+            initial_state: 0,
+        }
+    }
+
+    /// The synchronous product, accepting a pair of states when `accept(left, right)` holds. A
+    /// missing transition on either side is followed into an implicit rejecting sink.
+    pub fn product(&self, other: &Self, accept: impl Fn(bool, bool) -> bool) -> Self {
+        let (left, right) = self.with_shared_alphabet(other);
+        let sink_accepts = accept(false, false);
+
+        let start = (Some(left.initial_state), Some(right.initial_state));
+        let mut ids: HashMap<(Option<usize>, Option<usize>), usize> = HashMap::from([(start, 0)]);
+        let mut pairs = vec![start];
+        let mut transitions: Vec<Vec<Option<u32>>> = Vec::new();
+        let mut accepting: Vec<bool> = Vec::new();
+
+        let mut index = 0;
+        while index < pairs.len() {
+            let (l, r) = pairs[index];
+            accepting.push(accept(
+                l.is_some_and(|state| left.accepting[state]),
+                r.is_some_and(|state| right.accepting[state]),
+            ));
+
+            let mut row = Vec::with_capacity(left.alphabet.len());
+            for symbol in 0..left.alphabet.len() {
+                let next = (
+                    l.and_then(|state| left.successor(state, symbol)),
+                    r.and_then(|state| right.successor(state, symbol)),
+                );
+                // Both sides in their sinks: only worth a state if the sink pair accepts.
+                if next == (None, None) && !sink_accepts {
+                    row.push(None);
+                    continue;
+                }
+                let next_id = ids.len();
+                let id = *ids.entry(next).or_insert_with(|| {
+                    pairs.push(next);
+                    next_id
+                });
+                row.push(Some(dense_id(id)));
+            }
+            transitions.push(row);
+            index += 1;
+        }
+
+        DenseAutomaton {
+            alphabet: left.alphabet,
+            transitions,
+            accepting,
+            initial_state: 0,
+        }
+    }
+
+    /*
+    Hopcroft and Karp's near-linear equivalence test: merge the two initial states, and whenever
+    two states are merged also merge their successors under every symbol. The automata are
+    equivalent iff no merged class mixes accepting and rejecting states. States of `other` are
+    offset by our state count, and each side's implicit sink is one extra rejecting state.
+     */
+    pub fn equivalent(&self, other: &Self) -> bool {
+        let (left, right) = self.with_shared_alphabet(other);
+        let left_sink = left.state_count();
+        let offset = left_sink + 1;
+        let right_sink = offset + right.state_count();
+
+        let successor = |state: usize, symbol: usize| -> usize {
+            if state == left_sink || state == right_sink {
+                state
+            } else if state < offset {
+                left.successor(state, symbol).unwrap_or(left_sink)
+            } else {
+                right.successor(state - offset, symbol).map_or(right_sink, |target| target + offset)
+            }
+        };
+        let accepting = |state: usize| -> bool {
+            if state == left_sink || state == right_sink {
+                false
+            } else if state < offset {
+                left.accepting[state]
+            } else {
+                right.accepting[state - offset]
+            }
+        };
+
+        let mut parent: Vec<usize> = (0..=right_sink).collect();
+        fn find(parent: &mut [usize], mut state: usize) -> usize {
+            while parent[state] != state {
+                parent[state] = parent[parent[state]];
+                state = parent[state];
+            }
+            state
+        }
+
+        let mut pending = vec![(left.initial_state, right.initial_state + offset)];
+        parent[left.initial_state] = right.initial_state + offset;
+        while let Some((p, q)) = pending.pop() {
+            if accepting(p) != accepting(q) {
+                return false;
+            }
+            for symbol in 0..left.alphabet.len() {
+                let (p_next, q_next) = (find(&mut parent, successor(p, symbol)), find(&mut parent, successor(q, symbol)));
+                if p_next != q_next {
+                    parent[p_next] = q_next;
+                    pending.push((successor(p, symbol), successor(q, symbol)));
+                }
+            }
+        }
+        true
+    }
+
+    /// A shortest word accepted by exactly one of the two automata, or `None` if they are
+    /// equivalent.
+    pub fn distinguishing_word(&self, other: &Self) -> Option<Vec<L>> {
+        self.product(other, |left, right| left != right).shortest_accepted()
+    }
+
+    /// The shortest access word of every reachable state, breadth-first in alphabet order, so
+    /// the empty word comes first.
+    pub fn state_cover(&self) -> Vec<Vec<L>> {
+        let mut words: Vec<Option<Vec<L>>> = vec![None; self.state_count()];
+        words[self.initial_state] = Some(Vec::new());
+        let mut order = vec![self.initial_state];
+        let mut index = 0;
+        while index < order.len() {
+            let state = order[index];
+            for symbol in 0..self.alphabet.len() {
+                if let Some(next) = self.successor(state, symbol)
+                    && words[next].is_none() {
+                        let mut word = words[state].clone().unwrap_or_default();
+                        word.push(self.alphabet.symbol(symbol).clone());
+                        words[next] = Some(word);
+                        order.push(next);
+                    }
+            }
+            index += 1;
+        }
+        order.into_iter().filter_map(|state| words[state].take()).collect()
+    }
+
+    /// The state cover followed by each of its words extended by every symbol, so that every
+    /// transition of a reachable state is taken, those into the implicit sink included.
+    pub fn transition_cover(&self) -> Vec<Vec<L>> {
+        let state_cover = self.state_cover();
+        let mut words = state_cover.clone();
+        let mut seen: HashSet<Vec<L>> = state_cover.iter().cloned().collect();
+        for access in &state_cover {
+            for symbol in self.alphabet.iter() {
+                let mut word = access.clone();
+                word.push(symbol.clone());
+                if seen.insert(word.clone()) {
+                    words.push(word);
+                }
+            }
+        }
+        words
+    }
+
+    /*
+    Pairs of reachable states are visited in breadth-first order, the implicit sink last when
+    some transition is missing. A pair no word of W tells apart yet gets its shortest
+    distinguishing word added; pairs that cannot be told apart (the automaton is not minimal)
+    are skipped.
+     */
+    /// A characterizing set W: for every two reachable states with different futures, some
+    /// word in W is accepted from exactly one of them.
+    pub fn characterizing_set(&self) -> Vec<Vec<L>> {
+        let mut states: Vec<Option<usize>> = self.reachable().into_iter().map(Some).collect();
+        if states.iter().flatten().any(|state| self.transitions[*state].contains(&None)) {
+            states.push(None);
+        }
+
+        let mut set: Vec<Vec<L>> = Vec::new();
+        for (index, p) in states.iter().enumerate() {
+            for q in &states[index + 1..] {
+                if set.iter().any(|word| self.accepts_from(*p, word) != self.accepts_from(*q, word)) {
+                    continue;
+                }
+                if let Some(word) = self.starting_at(*p).distinguishing_word(&self.starting_at(*q)) {
+                    set.push(word);
+                }
+            }
+        }
+        set
+    }
+
+    /// Splits a characterizing set into one identification set per state: the words of
+    /// `characterizing_set` that tell state `i` apart from every other reachable state, the
+    /// implicit sink included, at index `i`. Unreachable states get an empty set.
+    pub fn identification_sets(&self, characterizing_set: &[Vec<L>]) -> Vec<Vec<Vec<L>>> {
+        let mut states: Vec<Option<usize>> = self.reachable().into_iter().map(Some).collect();
+        states.push(None);
+
+        let mut sets: Vec<Vec<Vec<L>>> = vec![Vec::new(); self.state_count()];
+        for p in states.iter().flatten() {
+            let set = &mut sets[*p];
+            for q in &states {
+                if *q == Some(*p) || set.iter().any(|word| self.accepts_from(Some(*p), word) != self.accepts_from(*q, word)) {
+                    continue;
+                }
+                if let Some(word) = characterizing_set.iter().find(|word| self.accepts_from(Some(*p), word) != self.accepts_from(*q, word)) {
+                    set.push(word.clone());
+                }
+            }
+        }
+        sets
+    }
+
+    /// The state reached on `word`, or `None` once a transition is missing.
+    pub fn run(&self, word: &[L]) -> Option<usize> {
+        let mut state = Some(self.initial_state);
+        for label in word {
+            state = state.and_then(|state| self.alphabet.index_of(label).and_then(|symbol| self.successor(state, symbol)));
+        }
+        state
+    }
+
+    // Whether `word` is accepted starting in `state`, `None` being the implicit sink.
+    fn accepts_from(&self, state: Option<usize>, word: &[L]) -> bool {
+        let mut state = state;
+        for label in word {
+            state = state.and_then(|state| self.alphabet.index_of(label).and_then(|symbol| self.successor(state, symbol)));
+        }
+        state.is_some_and(|state| self.accepting[state])
+    }
+
+    // The same automaton started in `state`; the sink becomes a lone rejecting state.
+    fn starting_at(&self, state: Option<usize>) -> Self {
+        match state {
+            Some(state) => DenseAutomaton { initial_state: state, ..self.clone() },
+            None => DenseAutomaton {
+                alphabet: self.alphabet.clone(),
+                transitions: vec![vec![None; self.alphabet.len()]],
+                accepting: vec![false],
+                initial_state: 0,
+            },
+        }
+    }
+
+    /// Number of accepted words, or `None` if there are infinitely many, i.e. some cycle is
+    /// both reachable and able to reach an accepting state. Counts past `u64::MAX` saturate.
+    pub fn language_size(&self) -> Option<u64> {
+        let live = self.live();
+        let useful: Vec<usize> = self.reachable().into_iter().filter(|state| live[*state]).collect();
+        if useful.is_empty() {
+            return Some(0);
+        }
+        let mut is_useful = vec![false; self.state_count()];
+        for state in &useful {
+            is_useful[*state] = true;
+        }
+        let successors = |state: usize| self.transitions[state].iter().flatten().map(|target| *target as usize).filter(|target| is_useful[*target]);
+
+        // Kahn's algorithm; states left over lie on a cycle.
+        let mut in_degree = vec![0usize; self.state_count()];
+        for state in &useful {
+            for target in successors(*state) {
+                in_degree[target] += 1;
+            }
+        }
+        let mut order: Vec<usize> = useful.iter().copied().filter(|state| in_degree[*state] == 0).collect();
+        let mut index = 0;
+        while index < order.len() {
+            for target in successors(order[index]) {
+                in_degree[target] -= 1;
+                if in_degree[target] == 0 {
+                    order.push(target);
+                }
+            }
+            index += 1;
+        }
+        if order.len() < useful.len() {
+            return None;
+        }
+
+        // Words accepted from each state, filled in from the sinks of the DAG backwards.
+        let mut count = vec![0u64; self.state_count()];
+        for state in order.into_iter().rev() {
+            count[state] = successors(state).fold(u64::from(self.accepting[state]), |total, target| total.saturating_add(count[target]));
+        }
+        Some(count[self.initial_state])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.reachable().iter().any(|state| self.accepting[*state])
+    }
+
+    /// A shortest accepted word, found breadth-first, or `None` if the language is empty.
+    pub fn shortest_accepted(&self) -> Option<Vec<L>> {
+        let mut parent: Vec<Option<(usize, usize)>> = vec![None; self.state_count()];
+        let mut seen = vec![false; self.state_count()];
+        let mut queue = VecDeque::from([self.initial_state]);
+        seen[self.initial_state] = true;
+
+        while let Some(state) = queue.pop_front() {
+            if self.accepting[state] {
+                let mut word = Vec::new();
+                let mut current = state;
+                while let Some((previous, symbol)) = parent[current] {
+                    word.push(self.alphabet.symbol(symbol).clone());
+                    current = previous;
+                }
+                word.reverse();
+                return Some(word);
+            }
+            for symbol in 0..self.alphabet.len() {
+                if let Some(next) = self.successor(state, symbol)
+                    && !seen[next] {
+                        seen[next] = true;
+                        parent[next] = Some((state, symbol));
+                        queue.push_back(next);
+                    }
+            }
+        }
+        None
+    }
+}
+
+impl<StateId: Eq + Hash + Clone, L: Eq + Hash + Clone> From<&Automaton<StateId, L>> for DenseAutomaton<L> {
+    fn from(automaton: &Automaton<StateId, L>) -> Self {
+        DenseAutomaton::from_automaton(automaton, &Alphabet::new([]))
+    }
+}
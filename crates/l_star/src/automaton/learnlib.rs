@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+use super::{Automaton, AutomatonBuilder, BuildError};
+
+
+/*
+The DOT flavour written and read by LearnLib/AutomataLib:
+
+    digraph g {
+
+        s0 [shape="circle" label="0"];
+        s1 [shape="doublecircle" label="1"];
+        s0 -> s1 [label="a"];
+
+    __start0 [label="" shape="none" width="0" height="0"];
+    __start0 -> s0;
+
+    }
+
+States are `s<n>`, accepting ones drawn as double circles, one edge per symbol, and the
+initial state is the target of the edge from the invisible `__start0` node.
+ */
+
+#[derive(Debug)]
+pub enum LearnLibDotError {
+    /// A statement on `line` (1-based) that could not be parsed.
+    Syntax { line: usize, text: String },
+    /// The statements parse but do not describe a DFA.
+    Build(BuildError),
+}
+
+impl Display for LearnLibDotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LearnLibDotError::Syntax { line, text } => write!(f, "line {}: cannot parse {:?}", line, text),
+            LearnLibDotError::Build(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for LearnLibDotError {}
+
+impl From<BuildError> for LearnLibDotError {
+    fn from(err: BuildError) -> Self {
+        LearnLibDotError::Build(err)
+    }
+}
+
+
+impl<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone + Display> Automaton<StateId, TransitionLabel> {
+
+    /// DOT in the dialect LearnLib and AutomataLib read, with states renamed `s0`, `s1`, ...
+    /// breadth-first from the initial state, following edges in label order. Labels are
+    /// written with `Display`.
+    pub fn to_learnlib_dot(&self) -> String {
+        let mut order: Vec<&StateId> = vec![&self.initial_state];
+        let mut ids: HashMap<&StateId, usize> = HashMap::from([(&self.initial_state, 0)]);
+        let mut index = 0;
+        while index < order.len() {
+            let mut edges: Vec<(String, &StateId)> = self.states[order[index]].transitions()
+                .map(|(label, target)| (label.to_string(), target))
+                .collect();
+            edges.sort_by(|a, b| a.0.cmp(&b.0));
+            for (_, target) in edges {
+                if !ids.contains_key(target) {
+                    ids.insert(target, order.len());
+                    order.push(target);
+                }
+            }
+            index += 1;
+        }
+        // Unreachable states go last, so that no state is silently dropped.
+        for state_id in self.states.keys() {
+            if !ids.contains_key(state_id) {
+                ids.insert(state_id, order.len());
+                order.push(state_id);
+            }
+        }
+        let states: Vec<(&StateId, usize)> = order.iter().map(|state_id| (*state_id, ids[state_id])).collect();
+
+        let mut dot = String::from("digraph g {\n\n");
+        for (state_id, id) in &states {
+            let shape = if self.states[*state_id].is_accepting() { "doublecircle" } else { "circle" };
+            dot.push_str(&format!("\ts{} [shape=\"{}\" label=\"{}\"];\n", id, shape, id));
+        }
+        for (state_id, id) in &states {
+            let mut edges: Vec<(usize, String)> = self.states[*state_id].transitions()
+                .map(|(label, target)| (ids[&target], label.to_string()))
+                .collect();
+            edges.sort();
+            for (target, label) in edges {
+                dot.push_str(&format!("\ts{} -> s{} [label={}];\n", id, target, quote(&label)));
+            }
+        }
+        dot.push_str("\n__start0 [label=\"\" shape=\"none\" width=\"0\" height=\"0\"];\n__start0 -> s0;\n\n}\n");
+        dot
+    }
+}
+
+impl Automaton<String, String> {
+
+    /// Reads a DFA written by LearnLib/AutomataLib (or `to_learnlib_dot`). State ids are the
+    /// DOT node names and labels are taken verbatim, with HTML-like `<...>` labels unwrapped.
+    pub fn from_learnlib_dot(dot: &str) -> Result<Self, LearnLibDotError> {
+        let mut nodes: Vec<String> = Vec::new();
+        let mut accepting: HashMap<String, bool> = HashMap::new();
+        let mut edges: Vec<(String, String, String)> = Vec::new();
+        let mut initial: Option<String> = None;
+
+        for (number, line) in dot.lines().enumerate() {
+            for statement in split_statements(line) {
+                if statement.starts_with("//") || statement.starts_with('#') || statement == "{" || statement == "}" || statement.starts_with("digraph") {
+                    continue;
+                }
+                let syntax_error = || LearnLibDotError::Syntax { line: number + 1, text: statement.to_string() };
+
+                let (head, attributes) = match statement.find('[') {
+                    Some(open) => {
+                        let close = statement.rfind(']').filter(|close| *close > open).ok_or_else(syntax_error)?;
+                        (statement[..open].trim(), parse_attributes(&statement[open + 1..close]).ok_or_else(syntax_error)?)
+                    }
+                    None => (statement, HashMap::new()),
+                };
+
+                if let Some((from, to)) = head.split_once("->") {
+                    let (from, to) = (unquote(from.trim()), unquote(to.trim()));
+                    if from.is_empty() || to.is_empty() {
+                        return Err(syntax_error());
+                    }
+                    if from.starts_with("__start") {
+                        initial = Some(to);
+                    } else {
+                        let label = attributes.get("label").cloned().ok_or_else(syntax_error)?;
+                        edges.push((from, label, to));
+                    }
+                } else if head.contains('=') || ["graph", "node", "edge"].contains(&head) {
+                    // Graph-wide attributes and defaults carry nothing for the automaton.
+                } else {
+                    let name = unquote(head);
+                    if name.is_empty() {
+                        return Err(syntax_error());
+                    }
+                    if name.starts_with("__start") {
+                        continue;
+                    }
+                    let is_accepting = attributes.get("shape").is_some_and(|shape| shape == "doublecircle");
+                    if accepting.insert(name.clone(), is_accepting).is_none() {
+                        nodes.push(name);
+                    }
+                }
+            }
+        }
+
+        // Nodes only mentioned in edges are declared implicitly, as in DOT itself.
+        for (from, _, to) in &edges {
+            for name in [from, to] {
+                if !accepting.contains_key(name) {
+                    accepting.insert(name.clone(), false);
+                    nodes.push(name.clone());
+                }
+            }
+        }
+
+        let mut builder = AutomatonBuilder::new();
+        for name in nodes {
+            builder = builder.state(name.clone());
+            if initial.as_ref() == Some(&name) {
+                builder = builder.initial();
+            }
+            if accepting[&name] {
+                builder = builder.accepting();
+            }
+        }
+        for (from, label, to) in edges {
+            builder = builder.transition(from, label, to);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+
+// Splits a line at the `;` that are not inside a quoted or `<...>` value.
+fn split_statements(line: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let (mut start, mut in_quotes, mut escaped, mut depth) = (0, false, false, 0);
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' if !escaped && depth == 0 => in_quotes = !in_quotes,
+            '<' if !in_quotes => depth += 1,
+            '>' if !in_quotes && depth > 0 => depth -= 1,
+            ';' if !in_quotes && depth == 0 => {
+                statements.push(line[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    statements.push(line[start..].trim());
+    statements.retain(|statement| !statement.is_empty());
+    statements
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn unquote(text: &str) -> String {
+    match text.strip_prefix('"').and_then(|text| text.strip_suffix('"')) {
+        Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => text.to_string(),
+    }
+}
+
+// Parses `key="value" key=<html> key=bare`, separated by spaces or commas.
+fn parse_attributes(text: &str) -> Option<HashMap<String, String>> {
+    let mut attributes = HashMap::new();
+    let mut rest = text.trim_start_matches([' ', ',', '\t']);
+    while !rest.is_empty() {
+        let equals = rest.find('=')?;
+        let key = rest[..equals].trim().to_string();
+        rest = rest[equals + 1..].trim_start();
+
+        let (value, remaining) = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut escaped = false;
+            let end = quoted.char_indices().find(|(_, c)| {
+                let closes = *c == '"' && !escaped;
+                escaped = *c == '\\' && !escaped;
+                closes
+            })?.0;
+            (unquote(&rest[..end + 2]), &quoted[end + 1..])
+        } else if rest.starts_with('<') {
+            let mut depth = 0;
+            let end = rest.char_indices().find(|(_, c)| {
+                match c {
+                    '<' => depth += 1,
+                    '>' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })?.0;
+            (rest[1..end].to_string(), &rest[end + 1..])
+        } else {
+            let end = rest.find([' ', ',', '\t']).unwrap_or(rest.len());
+            (rest[..end].to_string(), &rest[end..])
+        };
+
+        attributes.insert(key, value);
+        rest = remaining.trim_start_matches([' ', ',', '\t']);
+    }
+    Some(attributes)
+}
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use super::write_dot_edges;
+
+
+#[derive(Clone, Debug)]
+pub struct MealyState<StateId, Input, Output> {
+    state_id: StateId,
+    #[deprecated(note = "read through `transition`/`transitions` and modify through `MealyMachine::add_transition`; the field will become private")]
+    pub transitions: HashMap<Input, (StateId, Output)>,
+}
+
+#[allow(deprecated)]
+impl<StateId, Input, Output> MealyState<StateId, Input, Output> {
+    pub fn new(state_id: StateId) -> Self {
+        MealyState {
+            state_id,
+            transitions: HashMap::new(),
+        }
+    }
+
+    pub fn get_state_id(&self) -> &StateId {
+        &self.state_id
+    }
+
+    /// Outgoing edges as `(input, target, output)` triples, in no particular order.
+    pub fn transitions(&self) -> impl Iterator<Item = (&Input, &StateId, &Output)> {
+        self.transitions.iter().map(|(input, (target, output))| (input, target, output))
+    }
+}
+
+#[allow(deprecated)]
+impl<StateId, Input: Eq + Hash, Output> MealyState<StateId, Input, Output> {
+    /// The target and output of the edge on `input`.
+    pub fn transition(&self, input: &Input) -> Option<(&StateId, &Output)> {
+        self.transitions.get(input).map(|(target, output)| (target, output))
+    }
+
+    // Only `MealyMachine` adds edges, so that targets always exist in the machine.
+    fn insert_transition(&mut self, input: Input, target: StateId, output: Output) {
+        self.transitions.insert(input, (target, output));
+    }
+}
+
+
+/// A Mealy machine: every transition carries an output, so running a word yields one output
+/// per input symbol. This is the usual model for request/response protocols.
+#[derive(Clone, Debug)]
+pub struct MealyMachine<StateId: Eq + Hash + Clone + Debug, Input: Eq + Hash + Clone, Output: Clone> {
+    states: HashMap<StateId, MealyState<StateId, Input, Output>>,
+    initial_state: StateId,
+}
+
+impl<StateId, Input, Output> MealyMachine<StateId, Input, Output>
+where
+    StateId: Eq + Hash + Clone + Debug,
+    Input: Eq + Hash + Clone + Debug,
+    Output: Eq + Clone + Debug,
+{
+    pub fn new(initial_state: MealyState<StateId, Input, Output>) -> Self {
+        let mut states = HashMap::new();
+        states.insert(initial_state.state_id.clone(), initial_state.clone());
+
+        MealyMachine {
+            states,
+            initial_state: initial_state.state_id,
+        }
+    }
+
+    pub fn get_states(&self) -> &HashMap<StateId, MealyState<StateId, Input, Output>> {
+        &self.states
+    }
+
+    pub fn add_transition(&mut self, from: &MealyState<StateId, Input, Output>, to: &MealyState<StateId, Input, Output>, input: &Input, output: &Output) {
+        self.add_state(from.clone());
+        self.add_state(to.clone());
+
+        if let Some(from_state) = self.states.get_mut(&from.state_id) {
+            from_state.insert_transition(input.clone(), to.state_id.clone(), output.clone());
+        }
+    }
+
+    pub fn add_state(&mut self, state: MealyState<StateId, Input, Output>) {
+        if !self.states.contains_key(&state.state_id) {
+            self.states.insert(state.state_id.clone(), state);
+        }
+    }
+
+    pub fn get_state(&self, state_id: &StateId) -> Option<&MealyState<StateId, Input, Output>> {
+        self.states.get(state_id)
+    }
+
+    pub fn get_initial_state(&self) -> Option<&MealyState<StateId, Input, Output>> {
+        self.get_state(&self.initial_state)
+    }
+
+    pub fn set_initial_state(&mut self, state: &MealyState<StateId, Input, Output>) {
+        self.initial_state = state.state_id.clone();
+    }
+
+    /// The outputs produced while reading `inputs`. Stops early at the first missing
+    /// transition, so the result is shorter than `inputs` exactly when the run got stuck.
+    pub fn run(&self, inputs: &[Input]) -> Vec<Output> {
+        let mut outputs = Vec::with_capacity(inputs.len());
+        let mut state = self.get_initial_state();
+
+        for input in inputs {
+            match state.and_then(|state| state.transition(input)) {
+                Some((target, output)) => {
+                    outputs.push(output.clone());
+                    state = self.get_state(target);
+                }
+                None => break,
+            }
+        }
+        outputs
+    }
+
+    /// A shortest input sequence on which the two machines produce different outputs, or
+    /// `None` if they are equivalent. A transition present on one side only counts as a
+    /// difference.
+    pub fn distinguishing_word<OtherId: Eq + Hash + Clone + Debug>(&self, other: &MealyMachine<OtherId, Input, Output>) -> Option<Vec<Input>> {
+        let start = (&self.initial_state, &other.initial_state);
+        let mut seen: HashSet<(&StateId, &OtherId)> = HashSet::from([start]);
+        let mut queue: VecDeque<((&StateId, &OtherId), Vec<Input>)> = VecDeque::from([(start, Vec::new())]);
+
+        while let Some(((left, right), word)) = queue.pop_front() {
+            let left = &self.states[left];
+            let right = &other.states[right];
+
+            let inputs: HashSet<&Input> = left.transitions().map(|(input, _, _)| input)
+                .chain(right.transitions().map(|(input, _, _)| input))
+                .collect();
+            for input in inputs {
+                let mut next_word = word.clone();
+                next_word.push(input.clone());
+
+                match (left.transition(input), right.transition(input)) {
+                    (Some((left_target, left_output)), Some((right_target, right_output))) => {
+                        if left_output != right_output {
+                            return Some(next_word);
+                        }
+                        if seen.insert((left_target, right_target)) {
+                            queue.push_back(((left_target, right_target), next_word));
+                        }
+                    }
+                    _ => return Some(next_word),
+                }
+            }
+        }
+        None
+    }
+
+    pub fn is_equivalent<OtherId: Eq + Hash + Clone + Debug>(&self, other: &MealyMachine<OtherId, Input, Output>) -> bool {
+        self.distinguishing_word(other).is_none()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph Mealy {\n");
+
+        let mut names: Vec<String> = self.states.values().map(|state| format!("{:?}", state.state_id)).collect();
+        names.sort();
+        for name in names {
+            dot.push_str(&format!("    {:?};\n", name));
+        }
+
+        dot.push_str(&format!("    __start__ [shape=point];\n    __start__ -> {:?};\n", format!("{:?}", self.initial_state)));
+
+        write_dot_edges(&mut dot, self.states.values().flat_map(|state| {
+            state.transitions().map(|(input, target, output)| {
+                (format!("{:?}", state.state_id), format!("{:?}", target), format!("{:?}/{:?}", input, output))
+            })
+        }));
+
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use super::{write_dot_edges, Automaton, DfaState};
+
+
+// A pair of states walked in lockstep, `None` once a side has run off its transitions.
+type StatePair<'a, Left, Right> = (Option<&'a Left>, Option<&'a Right>);
+
+#[derive(Clone, Debug)]
+pub struct MooreState<StateId, Input, Output> {
+    state_id: StateId,
+    output: Output,
+    #[deprecated(note = "read through `transition`/`transitions` and modify through `MooreMachine::add_transition`; the field will become private")]
+    pub transitions: HashMap<Input, StateId>,
+}
+
+#[allow(deprecated)]
+impl<StateId, Input, Output> MooreState<StateId, Input, Output> {
+    pub fn new(state_id: StateId, output: Output) -> Self {
+        MooreState {
+            state_id,
+            output,
+            transitions: HashMap::new(),
+        }
+    }
+
+    pub fn get_state_id(&self) -> &StateId {
+        &self.state_id
+    }
+
+    pub fn get_output(&self) -> &Output {
+        &self.output
+    }
+
+    pub fn set_output(&mut self, output: Output) {
+        self.output = output;
+    }
+
+    /// Outgoing edges as `(input, target)` pairs, in no particular order.
+    pub fn transitions(&self) -> impl Iterator<Item = (&Input, &StateId)> {
+        self.transitions.iter()
+    }
+}
+
+#[allow(deprecated)]
+impl<StateId, Input: Eq + Hash, Output> MooreState<StateId, Input, Output> {
+    pub fn transition(&self, input: &Input) -> Option<&StateId> {
+        self.transitions.get(input)
+    }
+
+    // Only `MooreMachine` adds edges, so that targets always exist in the machine.
+    fn insert_transition(&mut self, input: Input, target: StateId) {
+        self.transitions.insert(input, target);
+    }
+}
+
+
+/// A Moore machine: like `Automaton`, but every state carries an arbitrary output instead of
+/// an accepting flag, so words can be classified into more than two classes.
+#[derive(Clone, Debug)]
+pub struct MooreMachine<StateId: Eq + Hash + Clone + Debug, Input: Eq + Hash + Clone, Output: Clone> {
+    states: HashMap<StateId, MooreState<StateId, Input, Output>>,
+    initial_state: StateId,
+}
+
+impl<StateId, Input, Output> MooreMachine<StateId, Input, Output>
+where
+    StateId: Eq + Hash + Clone + Debug,
+    Input: Eq + Hash + Clone + Debug,
+    Output: Eq + Hash + Clone + Debug,
+{
+    pub fn new(initial_state: MooreState<StateId, Input, Output>) -> Self {
+        let mut states = HashMap::new();
+        states.insert(initial_state.state_id.clone(), initial_state.clone());
+
+        MooreMachine {
+            states,
+            initial_state: initial_state.state_id,
+        }
+    }
+
+    pub fn get_states(&self) -> &HashMap<StateId, MooreState<StateId, Input, Output>> {
+        &self.states
+    }
+
+    pub fn add_transition(&mut self, from: &MooreState<StateId, Input, Output>, to: &MooreState<StateId, Input, Output>, input: &Input) {
+        self.add_state(from.clone());
+        self.add_state(to.clone());
+
+        if let Some(from_state) = self.states.get_mut(&from.state_id) {
+            from_state.insert_transition(input.clone(), to.state_id.clone());
+        }
+    }
+
+    pub fn add_state(&mut self, state: MooreState<StateId, Input, Output>) {
+        if !self.states.contains_key(&state.state_id) {
+            self.states.insert(state.state_id.clone(), state);
+        }
+    }
+
+    pub fn get_state(&self, state_id: &StateId) -> Option<&MooreState<StateId, Input, Output>> {
+        self.states.get(state_id)
+    }
+
+    pub fn get_initial_state(&self) -> Option<&MooreState<StateId, Input, Output>> {
+        self.get_state(&self.initial_state)
+    }
+
+    pub fn set_initial_state(&mut self, state: &MooreState<StateId, Input, Output>) {
+        self.initial_state = state.state_id.clone();
+    }
+
+    /// The output of the state reached by `word`, or `None` if the word runs into a missing
+    /// transition.
+    pub fn output(&self, word: &[Input]) -> Option<&Output> {
+        let mut state = self.get_initial_state()?;
+        for input in word {
+            state = self.get_state(state.transition(input)?)?;
+        }
+        Some(&state.output)
+    }
+
+    /// A shortest word on which the two machines produce different outputs, or `None` if they
+    /// are equivalent. A missing transition on one side only counts as a difference.
+    pub fn distinguishing_word<OtherId: Eq + Hash + Clone + Debug>(&self, other: &MooreMachine<OtherId, Input, Output>) -> Option<Vec<Input>> {
+        let start = (Some(&self.initial_state), Some(&other.initial_state));
+        let mut seen: HashSet<StatePair<StateId, OtherId>> = HashSet::from([start]);
+        let mut queue: VecDeque<(StatePair<StateId, OtherId>, Vec<Input>)> = VecDeque::from([(start, Vec::new())]);
+
+        while let Some(((left, right), word)) = queue.pop_front() {
+            let left = left.and_then(|state_id| self.states.get(state_id));
+            let right = right.and_then(|state_id| other.states.get(state_id));
+
+            if left.map(|state| &state.output) != right.map(|state| &state.output) {
+                return Some(word);
+            }
+
+            let inputs: HashSet<&Input> = left.into_iter().flat_map(|state| state.transitions().map(|(input, _)| input))
+                .chain(right.into_iter().flat_map(|state| state.transitions().map(|(input, _)| input)))
+                .collect();
+            for input in inputs {
+                let next = (
+                    left.and_then(|state| state.transition(input)),
+                    right.and_then(|state| state.transition(input)),
+                );
+                if seen.insert(next) {
+                    let mut next_word = word.clone();
+                    next_word.push(input.clone());
+                    queue.push_back((next, next_word));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn is_equivalent<OtherId: Eq + Hash + Clone + Debug>(&self, other: &MooreMachine<OtherId, Input, Output>) -> bool {
+        self.distinguishing_word(other).is_none()
+    }
+
+    /*
+    Moore's partition refinement: start from the reachable states grouped by output, then keep
+    splitting blocks whose members disagree on the block reached under some input (a missing
+    transition counts as its own target) until nothing changes. Each final block becomes one
+    state, numbered in breadth-first order from the initial state.
+     */
+    pub fn minimize(&self) -> MooreMachine<usize, Input, Output> {
+        let reachable = self.reachable_states();
+
+        let mut outputs: HashMap<&Output, usize> = HashMap::new();
+        let mut block_of: HashMap<&StateId, usize> = HashMap::new();
+        for state_id in &reachable {
+            let next_block = outputs.len();
+            let block = *outputs.entry(&self.states[*state_id].output).or_insert(next_block);
+            block_of.insert(state_id, block);
+        }
+
+        let inputs: HashSet<&Input> = reachable.iter()
+            .flat_map(|state_id| self.states[*state_id].transitions().map(|(input, _)| input))
+            .collect();
+
+        let mut block_count = outputs.len();
+        loop {
+            let mut signatures: HashMap<(usize, Vec<Option<usize>>), usize> = HashMap::new();
+            let mut refined: HashMap<&StateId, usize> = HashMap::new();
+            for state_id in &reachable {
+                let successors = inputs.iter()
+                    .map(|input| self.states[*state_id].transition(input).map(|target| block_of[target]))
+                    .collect();
+                let next_block = signatures.len();
+                let block = *signatures.entry((block_of[state_id], successors)).or_insert(next_block);
+                refined.insert(state_id, block);
+            }
+
+            // Refinement only ever splits blocks, so an unchanged count means a fixpoint.
+            let stable = signatures.len() == block_count;
+            block_count = signatures.len();
+            block_of = refined;
+            if stable {
+                break;
+            }
+        }
+
+        self.quotient(&reachable, &block_of)
+    }
+
+    fn reachable_states(&self) -> Vec<&StateId> {
+        let mut seen: HashSet<&StateId> = HashSet::from([&self.initial_state]);
+        let mut order: Vec<&StateId> = vec![&self.initial_state];
+        let mut index = 0;
+
+        while index < order.len() {
+            for (_, target) in self.states[order[index]].transitions() {
+                if seen.insert(target) {
+                    order.push(target);
+                }
+            }
+            index += 1;
+        }
+        order
+    }
+
+    fn quotient(&self, reachable: &[&StateId], block_of: &HashMap<&StateId, usize>) -> MooreMachine<usize, Input, Output> {
+        // Renumber blocks in the order their first member was reached.
+        let mut numbering: HashMap<usize, usize> = HashMap::new();
+        for state_id in reachable {
+            let next_id = numbering.len();
+            numbering.entry(block_of[state_id]).or_insert(next_id);
+        }
+
+        let state_for = |state_id: &StateId| MooreState::new(numbering[&block_of[state_id]], self.states[state_id].output.clone());
+        let mut minimized = MooreMachine::new(state_for(&self.initial_state));
+        for state_id in reachable {
+            let from = state_for(state_id);
+            minimized.add_state(from.clone());
+            for (input, target) in self.states[*state_id].transitions() {
+                minimized.add_transition(&from, &state_for(target), input);
+            }
+        }
+        minimized
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph Moore {\n");
+
+        let mut nodes: Vec<(String, String)> = self.states.values()
+            .map(|state| {
+                let name = format!("{:?}", state.state_id);
+                let label = format!("{} / {:?}", name, state.output);
+                (name, label)
+            })
+            .collect();
+        nodes.sort();
+        for (name, label) in nodes {
+            dot.push_str(&format!("    {:?} [label = {:?}];\n", name, label));
+        }
+
+        dot.push_str(&format!("    __start__ [shape=point];\n    __start__ -> {:?};\n", format!("{:?}", self.initial_state)));
+
+        write_dot_edges(&mut dot, self.states.values().flat_map(|state| {
+            state.transitions().map(|(input, target)| {
+                (format!("{:?}", state.state_id), format!("{:?}", target), format!("{:?}", input))
+            })
+        }));
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// With boolean outputs a Moore machine is a DFA whose accepting states output `true`. A
+/// missing transition, which a Moore machine treats as a difference of its own, becomes the
+/// DFA's implicit rejection.
+impl<StateId, Input> From<&MooreMachine<StateId, Input, bool>> for Automaton<StateId, Input>
+where
+    StateId: Eq + Hash + Clone + Debug,
+    Input: Eq + Hash + Clone,
+{
+    fn from(machine: &MooreMachine<StateId, Input, bool>) -> Self {
+        let state_for = |state_id: &StateId| DfaState::new(state_id.clone(), machine.states[state_id].output);
+        let mut automaton = Automaton::new(state_for(&machine.initial_state));
+        for state in machine.states.values() {
+            let from = state_for(&state.state_id);
+            automaton.add_state(from.clone());
+            for (input, target) in state.transitions() {
+                automaton.add_transition(&from, &state_for(target), input);
+            }
+        }
+        automaton
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::hash::Hash;
+
+
+use super::Automaton;
+use crate::alphabet::Alphabet;
+use crate::regex_compile::{self, RegexCompileError};
+
+
+// Regular expressions over already-escaped symbols, simplified as they are built so that state
+// elimination does not drown the result in redundant groups.
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Nothing,
+    Epsilon,
+    Symbol(String),
+    Concat(Box<Expr>, Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    Star(Box<Expr>),
+}
+
+impl Expr {
+    fn union(self, other: Expr) -> Expr {
+        match (self, other) {
+            (Expr::Nothing, e) | (e, Expr::Nothing) => e,
+            (a, b) if a == b => a,
+            (a, b) => Expr::Union(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn concat(self, other: Expr) -> Expr {
+        match (self, other) {
+            (Expr::Nothing, _) | (_, Expr::Nothing) => Expr::Nothing,
+            (Expr::Epsilon, e) | (e, Expr::Epsilon) => e,
+            (a, b) => Expr::Concat(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn star(self) -> Expr {
+        match self {
+            Expr::Nothing | Expr::Epsilon => Expr::Epsilon,
+            Expr::Star(e) => Expr::Star(e),
+            e => Expr::Star(Box::new(e)),
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            // The regex crate accepts an empty intersection as a class that matches nothing.
+            Expr::Nothing => String::from("[a&&b]"),
+            Expr::Epsilon => String::from("(?:)"),
+            Expr::Symbol(s) => s.clone(),
+            Expr::Concat(a, b) => format!("{}{}", a.render_in_concat(), b.render_in_concat()),
+            Expr::Union(a, b) => format!("{}|{}", a.render(), b.render()),
+            Expr::Star(e) => format!("{}*", e.render_atom()),
+        }
+    }
+
+    fn render_in_concat(&self) -> String {
+        match self {
+            Expr::Union(..) => format!("(?:{})", self.render()),
+            _ => self.render(),
+        }
+    }
+
+    fn render_atom(&self) -> String {
+        match self {
+            Expr::Symbol(s) if s.chars().count() == 1 => s.clone(),
+            _ => format!("(?:{})", self.render()),
+        }
+    }
+}
+
+
+impl<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone + Display> Automaton<StateId, TransitionLabel> {
+
+    /*
+    State elimination: add a fresh start state with an epsilon edge to the initial state and a
+    fresh final state with epsilon edges from every accepting state, then remove the original
+    states one by one, replacing each path i -> k -> j by the edge expression
+    R(i,k) R(k,k)* R(k,j) unioned into R(i,j). The expression left on start -> final denotes the
+    language.
+     */
+    /// A regular expression in `regex` crate syntax for the accepted language. Symbols are
+    /// escaped and concatenated without separators; the expression is not anchored.
+    pub fn to_regex(&self) -> String {
+        let ids: HashMap<&StateId, usize> = self.states.keys().enumerate().map(|(i, state_id)| (state_id, i)).collect();
+        let start = ids.len();
+        let end = ids.len() + 1;
+
+        let mut edges: HashMap<(usize, usize), Expr> = HashMap::new();
+        let mut add_edge = |from: usize, to: usize, expr: Expr| {
+            let current = edges.remove(&(from, to)).unwrap_or(Expr::Nothing);
+            edges.insert((from, to), current.union(expr));
+        };
+
+        add_edge(start, ids[&self.initial_state], Expr::Epsilon);
+        for (state_id, state) in &self.states {
+            if state.is_accepting() {
+                add_edge(ids[state_id], end, Expr::Epsilon);
+            }
+            for (label, target) in state.transitions() {
+                add_edge(ids[state_id], ids[target], Expr::Symbol(regex::escape(&label.to_string())));
+            }
+        }
+
+        for k in 0..ids.len() {
+            let self_loop = edges.remove(&(k, k)).unwrap_or(Expr::Nothing).star();
+            let incoming: Vec<(usize, Expr)> = edges.iter().filter(|((_, to), _)| *to == k).map(|((from, _), e)| (*from, e.clone())).collect();
+            let outgoing: Vec<(usize, Expr)> = edges.iter().filter(|((from, _), _)| *from == k).map(|((_, to), e)| (*to, e.clone())).collect();
+            edges.retain(|(from, to), _| *from != k && *to != k);
+
+            for (i, into) in &incoming {
+                for (j, out_of) in &outgoing {
+                    let through = into.clone().concat(self_loop.clone()).concat(out_of.clone());
+                    let current = edges.remove(&(*i, *j)).unwrap_or(Expr::Nothing);
+                    edges.insert((*i, *j), current.union(through));
+                }
+            }
+        }
+
+        edges.remove(&(start, end)).unwrap_or(Expr::Nothing).render()
+    }
+}
+
+impl<StateId: Eq + Hash + Clone> Automaton<StateId, String> {
+
+    /// The words accepted here that also match `pattern` under `Regex::is_match`, with symbols
+    /// joined directly as `RegexTeacher` reads them. The pattern is compiled with
+    /// `regex_compile::compile_joined`, so only its regex subset is supported. What the pattern
+    /// matches outside this automaton's symbols cannot occur in the product and is ignored.
+    pub fn intersect_with_regex(&self, pattern: &str) -> Result<Automaton<usize, String>, RegexCompileError> {
+        let symbols: BTreeSet<&String> = self.states.values().flat_map(|state| state.transitions().map(|(symbol, _)| symbol)).collect();
+        let alphabet = Alphabet::new(symbols.into_iter().cloned());
+        Ok(self.intersection(&regex_compile::compile_joined(pattern, &alphabet)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::DfaState;
+
+    fn word(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|symbol| symbol.to_string()).collect()
+    }
+
+    #[test]
+    fn intersect_with_regex_keeps_the_matching_words() {
+        // Any request sequence, restricted to GETs followed by a single POST.
+        let any = DfaState::new(0, true);
+        let mut requests = Automaton::new(any.clone());
+        requests.add_transition(&any, &any, &"GET".to_string());
+        requests.add_transition(&any, &any, &"POST".to_string());
+
+        let restricted = requests.intersect_with_regex("^(GET)*POST$").unwrap();
+        assert!(restricted.accepts(&word(&["POST"])));
+        assert!(restricted.accepts(&word(&["GET", "GET", "POST"])));
+        assert!(!restricted.accepts(&word(&["GET"])));
+        assert!(!restricted.accepts(&word(&["POST", "POST"])));
+        assert!(requests.intersect_with_regex("(GET").is_err());
+    }
+}
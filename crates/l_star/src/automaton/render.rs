@@ -0,0 +1,118 @@
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+
+use super::{Automaton, StateLabel};
+
+
+/// Output formats handed to Graphviz as `-T<format>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Svg,
+    Png,
+}
+
+impl Format {
+    fn flag(self) -> &'static str {
+        match self {
+            Format::Svg => "-Tsvg",
+            Format::Png => "-Tpng",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    /// No `dot` executable on `PATH`; Graphviz is not installed.
+    DotNotFound,
+    Io(io::Error),
+    /// `dot` ran but exited unsuccessfully.
+    Graphviz { status: ExitStatus, stderr: String },
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::DotNotFound => write!(f, "the Graphviz `dot` command was not found on PATH; install Graphviz to render automatons"),
+            RenderError::Io(err) => write!(f, "failed to run `dot`: {}", err),
+            RenderError::Graphviz { status, stderr } => write!(f, "`dot` exited with {}: {}", status, stderr.trim()),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<io::Error> for RenderError {
+    fn from(err: io::Error) -> Self {
+        RenderError::Io(err)
+    }
+}
+
+
+/// Renders `automaton` as SVG into `path`.
+pub fn to_svg<StateId, TransitionLabel>(automaton: &Automaton<StateId, TransitionLabel>, path: &Path) -> Result<(), RenderError>
+where
+    StateId: Eq + Hash + Clone + StateLabel,
+    TransitionLabel: Eq + Hash + Clone + Display,
+{
+    to_file(automaton, Format::Svg, path)
+}
+
+/// Renders `automaton` as SVG and returns the document.
+pub fn to_svg_string<StateId, TransitionLabel>(automaton: &Automaton<StateId, TransitionLabel>) -> Result<String, RenderError>
+where
+    StateId: Eq + Hash + Clone + StateLabel,
+    TransitionLabel: Eq + Hash + Clone + Display,
+{
+    let svg = run_dot(&automaton.to_dot(), &[OsStr::new(Format::Svg.flag())])?;
+    String::from_utf8(svg).map_err(|err| RenderError::Io(io::Error::new(io::ErrorKind::InvalidData, err)))
+}
+
+/// Renders `automaton` in `format` into `path`, overwriting it.
+pub fn to_file<StateId, TransitionLabel>(automaton: &Automaton<StateId, TransitionLabel>, format: Format, path: &Path) -> Result<(), RenderError>
+where
+    StateId: Eq + Hash + Clone + StateLabel,
+    TransitionLabel: Eq + Hash + Clone + Display,
+{
+    let mut output = OsString::from("-o");
+    output.push(path);
+    run_dot(&automaton.to_dot(), &[OsStr::new(format.flag()), &output])?;
+    Ok(())
+}
+
+// Pipes `dot_source` through `dot` and returns its standard output.
+fn run_dot(dot_source: &str, args: &[&OsStr]) -> Result<Vec<u8>, RenderError> {
+    let mut child = Command::new("dot")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => RenderError::DotNotFound,
+            _ => RenderError::Io(err),
+        })?;
+
+    // Feed the graph from another thread so a large rendering cannot fill the output pipe
+    // while we are still writing.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let source = dot_source.to_owned();
+    let writer = thread::spawn(move || stdin.write_all(source.as_bytes()));
+
+    let output = child.wait_with_output()?;
+    let written = writer.join().expect("writer thread panicked");
+
+    // A failing `dot` may stop reading early; its own error says more than the broken pipe.
+    if !output.status.success() {
+        return Err(RenderError::Graphviz {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    written?;
+    Ok(output.stdout)
+}
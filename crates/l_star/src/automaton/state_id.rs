@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::ops::Index;
+
+use super::{Automaton, DfaState, StateLabel};
+
+
+/// An opaque state id. Unlike access strings, fresh ids can be handed out for states that
+/// have no single word naming them, such as merged or product states; `StateNames` keeps
+/// the original names next to them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateId(usize);
+
+impl StateId {
+    pub fn new(index: usize) -> Self {
+        StateId(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl Display for StateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "q{}", self.0)
+    }
+}
+
+impl StateLabel for StateId {
+    fn state_label(&self) -> String {
+        self.to_string()
+    }
+}
+
+
+/// The side table from `StateId`s back to the ids they replaced, e.g. access strings.
+#[derive(Clone, Debug)]
+pub struct StateNames<Name> {
+    names: Vec<Name>,
+    ids: HashMap<Name, StateId>,
+}
+
+impl<Name: Eq + Hash + Clone> StateNames<Name> {
+    pub fn name(&self, id: StateId) -> Option<&Name> {
+        self.names.get(id.0)
+    }
+
+    pub fn id_of(&self, name: &Name) -> Option<StateId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (StateId, &Name)> {
+        self.names.iter().enumerate().map(|(index, name)| (StateId(index), name))
+    }
+
+    fn push(&mut self, name: Name) -> StateId {
+        let id = StateId(self.names.len());
+        self.ids.insert(name.clone(), id);
+        self.names.push(name);
+        id
+    }
+}
+
+impl<Name> Index<StateId> for StateNames<Name> {
+    type Output = Name;
+
+    fn index(&self, id: StateId) -> &Name {
+        &self.names[id.0]
+    }
+}
+
+
+impl<Name: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone> Automaton<Name, TransitionLabel> {
+
+    /// The same automaton over opaque `StateId`s, numbered breadth-first from the initial
+    /// state `q0` (unreachable states last), together with the table of original ids.
+    pub fn with_opaque_ids(&self) -> (Automaton<StateId, TransitionLabel>, StateNames<Name>) {
+        let mut names = StateNames { names: Vec::new(), ids: HashMap::new() };
+        names.push(self.initial_state.clone());
+        let mut index = 0;
+        while index < names.len() {
+            let targets: Vec<&Name> = self.states[&names.names[index]].transitions().map(|(_, target)| target).collect();
+            for target in targets {
+                if names.id_of(target).is_none() {
+                    names.push(target.clone());
+                }
+            }
+            index += 1;
+        }
+        for name in self.states.keys() {
+            if names.id_of(name).is_none() {
+                names.push(name.clone());
+            }
+        }
+
+        let renamed = |name: &Name| {
+            let id = names.ids[name];
+            DfaState::new(id, self.states[name].is_accepting())
+        };
+        let mut automaton = Automaton::new(renamed(&self.initial_state));
+        for (name, state) in &self.states {
+            automaton.add_state(renamed(name));
+            for (label, target) in state.transitions() {
+                automaton.add_transition(&renamed(name), &renamed(target), label);
+            }
+        }
+        (automaton, names)
+    }
+}
+
+impl<TransitionLabel: Eq + Hash + Clone> Automaton<StateId, TransitionLabel> {
+
+    /// Puts the original ids back, e.g. to show access strings in DOT output.
+    pub fn with_names<Name: Eq + Hash + Clone>(&self, names: &StateNames<Name>) -> Automaton<Name, TransitionLabel> {
+        let named = |id: &StateId| DfaState::new(names[*id].clone(), self.states[id].is_accepting());
+        let mut automaton = Automaton::new(named(&self.initial_state));
+        for (id, state) in &self.states {
+            automaton.add_state(named(id));
+            for (label, target) in state.transitions() {
+                automaton.add_transition(&named(id), &named(target), label);
+            }
+        }
+        automaton
+    }
+}
@@ -0,0 +1,74 @@
+use std::hash::Hash;
+
+use super::Automaton;
+
+
+// Conveniences for automata over `char`, taking and returning `&str`/`String` and splitting
+// text with `str::chars`, so multi-byte characters are single symbols.
+impl<StateId: Eq + Hash + Clone> Automaton<StateId, char> {
+
+    pub fn accepts_str(&self, word: &str) -> bool {
+        self.accepts(&word.chars().collect::<Vec<char>>())
+    }
+
+    pub fn trace_str(&self, word: &str) -> Vec<&StateId> {
+        self.trace(&word.chars().collect::<Vec<char>>())
+    }
+
+    /// Every accepted word of at most `max_len` characters, shortest first and in character
+    /// order within a length.
+    pub fn words_up_to(&self, max_len: usize) -> Vec<String> {
+        let mut accepted = Vec::new();
+        let mut layer: Vec<(String, &StateId)> = vec![(String::new(), &self.initial_state)];
+        for len in 0..=max_len {
+            for (word, state_id) in &layer {
+                if self.states[*state_id].is_accepting() {
+                    accepted.push(word.clone());
+                }
+            }
+            if len == max_len {
+                break;
+            }
+            layer = layer.iter()
+                .flat_map(|(word, state_id)| {
+                    self.states[*state_id].transitions_sorted().into_iter().map(move |(symbol, target)| {
+                        let mut next = word.clone();
+                        next.push(*symbol);
+                        (next, target)
+                    })
+                })
+                .collect();
+        }
+        accepted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::automaton::{Automaton, DfaState};
+
+    // `日é` followed by any number of `🦀`: one, two and four bytes a character.
+    fn crabs() -> Automaton<u8, char> {
+        let mut automaton = Automaton::new(DfaState::new(0, false));
+        automaton.add_transition(&DfaState::new(0, false), &DfaState::new(1, false), &'日');
+        automaton.add_transition(&DfaState::new(1, false), &DfaState::new(2, true), &'é');
+        automaton.add_transition(&DfaState::new(2, true), &DfaState::new(2, true), &'🦀');
+        automaton
+    }
+
+    #[test]
+    fn multi_byte_characters_are_single_symbols() {
+        let automaton = crabs();
+        assert!(automaton.accepts_str("日é"));
+        assert!(automaton.accepts_str("日é🦀🦀"));
+        assert!(!automaton.accepts_str("日"));
+        assert!(!automaton.accepts_str("日e"));
+        assert_eq!(automaton.trace_str("日é🦀"), vec![&0, &1, &2, &2]);
+        assert_eq!(automaton.trace_str("日x🦀"), vec![&0, &1]);
+    }
+
+    #[test]
+    fn words_up_to_counts_characters_not_bytes() {
+        assert_eq!(crabs().words_up_to(4), vec!["日é", "日é🦀", "日é🦀🦀"]);
+    }
+}
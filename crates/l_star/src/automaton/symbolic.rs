@@ -0,0 +1,112 @@
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+use super::Automaton;
+
+
+/// A transition label standing for a whole set of symbols, stored as sorted, disjoint inclusive
+/// ranges. Using classes as labels keeps byte or Unicode alphabets down to a handful of edges
+/// per state; the learner then works over the classes and a teacher answers for a
+/// `representative` of each, see `SymbolClassTeacher`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct SymbolClass<T> {
+    ranges: Vec<(T, T)>,
+}
+
+pub type CharClass = SymbolClass<char>;
+pub type ByteClass = SymbolClass<u8>;
+
+impl<T: Ord + Copy> SymbolClass<T> {
+    /// Normalizes `ranges`: reversed bounds are swapped and overlapping ranges merged.
+    pub fn new(ranges: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut ranges: Vec<(T, T)> = ranges.into_iter()
+            .map(|(lo, hi)| if lo <= hi { (lo, hi) } else { (hi, lo) })
+            .collect();
+        ranges.sort();
+
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+        for (lo, hi) in ranges {
+            match merged.last_mut() {
+                Some((_, last_hi)) if lo <= *last_hi => *last_hi = (*last_hi).max(hi),
+                _ => merged.push((lo, hi)),
+            }
+        }
+        SymbolClass { ranges: merged }
+    }
+
+    pub fn single(symbol: T) -> Self {
+        SymbolClass { ranges: vec![(symbol, symbol)] }
+    }
+
+    pub fn range(lo: T, hi: T) -> Self {
+        Self::new([(lo, hi)])
+    }
+
+    pub fn contains(&self, symbol: &T) -> bool {
+        self.ranges
+            .binary_search_by(|(lo, hi)| {
+                if hi < symbol {
+                    std::cmp::Ordering::Less
+                } else if lo > symbol {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    /// The smallest symbol in the class, which is what a teacher should be asked about;
+    /// `SymbolClassTeacher` takes the next one if it is `T::default()`, the learner's padding.
+    pub fn representative(&self) -> Option<T> {
+        self.ranges.first().map(|(lo, _)| *lo)
+    }
+
+    pub fn ranges(&self) -> &[(T, T)] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl<T: Display + PartialEq> Display for SymbolClass<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [(lo, hi)] = self.ranges.as_slice()
+            && lo == hi {
+                return write!(f, "{}", lo);
+            }
+
+        write!(f, "[")?;
+        for (lo, hi) in &self.ranges {
+            if lo == hi {
+                write!(f, "{}", lo)?;
+            } else {
+                write!(f, "{}-{}", lo, hi)?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+
+impl<StateId: Eq + Hash + Clone, T: Ord + Copy + Hash> Automaton<StateId, SymbolClass<T>> {
+
+    /// Runs a word of concrete symbols, following from each state the edge whose class contains
+    /// the next symbol. Classes leaving a state are expected to be disjoint; a symbol in no
+    /// class rejects the word.
+    pub fn accepts_symbols(&self, word: &[T]) -> bool {
+        let mut state = &self.states[&self.initial_state];
+        for symbol in word {
+            let next = state.transitions()
+                .find(|(class, _)| class.contains(symbol))
+                .and_then(|(_, target)| self.states.get(target));
+            match next {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        state.is_accepting()
+    }
+}
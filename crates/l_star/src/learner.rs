@@ -1,14 +1,23 @@
-use std::{collections::{HashMap, HashSet}, fmt::{Debug, Display}, hash::Hash, iter::once, vec};
-use crate::teacher::Teacher;
+use std::{collections::{HashMap, HashSet}, fmt::{self, Debug, Display}, hash::Hash, io::{self, Write}, iter::once, vec};
+use crate::alphabet::Alphabet;
+use crate::teacher::{EquivalenceBatch, Teacher, TeacherError};
+#[cfg(feature = "async")]
+use crate::teacher::{AsyncTeacher, EquivalenceAnswer};
+#[cfg(feature = "async")]
+use futures_util::{stream, StreamExt, TryStreamExt};
 use crate::automaton::{Automaton, DfaState};
 
 
 type ObsKe<T> = Vec<T>;
 
+type Inconsistency<T> = (ObsKe<T>, ObsKe<T>, T);
 
+
+/// The S, E and T of L*: access strings, distinguishing suffixes and the answers to the
+/// membership queries asked so far.
 #[derive(Debug)]
-struct ObservationTable<T: Eq + Hash + Default + Clone> {
-    alphabets: HashSet<T>,
+pub struct ObservationTable<T: Eq + Hash + Default + Clone> {
+    alphabets: Alphabet<T>,
     s_prefixes : HashSet<ObsKe<T>>,
     e_suffixes: HashSet<ObsKe<T>>,
     table: HashMap<ObsKe<T>, HashMap<ObsKe<T>, bool>>,
@@ -16,7 +25,7 @@ struct ObservationTable<T: Eq + Hash + Default + Clone> {
 
 impl <T: Eq + Hash + Default + Clone> ObservationTable<T> {
 
-    pub fn new(alphabets: HashSet<T>) -> Self {
+    pub fn new(alphabets: Alphabet<T>) -> Self {
 
         let e_suffixes: HashSet<ObsKe<T>> = once(vec![T::default()])
             .chain(alphabets.iter().map(|a| vec![a.clone()]))
@@ -30,30 +39,28 @@ impl <T: Eq + Hash + Default + Clone> ObservationTable<T> {
         }
     }
 
-    fn is_consistent(&self) -> Result<bool, Option<(ObsKe<T>, ObsKe<T>, T)>> {
+    fn is_consistent(&self) -> Result<bool, Option<Inconsistency<T>>> {
         /*
         An observation table is called consistent provided that
         whenever s1 and s2 are elements of S such that row(s,) = row(s,),
         for all a inA,row(s, .a)=row(s, .a).
             If (S, E, T) is a closed,
          */
-        for s1 in &self.s_prefixes {
-            if let Some(s1_row) = self.table.get(s1) { 
-                for s2 in &self.s_prefixes {
+        let prefixes = self.sorted_prefixes();
+        for s1 in &prefixes {
+            if let Some(s1_row) = self.table.get(*s1) { 
+                for s2 in &prefixes {
                     // let s2_row = self.table.get(s2);
-                    if let Some(s2_row) = self.table.get(s2) {
-                        if s1_row == s2_row {
+                    if let Some(s2_row) = self.table.get(*s2)
+                        && s1_row == s2_row {
                             for a in &self.alphabets {
                                 if let (Some(s1_a_row), Some(s2_a_row)) = (
                                     self.table.get(&concat_vec_elem(s1, a)),
                                     self.table.get(&concat_vec_elem(s2, a)))
-                                    {
-                                        if s1_a_row != s2_a_row {
-                                            return Err(Some((s1.clone(), s2.clone(), a.clone())));
-                                        }
+                                    && s1_a_row != s2_a_row {
+                                        return Err(Some(((*s1).clone(), (*s2).clone(), a.clone())));
                                     }
                                 }
-                            }
                         }
                     }
                 }
@@ -61,7 +68,7 @@ impl <T: Eq + Hash + Default + Clone> ObservationTable<T> {
         Ok(true)
     }
 
-    fn is_closed(&self) -> Result<bool, (ObsKe<T>)>{
+    fn is_closed(&self) -> Result<bool, ObsKe<T>>{
         /*
         An observation table is called closed provided that for each t in S. A there exists an s in S such that
         row(t) = row(s).
@@ -71,12 +78,11 @@ impl <T: Eq + Hash + Default + Clone> ObservationTable<T> {
             if let Some(t_row) = self.table.get(&t){
                 let mut found = false;
                 for s in &self.s_prefixes {
-                    if let Some(s_row) = self.table.get(s){
-                        if t_row == s_row {
+                    if let Some(s_row) = self.table.get(s)
+                        && t_row == s_row {
                             found = true;
                             break;
                         }
-                    }
                 }
 
                 if !found {
@@ -88,16 +94,36 @@ impl <T: Eq + Hash + Default + Clone> ObservationTable<T> {
         Ok(true)
     }
 
-    fn get_sa(&self) -> HashSet<ObsKe<T>> {
-        let mut sa: HashSet<ObsKe<T>> = HashSet::new();
-        for s in &self.s_prefixes {
+    fn get_sa(&self) -> Vec<ObsKe<T>> {
+        let mut sa: Vec<ObsKe<T>> = Vec::new();
+        for s in self.sorted_prefixes() {
             for a in &self.alphabets {
-                sa.insert(s.iter().cloned().chain(once(a.clone())).collect()); // Combine s and a into a single vector
+                sa.push(s.iter().cloned().chain(once(a.clone())).collect()); // Combine s and a into a single vector
             }
         }
         sa
     }
 
+    // S ordered shortest first and then by alphabet position, with the empty word first, so
+    // that closing the table picks the same rows on every run.
+    fn sorted_prefixes(&self) -> Vec<&ObsKe<T>> {
+        self.sorted(&self.s_prefixes)
+    }
+
+    // E in the same order.
+    fn sorted_suffixes(&self) -> Vec<&ObsKe<T>> {
+        self.sorted(&self.e_suffixes)
+    }
+
+    fn sorted<'a>(&self, words: &'a HashSet<ObsKe<T>>) -> Vec<&'a ObsKe<T>> {
+        let mut words: Vec<&ObsKe<T>> = words.iter().collect();
+        words.sort_by_cached_key(|word| {
+            let positions: Vec<Option<usize>> = word.iter().map(|a| self.alphabets.index_of(a)).collect();
+            (word.len(), positions)
+        });
+        words
+    }
+
     fn get_rows(&self) -> HashSet<Vec<T>> {
         let mut rows: HashSet<Vec<T>> = HashSet::new();
 
@@ -116,6 +142,76 @@ impl <T: Eq + Hash + Default + Clone> ObservationTable<T> {
         self.e_suffixes.clone()
     }
 
+    fn get(&self, row: &ObsKe<T>, col: &ObsKe<T>) -> Option<bool> {
+        self.table.get(row).and_then(|cells| cells.get(col)).copied()
+    }
+
+    // The first column, in E order, on which two filled rows differ.
+    fn first_difference(&self, row1: &ObsKe<T>, row2: &ObsKe<T>) -> Option<ObsKe<T>> {
+        self.sorted_suffixes().into_iter().find(|col| self.get(row1, col) != self.get(row2, col)).cloned()
+    }
+
+    /// E, in the order of the entries of `row_signatures`.
+    pub fn suffixes(&self) -> Vec<&ObsKe<T>> {
+        self.sorted_suffixes()
+    }
+
+    /// Each row of S ∪ S·A as one character per suffix of E: `1` or `0` for an answered cell,
+    /// `?` for one not asked for. Rows of S that are states of a hypothesis can be shown with
+    /// `Automaton::to_dot_with_labels`.
+    pub fn row_signatures(&self) -> HashMap<ObsKe<T>, String> {
+        let columns = self.sorted_suffixes();
+        self.get_rows().into_iter()
+            .map(|row| {
+                let signature = columns.iter().map(|col| match self.get(&row, col) {
+                    Some(true) => '1',
+                    Some(false) => '0',
+                    None => '?',
+                }).collect();
+                (row, signature)
+            })
+            .collect()
+    }
+
+    // Removes, longest first, each suffix of E that no two rows of S ∪ S·A differ on alone,
+    // together with its cells, and returns the ones removed. The partition of the rows stays
+    // the same, so a closed and consistent table stays so and yields the same hypothesis. The
+    // empty suffix is always kept, since it decides acceptance.
+    fn compact_suffixes(&mut self) -> Vec<ObsKe<T>> {
+        let rows: Vec<ObsKe<T>> = self.get_rows().into_iter().collect();
+        let epsilon = vec![T::default()];
+        let mut kept: Vec<ObsKe<T>> = self.sorted_suffixes().into_iter().cloned().collect();
+        let distinct_rows = |columns: &[ObsKe<T>]| -> usize {
+            rows.iter()
+                .map(|row| columns.iter().map(|col| self.get(row, col)).collect::<Vec<Option<bool>>>())
+                .collect::<HashSet<_>>()
+                .len()
+        };
+
+        let partition = distinct_rows(&kept);
+        let mut removed = Vec::new();
+        for index in (0..kept.len()).rev() {
+            if kept[index] == epsilon {
+                continue;
+            }
+            let candidate = kept.remove(index);
+            // Dropping a column can only merge rows, so an unchanged count is an unchanged partition.
+            if distinct_rows(&kept) == partition {
+                removed.push(candidate);
+            } else {
+                kept.insert(index, candidate);
+            }
+        }
+
+        for suffix in &removed {
+            self.e_suffixes.remove(suffix);
+            for cells in self.table.values_mut() {
+                cells.remove(suffix);
+            }
+        }
+        removed
+    }
+
     fn update(&mut self, row: &ObsKe<T>, col: &ObsKe<T>, value: bool) {
         self.table.entry(row.clone())
             .or_default()
@@ -132,38 +228,320 @@ fn concat_vecs<T: Clone>(a: &[T], b: &[T]) -> Vec<T> {
     a.iter().cloned().chain(b.iter().cloned()).collect()
 }
 
+fn write_log(log: &mut Option<Box<dyn Write>>, message: fmt::Arguments) {
+    if let Some(log) = log.as_mut() {
+        // Logging is best effort and must never abort learning.
+        let _ = writeln!(log, "{}", message);
+    }
+}
+
+// Shows a word as its joined symbols, or ε when it is empty.
+struct DisplayWord<'a, T>(&'a [T]);
+
+impl<T: Display> Display for DisplayWord<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let joined: String = self.0.iter().map(|symbol| symbol.to_string()).collect();
+        if joined.is_empty() {
+            write!(f, "ε")
+        } else {
+            write!(f, "{}", joined)
+        }
+    }
+}
+
+
+/// How a counterexample is worked into the observation table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CounterexampleHandling {
+    /// Angluin's original method: every prefix of the counterexample joins S.
+    #[default]
+    Prefixes,
+    /// The Shahbaz–Groz method: strip the longest prefix already in S ∪ S·A and add every
+    /// suffix of the rest to E. S only grows through closedness, so the table never becomes
+    /// inconsistent, and far fewer rows are added for long counterexamples.
+    Suffixes,
+}
+
+/// When the learner asks for the cells of the observation table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableFilling {
+    /// Every cell of S ∪ S·A × E is asked for as soon as the row or column appears.
+    #[default]
+    Eager,
+    /// A cell is only asked for when comparing two rows gets to it. Rows are compared column
+    /// by column and a comparison stops at the first difference, so rows that differ early
+    /// are never filled in completely. Worthwhile when E is wide and queries are expensive.
+    Lazy,
+}
+
+/// Why `Learner::learn` stopped without a confirmed hypothesis.
+#[derive(Debug)]
+pub enum LearnError<T: Eq + Hash + Clone> {
+    /// The teacher could not answer. `hypothesis` is the last one built, if any round got
+    /// that far: not confirmed, but often a good approximation.
+    Teacher { error: TeacherError, hypothesis: Option<Automaton<ObsKe<T>, T>> },
+    /// A hypothesis outgrew `Learner::with_target_state_bound`.
+    StateBoundExceeded { bound: usize, states: usize },
+    /// A hypothesis was to be built from a table with an unanswered cell, which would have
+    /// left out transitions. The learner fills every cell first, so this is a bug.
+    IncompleteTable { row: ObsKe<T>, column: ObsKe<T> },
+    /// The next membership queries would have gone over `Learner::with_query_budget`.
+    /// `hypothesis` is the last one built, as for `Teacher`.
+    BudgetExhausted { budget: usize, hypothesis: Option<Automaton<ObsKe<T>, T>> },
+}
+
+impl<T: Eq + Hash + Clone + Debug> Display for LearnError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LearnError::Teacher { error, .. } => write!(f, "teacher failed: {}", error),
+            LearnError::StateBoundExceeded { bound, states } => write!(f, "hypothesis has {} states, more than the target state bound of {}", states, bound),
+            LearnError::IncompleteTable { row, column } => write!(f, "observation table has no answer for row {:?} in column {:?}", row, column),
+            LearnError::BudgetExhausted { budget, .. } => write!(f, "membership query budget of {} exhausted", budget),
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug> std::error::Error for LearnError<T> {}
+
+/// What `Learner::on_round` reports after each equivalence query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundInfo {
+    pub states: usize,
+    pub transitions: usize,
+    /// Length of the first counterexample, or `None` if the hypothesis was accepted.
+    pub counterexample_len: Option<usize>,
+    /// Counterexamples the teacher returned, 0 if the hypothesis was accepted.
+    pub counterexamples: usize,
+    /// Membership queries asked so far, over all rounds.
+    pub membership_queries: usize,
+    /// Equivalence queries asked so far, this one included.
+    pub equivalence_queries: usize,
+}
+
+type RoundCallback = Box<dyn FnMut(&RoundInfo)>;
 
 pub struct Learner<T: Eq + Hash + Clone + Default> {
     observation_table: ObservationTable<T>,
     teacher: Box<dyn Teacher<T>>,
+    log: Option<Box<dyn Write>>,
+    counterexample_handling: CounterexampleHandling,
+    table_filling: TableFilling,
+    target_state_bound: Option<usize>,
+    suffix_compaction: bool,
+    query_budget: Option<usize>,
+    membership_queries: usize,
+    equivalence_queries: usize,
+    on_round: Option<RoundCallback>,
+    // Set when the learner itself turned a query down for the budget, as opposed to the
+    // teacher failing with `TeacherError::BudgetExhausted`.
+    budget_exhausted: bool,
+    hypothesis: Option<Automaton<ObsKe<T>, T>>,
 }
 
 
 impl<T: Eq + Hash + Clone + Default + Debug + Display> Learner<T> {
-    pub fn new(alphabets: HashSet<T>, teacher: Box<dyn Teacher<T>>) -> Self {
+    /// Takes an `Alphabet`, or anything that converts into one such as a `HashSet` of an
+    /// ordered symbol type. The alphabet's order fixes the order hypotheses are built in.
+    pub fn new(alphabets: impl Into<Alphabet<T>>, teacher: Box<dyn Teacher<T>>) -> Self {
 
         Learner {
-            observation_table: ObservationTable::new(alphabets),
+            observation_table: ObservationTable::new(alphabets.into()),
             teacher,
+            log: None,
+            counterexample_handling: CounterexampleHandling::default(),
+            table_filling: TableFilling::default(),
+            target_state_bound: None,
+            suffix_compaction: false,
+            query_budget: None,
+            membership_queries: 0,
+            equivalence_queries: 0,
+            on_round: None,
+            budget_exhausted: false,
+            hypothesis: None,
         }
     }
 
+    /// Like `new`, with the alphabet made of the symbols in `words`, in the order they first
+    /// appear. A symbol the sample never uses is not in the alphabet, so the learned automaton
+    /// has no transitions on it and rejects every word containing it.
+    pub fn from_sample(words: &[Vec<T>], teacher: Box<dyn Teacher<T>>) -> Self {
+        let alphabet: Alphabet<T> = words.iter()
+            .flatten()
+            .filter(|symbol| **symbol != T::default())
+            .cloned()
+            .collect();
+        Learner::new(alphabet, teacher)
+    }
+
+    /// Logs counterexamples, added prefixes and suffixes, and hypothesis sizes to stderr.
+    pub fn with_logging(mut self, enabled: bool) -> Self {
+        self.log = match (enabled, self.log.take()) {
+            (false, _) => None,
+            (true, Some(log)) => Some(log),
+            (true, None) => Some(Box::new(io::stderr())),
+        };
+        self
+    }
+
+    pub fn observation_table(&self) -> &ObservationTable<T> {
+        &self.observation_table
+    }
+
+    /// The hypothesis of the last round, i.e. the one the teacher was last asked about, or
+    /// `None` before the first `step`.
+    pub fn current_hypothesis(&self) -> Option<&Automaton<ObsKe<T>, T>> {
+        self.hypothesis.as_ref()
+    }
+
+    pub fn with_counterexample_handling(mut self, handling: CounterexampleHandling) -> Self {
+        self.counterexample_handling = handling;
+        self
+    }
+
+    pub fn with_table_filling(mut self, table_filling: TableFilling) -> Self {
+        self.table_filling = table_filling;
+        self
+    }
+
+    /// Promises that the minimal complete DFA of the target has at most `states` states. L*
+    /// hypotheses never have more states than that DFA, so a hypothesis with `states` states
+    /// is accepted without asking the teacher, and one with more is an error, since the bound
+    /// or the teacher must be wrong.
+    pub fn with_target_state_bound(mut self, states: usize) -> Self {
+        self.target_state_bound = Some(states);
+        self
+    }
+
+    /// Before each hypothesis, drops the suffixes of E that no longer tell any two rows
+    /// apart, so that the table stays narrow over many counterexamples. The hypotheses are
+    /// the same, but a dropped suffix that becomes useful again costs its queries again.
+    /// Only applies with `TableFilling::Eager`.
+    pub fn with_suffix_compaction(mut self, enabled: bool) -> Self {
+        self.suffix_compaction = enabled;
+        self
+    }
+
+    /// Asks at most `queries` membership queries, counting every word sent to the teacher.
+    /// A table filled eagerly asks a batch at a time, and learning stops before a batch that
+    /// would go over the budget, with `LearnError::BudgetExhausted` carrying the last hypothesis.
+    pub fn with_query_budget(mut self, queries: usize) -> Self {
+        self.query_budget = Some(queries);
+        self
+    }
+
+    /// Membership queries asked so far.
+    pub fn membership_queries(&self) -> usize {
+        self.membership_queries
+    }
+
+    /// Equivalence queries asked so far.
+    pub fn equivalence_queries(&self) -> usize {
+        self.equivalence_queries
+    }
+
+    /// Calls `callback` after each equivalence query, e.g. to drive a progress bar. It runs
+    /// before the counterexamples are added to the table.
+    pub fn on_round(mut self, callback: impl FnMut(&RoundInfo) + 'static) -> Self {
+        self.on_round = Some(Box::new(callback));
+        self
+    }
+
+    /// Like `with_logging(true)`, but writes the log to `writer`.
+    pub fn with_log_writer(mut self, writer: impl Write + 'static) -> Self {
+        self.log = Some(Box::new(writer));
+        self
+    }
+
+    /// Extends the alphabet with `symbol`, e.g. one first seen in a counterexample, and
+    /// returns whether it was new. S and the answers so far are kept; the rows for `symbol`
+    /// and the column `[symbol]` are asked for by the next `step`, so the next hypothesis
+    /// has transitions on it.
+    pub fn add_symbol(&mut self, symbol: T) -> bool {
+        if symbol == T::default() || self.observation_table.alphabets.contains(&symbol) {
+            return false;
+        }
+        self.observation_table.e_suffixes.insert(vec![symbol.clone()]);
+        write_log(&mut self.log, format_args!("added symbol {}", symbol));
+        self.observation_table.alphabets.insert(symbol);
+        true
+    }
 
-    fn update_observation_table(&mut self){
-        let rows = self.observation_table.get_rows();
-        let columns = self.observation_table.get_columns();
 
-        for row in rows {
+
+    // The missing cells go to the teacher as a single batch.
+    fn update_observation_table(&mut self) -> Result<(), TeacherError> {
+        let cells = self.missing_cells();
+        if cells.is_empty() {
+            return Ok(());
+        }
+        let words: Vec<Vec<T>> = cells.iter().map(|(row, col)| concat_vecs(row, col)).collect();
+        self.spend(words.len())?;
+        let responses = self.teacher.try_membership_query_batch(&words)?;
+        self.fill_cells(&cells, responses)
+    }
+
+    // The cells not asked for yet. Answers never change, so a cell is only asked for once.
+    fn missing_cells(&self) -> Vec<(ObsKe<T>, ObsKe<T>)> {
+        let columns = self.observation_table.get_columns();
+        let mut cells = Vec::new();
+        for row in self.observation_table.get_rows() {
             for col in &columns {
-                let c: Vec<_> = concat_vecs(&row, &col);
-                // Query the teacher for information about the (row, col) pair
-                let response = self.teacher.membership_query(c);
-                // Update the observation table with the teacher's response
-                self.observation_table.update(&row, &col, response);
+                if self.observation_table.get(&row, col).is_none() {
+                    cells.push((row.clone(), col.clone()));
+                }
             }
         }
+        cells
     }
 
+    // Stores the answers to `cells`, one per cell and in the same order.
+    fn fill_cells(&mut self, cells: &[(ObsKe<T>, ObsKe<T>)], responses: Vec<bool>) -> Result<(), TeacherError> {
+        if responses.len() != cells.len() {
+            return Err(TeacherError::Other(format!("{} membership queries got {} answers", cells.len(), responses.len())));
+        }
+        for ((row, col), response) in cells.iter().zip(responses) {
+            self.observation_table.update(row, col, response);
+        }
+        Ok(())
+    }
+
+
+    fn add_prefixes(&mut self, counterexample: &[T]) {
+        // S must stay prefix-closed, so every prefix of the counterexample joins it.
+        for end in 1..=counterexample.len() {
+            let prefix = counterexample[..end].to_vec();
+            if self.observation_table.s_prefixes.insert(prefix.clone()) {
+                write_log(&mut self.log, format_args!("added prefix {}", DisplayWord(&prefix)));
+            }
+        }
+    }
+
+    fn add_suffixes(&mut self, counterexample: &[T]) {
+        // Rows are stored with `T::default()` standing in for the empty word, so compare them
+        // without it.
+        let known: HashSet<Vec<T>> = self.observation_table.get_rows().into_iter()
+            .map(|row| row.into_iter().filter(|symbol| *symbol != T::default()).collect())
+            .collect();
+        let start = (0..=counterexample.len()).rev()
+            .find(|end| known.contains(&counterexample[..*end]))
+            .unwrap_or(0);
+
+        for begin in start..counterexample.len() {
+            let suffix = counterexample[begin..].to_vec();
+            if self.observation_table.e_suffixes.insert(suffix.clone()) {
+                write_log(&mut self.log, format_args!("added suffix {}", DisplayWord(&suffix)));
+            }
+        }
+    }
+
+    // `gen_hypothesis` reads every cell of S ∪ S·A × E; one it cannot find would silently drop
+    // transitions, so a table with a gap is refused, naming its shortest missing cell.
+    fn check_filled(&self) -> Result<(), LearnError<T>> {
+        match self.missing_cells().into_iter().min_by_key(|(row, column)| (row.len() + column.len(), format!("{:?} {:?}", row, column))) {
+            Some((row, column)) => Err(LearnError::IncompleteTable { row, column }),
+            None => Ok(()),
+        }
+    }
 
     fn gen_hypothesis(&self) -> Automaton<ObsKe<T>, T> {
         /*
@@ -173,102 +551,656 @@ impl<T: Eq + Hash + Clone + Default + Debug + Display> Learner<T> {
             F= {row(s):s ∈ S and T(s)=1}, 
             
             δ(row(s), a) = row(s .a).
+
+        Each distinct row becomes one state, named after a prefix in S that has it. The empty
+        word always names its own row, so the initial state is `[T::default()]`. With an empty
+        alphabet, or a language that is empty or universal, this gives a single state, accepting
+        exactly when the empty word is.
          */
+        let epsilon = vec![T::default()];
+        let columns: Vec<&ObsKe<T>> = self.observation_table.e_suffixes.iter().collect();
+        let row = |prefix: &ObsKe<T>| -> Vec<Option<bool>> {
+            let cells = self.observation_table.table.get(prefix);
+            columns.iter().map(|e| cells.and_then(|cells| cells.get(*e)).copied()).collect()
+        };
+        let is_accepting = |prefix: &ObsKe<T>| {
+            self.observation_table.table.get(prefix).and_then(|cells| cells.get(&epsilon)) == Some(&true)
+        };
+
+        // Shorter access strings name their row first, with the empty word ahead of all.
+        let mut representatives: HashMap<Vec<Option<bool>>, &ObsKe<T>> = HashMap::new();
+        for prefix in self.observation_table.sorted_prefixes() {
+            representatives.entry(row(prefix)).or_insert(prefix);
+        }
 
-        let mut automaton: Automaton<ObsKe<T>, T> = Automaton::new(DfaState::new(vec![T::default()], false));
+        let mut automaton: Automaton<ObsKe<T>, T> = Automaton::new(DfaState::new(epsilon.clone(), is_accepting(&epsilon)));
+        for prefix in representatives.values() {
+            automaton.add_state(DfaState::new((*prefix).clone(), is_accepting(prefix)));
+        }
+
+        for prefix in representatives.values() {
+            let state_s = automaton.get_state(prefix).unwrap().clone();
+            for a in &self.observation_table.alphabets {
+                // Closedness guarantees a representative; a missing one leaves the edge out.
+                if let Some(target) = representatives.get(&row(&concat_vec_elem(prefix, a))) {
+                    let target_state = automaton.get_state(target).unwrap().clone();
+                    automaton.add_transition(&state_s, &target_state, a);
+                }
+            }
+        }
+        automaton
+    }
+
+    // Fills the table and repairs it until it is closed and consistent. Each repair changes S
+    // or E, after which the table is filled again and both properties are checked from the
+    // start, closedness first.
+    fn close_eagerly(&mut self) -> Result<(), TeacherError> {
+        loop {
+            self.update_observation_table()?;
+            if !self.repair_table() {
+                return Ok(());
+            }
+        }
+    }
 
-        let mut state_distinguish: HashMap<bool, ObsKe<T>> = HashMap::new();
+    // One repair of a filled table that is not closed or not consistent; `false` if it is both.
+    fn repair_table(&mut self) -> bool {
+        if let Err(sa) = self.observation_table.is_closed() {
+            write_log(&mut self.log, format_args!("added prefix {}", DisplayWord(&sa)));
+            self.observation_table.s_prefixes.insert(sa);
+            return true;
+        }
 
-        for prefix in &self.observation_table.s_prefixes {
-            let state_id = prefix.clone();
+        if let Err(Some((s1, s2, a))) = self.observation_table.is_consistent() {
+            let e = self.observation_table.first_difference(&concat_vec_elem(&s1, &a), &concat_vec_elem(&s2, &a))
+                .expect("inconsistent rows differ in some column");
+            // s1 and s2 are told apart by a·e, so that suffix goes into E.
+            let suffix = if e == vec![T::default()] { vec![a.clone()] } else { concat_vecs(std::slice::from_ref(&a), &e) };
+            write_log(&mut self.log, format_args!("added suffix {}", DisplayWord(&suffix)));
+            self.observation_table.e_suffixes.insert(suffix);
+            return true;
+        }
 
-            let mut state: DfaState<ObsKe<T>, T>  = DfaState::new(state_id, false);
+        false
+    }
 
-            if let Some(true) = self.observation_table.table.get(&prefix.clone()).and_then(|t| t.get(&vec![T::default()])) {
-                // Check if the state is accepting based on the table
-                state.set_accepting(true);
+    fn compact_suffixes(&mut self) {
+        if self.suffix_compaction {
+            for suffix in self.observation_table.compact_suffixes() {
+                write_log(&mut self.log, format_args!("removed suffix {}", DisplayWord(&suffix)));
             }
+        }
+    }
 
-            automaton.add_state(state.clone());
+    // A cell, asked for if it is not in the table yet.
+    fn cell(&mut self, row: &ObsKe<T>, col: &ObsKe<T>) -> Result<bool, TeacherError> {
+        if let Some(value) = self.observation_table.get(row, col) {
+            return Ok(value);
+        }
+        self.spend(1)?;
+        let response = self.teacher.try_membership_query(&concat_vecs(row, col))?;
+        self.observation_table.update(row, col, response);
+        Ok(response)
+    }
 
-            if let Some(t_value) = self.observation_table.table.get(&prefix.clone()).and_then(|t| t.get(&vec![T::default()])) {
-                state_distinguish.insert(t_value.clone(), prefix.clone());
+    // The first column, in E order, on which two rows differ, filling in only the cells up to it.
+    fn first_difference(&mut self, row1: &ObsKe<T>, row2: &ObsKe<T>) -> Result<Option<ObsKe<T>>, TeacherError> {
+        if row1 == row2 {
+            return Ok(None);
+        }
+        let columns: Vec<ObsKe<T>> = self.observation_table.sorted_suffixes().into_iter().cloned().collect();
+        for col in columns {
+            if self.cell(row1, &col)? != self.cell(row2, &col)? {
+                return Ok(Some(col));
             }
+        }
+        Ok(None)
+    }
 
-            if prefix.clone() == vec![T::default()] {
-                automaton.set_initial_state(&state);
+    // The first row of `rows` equal to `row`.
+    fn find_equal_row<'a>(&mut self, row: &ObsKe<T>, rows: impl IntoIterator<Item = &'a ObsKe<T>>) -> Result<Option<&'a ObsKe<T>>, TeacherError>
+    where T: 'a {
+        for candidate in rows {
+            if self.first_difference(row, candidate)?.is_none() {
+                return Ok(Some(candidate));
             }
         }
+        Ok(None)
+    }
 
-        for prefix in &self.observation_table.s_prefixes {
-            let state_s = automaton.get_state(&prefix.clone()).unwrap().clone();
-            for a in &self.observation_table.alphabets {
-                let state_sa_id = concat_vec_elem(prefix, a);
+    // The same repairs as `close_eagerly`, in the same order, with every row comparison made by
+    // `first_difference`.
+    fn close_lazily(&mut self) -> Result<(), TeacherError> {
+        'repair: loop {
+            let prefixes: Vec<ObsKe<T>> = self.observation_table.sorted_prefixes().into_iter().cloned().collect();
+            let alphabet: Vec<T> = self.observation_table.alphabets.iter().cloned().collect();
 
-                if let Some(t_value) = self.observation_table.table.get(&state_sa_id).and_then(|t| t.get(&vec![T::default()])) {
-                    if let Some(state) = state_distinguish.get(t_value) {
-                        let target_state = automaton.get_state(state).unwrap().clone();
-                        automaton.add_transition(&state_s, &target_state, a);
+            for t in self.observation_table.get_sa() {
+                if self.observation_table.s_prefixes.contains(&t) {
+                    continue;
+                }
+                if self.find_equal_row(&t, &prefixes)?.is_none() {
+                    write_log(&mut self.log, format_args!("added prefix {}", DisplayWord(&t)));
+                    self.observation_table.s_prefixes.insert(t);
+                    continue 'repair;
+                }
+            }
+
+            for (index, s1) in prefixes.iter().enumerate() {
+                for s2 in &prefixes[index + 1..] {
+                    if self.first_difference(s1, s2)?.is_some() {
+                        continue;
+                    }
+                    for a in &alphabet {
+                        if let Some(e) = self.first_difference(&concat_vec_elem(s1, a), &concat_vec_elem(s2, a))? {
+                            let suffix = if e == vec![T::default()] { vec![a.clone()] } else { concat_vecs(std::slice::from_ref(a), &e) };
+                            write_log(&mut self.log, format_args!("added suffix {}", DisplayWord(&suffix)));
+                            self.observation_table.e_suffixes.insert(suffix);
+                            continue 'repair;
+                        }
                     }
                 }
             }
+            return Ok(());
         }
-        automaton
     }
 
-    pub fn learn(&mut self) -> Automaton<ObsKe<T>, T> {
-        loop {
-            self.update_observation_table();
-            loop {
-                let is_consistent = self.observation_table.is_consistent();
-
-                if let Err(Some((s1, s2, a ))) = is_consistent.clone() {
-                    // find e that resulted in inconsistency
-                    let s1_a_row = self.observation_table.table.get(&concat_vec_elem(&s1, &a));
-                    let s2_a_row = self.observation_table.table.get(&concat_vec_elem(&s2, &a));
-
-                    if let (Some(s1_a_row), Some(s2_a_row)) = (s1_a_row, s2_a_row) {
-                        for (e, v) in s1_a_row.iter() {
-                            if let Some(s2_a_v) = s2_a_row.get(e) {
-                                if v != s2_a_v {
-                                    self.observation_table.e_suffixes.insert(e.clone());
-                                }
-                            }
-                        }
-                    }
-                    self.update_observation_table();
+    // `gen_hypothesis` for a partly filled table: rows are matched by `first_difference` rather
+    // than by their full contents.
+    fn gen_hypothesis_lazily(&mut self) -> Result<Automaton<ObsKe<T>, T>, TeacherError> {
+        let epsilon = vec![T::default()];
+        let prefixes: Vec<ObsKe<T>> = self.observation_table.sorted_prefixes().into_iter().cloned().collect();
+        let alphabet: Vec<T> = self.observation_table.alphabets.iter().cloned().collect();
+
+        let mut representatives: Vec<ObsKe<T>> = Vec::new();
+        for prefix in prefixes {
+            if self.find_equal_row(&prefix, &representatives)?.is_none() {
+                representatives.push(prefix);
+            }
+        }
+        let mut states: Vec<DfaState<ObsKe<T>, T>> = Vec::new();
+        for prefix in &representatives {
+            states.push(DfaState::new(prefix.clone(), self.cell(prefix, &epsilon)?));
+        }
+
+        let mut automaton: Automaton<ObsKe<T>, T> = Automaton::new(states[0].clone());
+        for state in &states {
+            automaton.add_state(state.clone());
+        }
+        for (prefix, state) in representatives.iter().zip(&states) {
+            for a in &alphabet {
+                let row = concat_vec_elem(prefix, a);
+                if let Some(target) = self.find_equal_row(&row, &representatives)? {
+                    let target_state = automaton.get_state(target).unwrap().clone();
+                    automaton.add_transition(state, &target_state, a);
                 }
+            }
+        }
+        Ok(automaton)
+    }
 
-                let is_closed = self.observation_table.is_closed();
+    // Counts `queries` membership queries against the budget, unless they would go over it.
+    fn spend(&mut self, queries: usize) -> Result<(), TeacherError> {
+        if self.query_budget.is_some_and(|budget| self.membership_queries + queries > budget) {
+            self.budget_exhausted = true;
+            return Err(TeacherError::BudgetExhausted);
+        }
+        self.membership_queries += queries;
+        Ok(())
+    }
+
+    fn teacher_error(&mut self, error: TeacherError) -> LearnError<T> {
+        if let (true, Some(budget)) = (std::mem::take(&mut self.budget_exhausted), self.query_budget) {
+            write_log(&mut self.log, format_args!("membership query budget of {} exhausted after {} queries", budget, self.membership_queries));
+            return LearnError::BudgetExhausted { budget, hypothesis: self.hypothesis.clone() };
+        }
+        write_log(&mut self.log, format_args!("teacher failed: {}", error));
+        LearnError::Teacher { error, hypothesis: self.hypothesis.clone() }
+    }
 
-                if let Err(sa) = is_closed.clone() {
-                    self.observation_table.s_prefixes.insert(sa);
-                    self.update_observation_table();
+    /// Runs rounds until the teacher accepts a hypothesis, and returns it. When the teacher
+    /// fails, the error carries the last hypothesis built, which may still be useful.
+    pub fn learn(&mut self) -> Result<Automaton<ObsKe<T>, T>, LearnError<T>> {
+        while !self.step()? {}
+        Ok(self.hypothesis.clone().expect("a round always builds a hypothesis"))
+    }
+
+    /// One round of L*: makes the table closed and consistent, builds a hypothesis and asks the
+    /// teacher about it. Returns `true` once the teacher accepted it; otherwise the
+    /// counterexamples have been added to the table. See `current_hypothesis`.
+    pub fn step(&mut self) -> Result<bool, LearnError<T>> {
+        let hypothesis = match self.table_filling {
+            TableFilling::Eager => {
+                self.close_eagerly().map_err(|error| self.teacher_error(error))?;
+                self.check_filled()?;
+                self.compact_suffixes();
+                self.gen_hypothesis()
+            }
+            TableFilling::Lazy => self.close_lazily().and_then(|()| self.gen_hypothesis_lazily()).map_err(|error| self.teacher_error(error))?,
+        };
+        if self.accepted_by_bound(&hypothesis)? {
+            return Ok(true);
+        }
+        let result = self.teacher.try_equivalence_query_batch(hypothesis.clone());
+        self.take_verdict(hypothesis, result)
+    }
+
+    // Logs a new hypothesis and holds it against the target state bound; `true` if the bound
+    // proves it correct, which makes it the result.
+    fn accepted_by_bound(&mut self, hypothesis: &Automaton<ObsKe<T>, T>) -> Result<bool, LearnError<T>> {
+        write_log(&mut self.log, format_args!("hypothesis with {} states and {} transitions", hypothesis.state_count(), hypothesis.transition_count()));
+
+        if let Some(bound) = self.target_state_bound {
+            let states = hypothesis.state_count();
+            if states > bound {
+                return Err(LearnError::StateBoundExceeded { bound, states });
+            }
+            if states == bound {
+                write_log(&mut self.log, format_args!("hypothesis reached the target state bound"));
+                self.hypothesis = Some(hypothesis.clone());
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // The teacher's answer about `hypothesis`: `true` if it was accepted, otherwise the
+    // counterexamples are added to the table.
+    fn take_verdict(&mut self, hypothesis: Automaton<ObsKe<T>, T>, result: Result<EquivalenceBatch<T>, TeacherError>) -> Result<bool, LearnError<T>> {
+        self.hypothesis = Some(hypothesis.clone());
+        let verdict = result.map_err(|error| self.teacher_error(error))?;
+        self.equivalence_queries += 1;
+        if let Some(callback) = &mut self.on_round {
+            let counterexamples = verdict.as_ref().err();
+            callback(&RoundInfo {
+                states: hypothesis.state_count(),
+                transitions: hypothesis.transition_count(),
+                counterexample_len: counterexamples.and_then(|counterexamples| counterexamples.first()).map(Vec::len),
+                counterexamples: counterexamples.map_or(0, Vec::len),
+                membership_queries: self.membership_queries,
+                equivalence_queries: self.equivalence_queries,
+            });
+        }
+        match verdict {
+            Ok(()) => {
+                write_log(&mut self.log, format_args!("learning completed successfully"));
+                Ok(true) // Learning is complete
+            },
+            Err(counterexamples) => {
+                // All counterexamples go into the table before the next hypothesis is built.
+                for counterexample in counterexamples {
+                    let trace = hypothesis.trace(&counterexample).iter()
+                        .map(|state_id| DisplayWord(state_id).to_string())
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    write_log(&mut self.log, format_args!("counterexample {} (trace: {})", DisplayWord(&counterexample), trace));
+                    match self.counterexample_handling {
+                        CounterexampleHandling::Prefixes => self.add_prefixes(&counterexample),
+                        CounterexampleHandling::Suffixes => self.add_suffixes(&counterexample),
+                    }
                 }
+                Ok(false)
+            }
+        }
+    }
+}
+
+
+#[cfg(feature = "async")]
+impl<T: Eq + Hash + Clone + Default + Debug + Display> Learner<T> {
+    /// A learner for `learn_async` only: it has no teacher to `learn` or `step` with.
+    pub fn new_async(alphabets: impl Into<Alphabet<T>>) -> Self {
+        Learner::new(alphabets, Box::new(NoTeacher))
+    }
 
-                if matches!(is_closed, Ok(true)) && matches!(is_consistent, Ok(true)) {
-                    break; // Exit the loop if the table is closed or consistent
+    /// Like `learn`, but asks `teacher`, with up to `concurrency` membership queries in flight
+    /// at a time. The table is always filled eagerly, whatever `with_table_filling` says, and
+    /// the teacher given to `new` is not asked.
+    pub async fn learn_async(&mut self, teacher: &impl AsyncTeacher<T>, concurrency: usize) -> Result<Automaton<ObsKe<T>, T>, LearnError<T>> {
+        loop {
+            loop {
+                let cells = self.missing_cells();
+                let words: Vec<Vec<T>> = cells.iter().map(|(row, col)| concat_vecs(row, col)).collect();
+                self.spend(words.len()).map_err(|error| self.teacher_error(error))?;
+                let responses = stream::iter(words.iter().map(|word| teacher.membership_query(word)))
+                    .buffered(concurrency.max(1))
+                    .try_collect::<Vec<bool>>()
+                    .await;
+                responses.and_then(|responses| self.fill_cells(&cells, responses)).map_err(|error| self.teacher_error(error))?;
+                if !self.repair_table() {
+                    break;
                 }
             }
 
+            self.check_filled()?;
+            self.compact_suffixes();
             let hypothesis = self.gen_hypothesis();
+            if self.accepted_by_bound(&hypothesis)? {
+                return Ok(hypothesis);
+            }
+            let result = teacher.equivalence_query(hypothesis.clone()).await;
+            if self.take_verdict(hypothesis, result.map(|answer| answer.map_err(|counterexample| vec![counterexample])))? {
+                return Ok(self.hypothesis.clone().expect("an accepted hypothesis is kept"));
+            }
+        }
+    }
+}
 
-            match self.teacher.validate_hypothesis(hypothesis.clone()) {
-                Ok(true) => {
-                    println!("Learning completed successfully.");
-                    return hypothesis; // Learning is complete
-                },
-                Err(counterexample) => {
-                    // If a counterexample was provided, we need to update the observation table
-                    for e in counterexample {
-                        self.observation_table.s_prefixes.extend(vec![e.clone()]);
-                    }
+// The synchronous teacher of a learner made with `new_async`.
+#[cfg(feature = "async")]
+struct NoTeacher;
+
+#[cfg(feature = "async")]
+impl<T: Eq + Hash + Clone + Default + Debug> Teacher<T> for NoTeacher {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, _states: &[T]) -> Result<bool, TeacherError> {
+        Err(TeacherError::Other("this learner can only learn_async".to_string()))
+    }
+
+    fn try_equivalence_query(&self, _automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        Err(TeacherError::Other("this learner can only learn_async".to_string()))
+    }
+}
+
+// What `ObservationTable::to_json` writes: the table without the maps keyed by words, which
+// JSON objects cannot hold.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TableSnapshot<T> {
+    alphabet: Vec<T>,
+    s_prefixes: Vec<ObsKe<T>>,
+    e_suffixes: Vec<ObsKe<T>>,
+    cells: Vec<(ObsKe<T>, ObsKe<T>, bool)>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Eq + Hash + Default + Clone + serde::Serialize + serde::de::DeserializeOwned> ObservationTable<T> {
+
+    /// S, E and every answered cell, so that a run can be resumed without asking again.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&self.snapshot())
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json).map(Self::from_snapshot)
+    }
+
+    fn snapshot(&self) -> TableSnapshot<T> {
+        let cells = self.table.iter()
+            .flat_map(|(row, cells)| cells.iter().map(move |(col, value)| (row.clone(), col.clone(), *value)))
+            .collect();
+        TableSnapshot {
+            alphabet: self.alphabets.symbols().to_vec(),
+            s_prefixes: self.sorted_prefixes().into_iter().cloned().collect(),
+            e_suffixes: self.e_suffixes.iter().cloned().collect(),
+            cells,
+        }
+    }
+
+    fn from_snapshot(snapshot: TableSnapshot<T>) -> Self {
+        let mut table = ObservationTable {
+            alphabets: Alphabet::new(snapshot.alphabet),
+            s_prefixes: snapshot.s_prefixes.into_iter().collect(),
+            e_suffixes: snapshot.e_suffixes.into_iter().collect(),
+            table: HashMap::new(),
+        };
+        for (row, col, value) in snapshot.cells {
+            table.update(&row, &col, value);
+        }
+        table
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Eq + Hash + Clone + Default + Debug + Display + serde::Serialize + serde::de::DeserializeOwned> Learner<T> {
+
+    /// Checkpoints the observation table as JSON. The teacher and logging are not saved.
+    pub fn save(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, &self.observation_table.snapshot())
+    }
+
+    /// Resumes from a checkpoint written by `save`, asking `teacher` only about cells the
+    /// checkpoint does not have.
+    pub fn load(reader: impl io::Read, teacher: Box<dyn Teacher<T>>) -> serde_json::Result<Self> {
+        let snapshot: TableSnapshot<T> = serde_json::from_reader(reader)?;
+        let mut learner = Learner::new(Alphabet::new(snapshot.alphabet.clone()), teacher);
+        learner.observation_table = ObservationTable::from_snapshot(snapshot);
+        Ok(learner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::teachers::regex_teacher::RegexTeacher;
+
+    fn alphabet(symbols: &[&str]) -> Alphabet<String> {
+        Alphabet::new(symbols.iter().map(|symbol| symbol.to_string()))
+    }
+
+    // Steps `learner` until the teacher accepts, failing the test after `rounds` rounds rather
+    // than looping forever.
+    fn learn_within(learner: &mut Learner<String>, rounds: usize) -> Automaton<ObsKe<String>, String> {
+        for _ in 0..rounds {
+            if learner.step().expect("learning failed") {
+                return learner.current_hypothesis().unwrap().clone();
+            }
+        }
+        panic!("no hypothesis accepted after {} rounds", rounds);
+    }
+
+    fn regex_learner(pattern: &str, symbols: &[&str]) -> (Learner<String>, Automaton<usize, String>) {
+        let teacher = RegexTeacher::new_anchored(pattern).unwrap();
+        let target = teacher.to_automaton(&alphabet(symbols)).unwrap();
+        let teacher = teacher.with_exact_equivalence(&alphabet(symbols)).unwrap();
+        (Learner::new(alphabet(symbols), Box::new(teacher)), target)
+    }
+
+    #[test]
+    fn counterexamples_are_prefix_closed_and_learning_converges() {
+        // The third symbol from the end: eight states, each told apart by a different suffix.
+        let (mut learner, target) = regex_learner("(a|b)*a(a|b)(a|b)", &["a", "b"]);
+        let learned = learn_within(&mut learner, 10);
+
+        assert!(learned.equivalent(&target));
+        assert_eq!(learned.state_count(), 8);
+        let prefixes = &learner.observation_table().s_prefixes;
+        for prefix in prefixes {
+            for end in 1..prefix.len() {
+                assert!(prefixes.contains(&prefix[..end]), "{:?} is in S but its prefix is not", prefix);
+            }
+        }
+    }
+
+    #[test]
+    fn repairs_terminate_on_a_tricky_regex() {
+        // The first counterexample leaves the table inconsistent twice over, so closedness and
+        // consistency repairs have to interleave.
+        let pattern = "(a|bb|bab)*a";
+        for table_filling in [TableFilling::Eager, TableFilling::Lazy] {
+            for handling in [CounterexampleHandling::Prefixes, CounterexampleHandling::Suffixes] {
+                let (learner, target) = regex_learner(pattern, &["a", "b"]);
+                let mut learner = learner.with_table_filling(table_filling).with_counterexample_handling(handling);
+                let learned = learn_within(&mut learner, 10);
+                assert!(learned.equivalent(&target), "{:?} with {:?}", table_filling, handling);
+                assert_eq!(learned.state_count(), 5);
+            }
+        }
+    }
+
+    #[test]
+    fn suffix_compaction_drops_columns_but_not_the_hypothesis() {
+        let pattern = "(a|bb|bab)*a";
+        let (mut plain, _) = regex_learner(pattern, &["a", "b"]);
+        let plain_learned = learn_within(&mut plain, 10);
+        let (compacting, _) = regex_learner(pattern, &["a", "b"]);
+        let mut compacting = compacting.with_suffix_compaction(true);
+        let compacted = learn_within(&mut compacting, 10);
+
+        assert_eq!(compacted, plain_learned);
+        let columns = compacting.observation_table().suffixes().len();
+        assert!(columns < plain.observation_table().suffixes().len(), "{} columns left", columns);
+    }
+
+    #[test]
+    fn an_empty_alphabet_gives_one_state() {
+        use crate::teachers::fn_teacher::FnTeacher;
+
+        for accepts_empty in [true, false] {
+            let teacher = FnTeacher::new(move |word: &[String]| accepts_empty && word.is_empty());
+            let mut learner = Learner::new(alphabet(&[]), Box::new(teacher));
+            let learned = learn_within(&mut learner, 3);
+            assert_eq!(learned.state_count(), 1);
+            assert_eq!(learned.transition_count(), 0);
+            assert_eq!(learned.accepts(&[]), accepts_empty);
+        }
+    }
+
+    #[test]
+    fn the_universal_and_the_empty_language_give_one_state() {
+        // `[a&&b]` is an empty class, so the second pattern matches nothing.
+        for (pattern, universal) in [("^(a|b)*$", true), ("[a&&b]", false)] {
+            let (mut learner, target) = regex_learner(pattern, &["a", "b"]);
+            let learned = learn_within(&mut learner, 3);
+            assert!(learned.equivalent(&target), "{}", pattern);
+            assert_eq!(learned.state_count(), 1, "{}", pattern);
+            assert_eq!(learned.accepts(&[]), universal);
+            assert_eq!(learned.accepts(&["b", "a", "b"].map(String::from)), universal);
+        }
+    }
+
+    #[test]
+    fn lazy_filling_asks_fewer_queries_over_a_wide_alphabet() {
+        use crate::teachers::counting_teacher::CountingTeacher;
+        use std::rc::Rc;
+
+        // Rows of S that the others differ from early on are never filled in completely.
+        let symbols = Alphabet::new(('a'..='z').map(String::from));
+        let mut queries = Vec::new();
+        for table_filling in [TableFilling::Eager, TableFilling::Lazy] {
+            let teacher = RegexTeacher::new_anchored("hello|world|help").unwrap().with_exact_equivalence(&symbols).unwrap();
+            let teacher = Rc::new(CountingTeacher::new(teacher));
+            let mut learner = Learner::new(symbols.clone(), Box::new(Rc::clone(&teacher))).with_table_filling(table_filling);
+            let learned = learn_within(&mut learner, 10);
+            assert_eq!(learned.state_count(), 11, "{:?}", table_filling);
+            queries.push(teacher.stats().membership_queries);
+        }
+        let (eager, lazy) = (queries[0], queries[1]);
+        assert!(lazy < eager, "lazy filling asked {} queries, eager {}", lazy, eager);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_loaded_learner_resumes_without_asking_again() {
+        let (mut learner, target) = regex_learner("(a|b)*a(a|b)", &["a", "b"]);
+        learn_within(&mut learner, 10);
+        let mut checkpoint = Vec::new();
+        learner.save(&mut checkpoint).unwrap();
+
+        let teacher = RegexTeacher::new_anchored("(a|b)*a(a|b)").unwrap().with_exact_equivalence(&alphabet(&["a", "b"])).unwrap();
+        let mut loaded = Learner::load(checkpoint.as_slice(), Box::new(teacher)).unwrap();
+        assert_eq!(loaded.observation_table().s_prefixes, learner.observation_table().s_prefixes);
+        let learned = learn_within(&mut loaded, 1);
+        assert!(learned.equivalent(&target));
+        assert_eq!(loaded.membership_queries(), 0);
+    }
+
+    #[cfg(feature = "async")]
+    mod learn_async {
+        use super::*;
+        use crate::teacher::{AsyncTeacher, EquivalenceAnswer};
+        use std::cell::Cell;
+        use std::future::Future;
+        use std::pin::{pin, Pin};
+        use std::task::{Context, Poll, Waker};
+
+        // Pending `polls` times before finishing, waking itself each time.
+        struct Delay {
+            polls: usize,
+        }
+
+        impl Future for Delay {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.polls == 0 {
+                    return Poll::Ready(());
                 }
-                _ => {
-                    // If no counterexample was provided, we can continue learning
-                    panic!("Unexpected response from teacher");
+                self.polls -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+
+        // Polls `future` to completion, returning how many polls it took: the time the
+        // learner spent waiting, with every query taking as long as its `Delay`.
+        fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+            let mut future = pin!(future);
+            let mut cx = Context::from_waker(Waker::noop());
+            let mut polls = 1;
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return (output, polls);
                 }
+                polls += 1;
             }
         }
+
+        // A regex teacher whose membership queries take a while, recording how many were in
+        // flight at once.
+        struct SlowTeacher {
+            inner: RegexTeacher,
+            in_flight: Cell<usize>,
+            max_in_flight: Cell<usize>,
+        }
+
+        impl SlowTeacher {
+            fn new(pattern: &str) -> Self {
+                let inner = RegexTeacher::new_anchored(pattern).unwrap().with_exact_equivalence(&alphabet(&["a", "b"])).unwrap();
+                SlowTeacher { inner, in_flight: Cell::new(0), max_in_flight: Cell::new(0) }
+            }
+        }
+
+        impl AsyncTeacher<String> for SlowTeacher {
+
+            async fn membership_query(&self, states: &[String]) -> Result<bool, TeacherError> {
+                self.in_flight.set(self.in_flight.get() + 1);
+                self.max_in_flight.set(self.max_in_flight.get().max(self.in_flight.get()));
+                Delay { polls: 16 }.await;
+                self.in_flight.set(self.in_flight.get() - 1);
+                self.inner.try_membership_query(states)
+            }
+
+            async fn equivalence_query(&self, automaton: Automaton<Vec<String>, String>) -> Result<EquivalenceAnswer<String>, TeacherError> {
+                self.inner.try_equivalence_query(automaton)
+            }
+        }
+
+        fn learn(concurrency: usize) -> (usize, usize) {
+            let teacher = SlowTeacher::new("(a|b)*a(a|b)(a|b)");
+            let mut learner = Learner::new_async(alphabet(&["a", "b"]));
+            let (learned, polls) = block_on(learner.learn_async(&teacher, concurrency));
+            assert_eq!(learned.unwrap().state_count(), 8);
+            (teacher.max_in_flight.get(), polls)
+        }
+
+        #[test]
+        fn queries_overlap_up_to_the_concurrency() {
+            let (sequential_in_flight, sequential_polls) = learn(1);
+            let (concurrent_in_flight, concurrent_polls) = learn(8);
+            assert_eq!(sequential_in_flight, 1);
+            assert_eq!(concurrent_in_flight, 8);
+            // Closedness repairs add one row at a time, and so batches of only a few cells, so
+            // the waiting is not cut by the full factor of 8.
+            assert!(concurrent_polls * 3 < sequential_polls * 2, "{} polls with 8 in flight, {} with 1", concurrent_polls, sequential_polls);
+        }
     }
 }
@@ -5,9 +5,48 @@ https://people.eecs.berkeley.edu/~dawnsong/teaching/s10/papers/angluin87.pdf
  */
 
 pub mod learner;
-mod automaton;
-mod teacher;
+pub mod mealy_learner;
+pub mod metrics;
+pub mod alphabet;
+pub mod automaton;
+pub mod nfa;
+pub mod teacher;
+pub mod regex_compile;
+mod random;
 
 pub mod teachers{
     pub mod regex_teacher;
+    pub mod file_teacher;
+    pub mod dataset_teacher;
+    pub mod dfa_teacher;
+    pub mod finite_language_teacher;
+    pub mod random_word_oracle;
+    pub mod random_walk_oracle;
+    pub mod mutation_oracle;
+    pub mod corpus_oracle;
+    pub mod w_method_oracle;
+    pub mod wp_method_oracle;
+    pub mod stream_teacher;
+    pub mod recording_teacher;
+    pub mod fn_teacher;
+    pub mod caching_teacher;
+    pub mod counting_teacher;
+    pub mod budget_teacher;
+    pub mod throttled_teacher;
+    pub mod interactive_teacher;
+    pub mod majority_vote_teacher;
+    pub mod chained_oracle;
+    pub mod reverse_teacher;
+    pub mod complement_teacher;
+    pub mod agreement_teacher;
+    pub mod examples;
+    pub mod conformance;
+    pub mod process_teacher;
+    pub mod sul_teacher;
+    pub mod mealy_machine_teacher;
+    pub mod moore_machine_teacher;
+    pub mod byte_teacher;
+    pub mod symbol_class_teacher;
+    #[cfg(feature = "http")]
+    pub mod http_teacher;
 }
\ No newline at end of file
@@ -1,25 +1,54 @@
-use std::collections::HashSet;
+use std::error::Error;
 use std::fs::File;
 use std::io::Write;
+use std::process::ExitCode;
 
 use l_star::teachers::regex_teacher::{RegexTeacher};
 use l_star::learner::Learner;
+use l_star::alphabet::Alphabet;
+use l_star::teachers::byte_teacher::{Byte, ByteTeacher};
+
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
 
+fn run() -> Result<(), Box<dyn Error>> {
 
-fn main() {
+    let regex_teacher = RegexTeacher::new("^(b*ab*){1}(b*ab*b*ab*){0,}$")?
+        .with_bounded_equivalence(8);
 
-    let regex_teacher = RegexTeacher::new(
-        "^(b*ab*){1}(b*ab*b*ab*){0,}$".to_string());
+    let alphabet = regex_teacher.inferred_alphabet()?;
 
-    let mut learner = Learner::new(
-        HashSet::from(["a".to_string(), "b".to_string()]), 
-        Box::new(regex_teacher));
+    let mut learner = Learner::new(alphabet, Box::new(regex_teacher));
 
-    let learnt_hypothesis = learner.learn();
+    let learnt_hypothesis = learner.learn()?;
 
-    let mut file = File::create("hypothesis.dot").expect("Unable to create file");
-    file.write_all(learnt_hypothesis.to_dot().as_bytes())
-        .expect("Unable to write to file");
+    let mut file = File::create("hypothesis.dot")?;
+    file.write_all(learnt_hypothesis.to_dot().as_bytes())?;
     println!("Hypothesis written to hypothesis.dot");
 
+    // Frames of a binary protocol: a 0x7e flag, any payload of 0x00 and 0x01, another flag.
+    let byte_teacher = ByteTeacher::new(|bytes| {
+        bytes.len() >= 2 && bytes[0] == 0x7e && bytes[bytes.len() - 1] == 0x7e
+            && bytes[1..bytes.len() - 1].iter().all(|byte| *byte != 0x7e)
+    });
+
+    let mut learner = Learner::new(
+        Alphabet::new([0x00, 0x01, 0x7e].map(Byte::from)),
+        Box::new(byte_teacher));
+
+    let learnt_hypothesis = learner.learn()?;
+
+    let mut file = File::create("byte_hypothesis.dot")?;
+    file.write_all(learnt_hypothesis.to_dot().as_bytes())?;
+    println!("Hypothesis written to byte_hypothesis.dot");
+
+    Ok(())
 }
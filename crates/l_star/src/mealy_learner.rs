@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::alphabet::Alphabet;
+use crate::automaton::{MealyMachine, MealyState};
+use crate::learner::CounterexampleHandling;
+use crate::teacher::{MealyTeacher, TeacherError};
+
+
+/*
+L* for Mealy machines, after Niese and Shahbaz–Groz. A cell of the table holds the outputs of
+the suffix rather than acceptance, and E starts with every single input, whose outputs label
+the transitions of the hypothesis. There is no empty suffix and no padding: a row of S ∪ S·A
+is its outputs for each suffix of E, and the empty word is the empty vector.
+ */
+/// Learns a Mealy machine from a `MealyTeacher`. Counterexamples are worked in as
+/// `with_counterexample_handling` says; `CounterexampleHandling::Suffixes` is the usual choice
+/// for Mealy machines, since long counterexamples then add columns instead of many rows.
+pub struct MealyLearner<I, O> {
+    inputs: Alphabet<I>,
+    teacher: Box<dyn MealyTeacher<I, O>>,
+    counterexample_handling: CounterexampleHandling,
+    s_prefixes: Vec<Vec<I>>,
+    e_suffixes: Vec<Vec<I>>,
+    // The answer to every output query asked so far.
+    outputs: HashMap<Vec<I>, Vec<O>>,
+    equivalence_queries: usize,
+}
+
+impl<I: Eq + Hash + Clone + Debug, O: Eq + Clone + Debug> MealyLearner<I, O> {
+    pub fn new(inputs: impl Into<Alphabet<I>>, teacher: Box<dyn MealyTeacher<I, O>>) -> Self {
+        let inputs = inputs.into();
+        let e_suffixes = inputs.iter().map(|input| vec![input.clone()]).collect();
+        MealyLearner {
+            inputs,
+            teacher,
+            counterexample_handling: CounterexampleHandling::default(),
+            s_prefixes: vec![Vec::new()],
+            e_suffixes,
+            outputs: HashMap::new(),
+            equivalence_queries: 0,
+        }
+    }
+
+    pub fn with_counterexample_handling(mut self, handling: CounterexampleHandling) -> Self {
+        self.counterexample_handling = handling;
+        self
+    }
+
+    /// Output queries asked so far. No word is asked about twice.
+    pub fn output_queries(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn equivalence_queries(&self) -> usize {
+        self.equivalence_queries
+    }
+
+    /// Runs rounds until the teacher accepts a hypothesis, and returns it. States are named
+    /// after the first prefix of S that reaches them, the empty word for the initial state.
+    pub fn learn(&mut self) -> Result<MealyMachine<Vec<I>, I, O>, TeacherError> {
+        loop {
+            self.close()?;
+            let hypothesis = self.hypothesis()?;
+            self.equivalence_queries += 1;
+            match self.teacher.try_equivalence_query(&hypothesis)? {
+                Ok(()) => return Ok(hypothesis),
+                Err(counterexample) => self.add_counterexample(&hypothesis, &counterexample)?,
+            }
+        }
+    }
+
+    // The outputs for the `suffix` part of `prefix·suffix`.
+    fn cell(&mut self, prefix: &[I], suffix: &[I]) -> Result<Vec<O>, TeacherError> {
+        let word = [prefix, suffix].concat();
+        if !self.outputs.contains_key(&word) {
+            let outputs = self.teacher.try_output_query(&word)?;
+            if outputs.len() != word.len() {
+                return Err(TeacherError::Other(format!("output query for {} inputs got {} outputs", word.len(), outputs.len())));
+            }
+            self.outputs.insert(word.clone(), outputs);
+        }
+        Ok(self.outputs[&word][prefix.len()..].to_vec())
+    }
+
+    fn row(&mut self, prefix: &[I]) -> Result<Vec<Vec<O>>, TeacherError> {
+        let suffixes = self.e_suffixes.clone();
+        suffixes.iter().map(|suffix| self.cell(prefix, suffix)).collect()
+    }
+
+    fn extensions(&self, prefix: &[I]) -> Vec<Vec<I>> {
+        self.inputs.iter().map(|input| [prefix, std::slice::from_ref(input)].concat()).collect()
+    }
+
+    // Repairs the table until it is closed and consistent, closedness first, checking both
+    // again after every change as the DFA learner does.
+    fn close(&mut self) -> Result<(), TeacherError> {
+        'repair: loop {
+            let prefixes = self.s_prefixes.clone();
+            let mut rows = Vec::with_capacity(prefixes.len());
+            for prefix in &prefixes {
+                rows.push(self.row(prefix)?);
+            }
+
+            for prefix in &prefixes {
+                for extended in self.extensions(prefix) {
+                    if !self.s_prefixes.contains(&extended) && !rows.contains(&self.row(&extended)?) {
+                        self.s_prefixes.push(extended);
+                        continue 'repair;
+                    }
+                }
+            }
+
+            for (index, first) in prefixes.iter().enumerate() {
+                for (second, row) in prefixes.iter().zip(&rows).skip(index + 1) {
+                    if *row != rows[index] {
+                        continue;
+                    }
+                    for input in self.inputs.symbols().to_vec() {
+                        for suffix in self.e_suffixes.clone() {
+                            let first_extended = [first.as_slice(), std::slice::from_ref(&input)].concat();
+                            let second_extended = [second.as_slice(), std::slice::from_ref(&input)].concat();
+                            if self.cell(&first_extended, &suffix)? != self.cell(&second_extended, &suffix)? {
+                                // `first` and `second` are told apart by input·suffix.
+                                self.e_suffixes.push([std::slice::from_ref(&input), suffix.as_slice()].concat());
+                                continue 'repair;
+                            }
+                        }
+                    }
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    // One state per distinct row of S, named after the first prefix in S that has it.
+    fn hypothesis(&mut self) -> Result<MealyMachine<Vec<I>, I, O>, TeacherError> {
+        let mut representatives: Vec<(Vec<Vec<O>>, Vec<I>)> = Vec::new();
+        for prefix in self.s_prefixes.clone() {
+            let row = self.row(&prefix)?;
+            if !representatives.iter().any(|(known, _)| *known == row) {
+                representatives.push((row, prefix));
+            }
+        }
+
+        let mut hypothesis = MealyMachine::new(MealyState::new(Vec::new()));
+        for (_, prefix) in &representatives {
+            for input in self.inputs.symbols().to_vec() {
+                let extended = [prefix.as_slice(), std::slice::from_ref(&input)].concat();
+                let row = self.row(&extended)?;
+                // Closedness guarantees a representative.
+                let (_, target) = representatives.iter().find(|(known, _)| *known == row).expect("a closed table has every row in S");
+                let output = self.cell(prefix, std::slice::from_ref(&input))?.remove(0);
+                hypothesis.add_transition(&MealyState::new(prefix.clone()), &MealyState::new(target.clone()), &input, &output);
+            }
+        }
+        Ok(hypothesis)
+    }
+
+    fn add_counterexample(&mut self, hypothesis: &MealyMachine<Vec<I>, I, O>, counterexample: &[I]) -> Result<(), TeacherError> {
+        // A word the hypothesis already gets right would change nothing and be returned again.
+        if hypothesis.run(counterexample) == self.cell(&[], counterexample)? {
+            return Err(TeacherError::Other(format!("{:?} is no counterexample: the hypothesis gives the target's outputs", counterexample)));
+        }
+        match self.counterexample_handling {
+            CounterexampleHandling::Prefixes => {
+                for end in 1..=counterexample.len() {
+                    let prefix = counterexample[..end].to_vec();
+                    if !self.s_prefixes.contains(&prefix) {
+                        self.s_prefixes.push(prefix);
+                    }
+                }
+            }
+            CounterexampleHandling::Suffixes => {
+                // Strip the longest prefix in S ∪ S·A; every suffix of the rest joins E.
+                let known = |end: usize| self.s_prefixes.iter().any(|prefix| *prefix == counterexample[..end] || (end > 0 && *prefix == counterexample[..end - 1]));
+                let start = (0..=counterexample.len()).rev().find(|end| known(*end)).unwrap_or(0);
+                for begin in start..counterexample.len() {
+                    let suffix = counterexample[begin..].to_vec();
+                    if !self.e_suffixes.contains(&suffix) {
+                        self.e_suffixes.push(suffix);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::teacher::EquivalenceAnswer;
+    use crate::teachers::mealy_machine_teacher::MealyMachineTeacher;
+
+    // A counter modulo `modulus` on `+`, reset by `0`, that outputs whether the count wrapped.
+    fn wrapping_counter(modulus: usize) -> MealyMachine<usize, char, bool> {
+        let state = MealyState::new;
+        let mut machine = MealyMachine::new(state(0));
+        for count in 0..modulus {
+            machine.add_transition(&state(count), &state((count + 1) % modulus), &'+', &(count + 1 == modulus));
+            machine.add_transition(&state(count), &state(0), &'0', &false);
+        }
+        machine
+    }
+
+    // Returns a counterexample padded with resets in front, as a random-walk oracle might.
+    struct LongCounterexamples(MealyMachineTeacher<usize, char, bool>);
+
+    impl MealyTeacher<char, bool> for LongCounterexamples {
+
+        fn output_query(&mut self, word: &[char]) -> Vec<bool> {
+            self.0.output_query(word)
+        }
+
+        fn equivalence_query(&mut self, hypothesis: &MealyMachine<Vec<char>, char, bool>) -> EquivalenceAnswer<char> {
+            self.0.equivalence_query(hypothesis).map_err(|counterexample| ['+', '0'].repeat(8).into_iter().chain(counterexample).collect())
+        }
+    }
+
+    #[test]
+    fn learns_the_target_with_either_handling() {
+        for handling in [CounterexampleHandling::Prefixes, CounterexampleHandling::Suffixes] {
+            let teacher = LongCounterexamples(MealyMachineTeacher::new(wrapping_counter(5)));
+            let mut learner = MealyLearner::new(Alphabet::new(['+', '0']), Box::new(teacher)).with_counterexample_handling(handling);
+            let learned = learner.learn().unwrap();
+            assert!(learned.is_equivalent(&wrapping_counter(5)), "{:?}", handling);
+            assert_eq!(learned.get_states().len(), 5);
+        }
+    }
+
+    // Always outputs `false`, but calls `+` a counterexample to every hypothesis.
+    struct Wrong;
+
+    impl MealyTeacher<char, bool> for Wrong {
+
+        fn output_query(&mut self, word: &[char]) -> Vec<bool> {
+            vec![false; word.len()]
+        }
+
+        fn equivalence_query(&mut self, _hypothesis: &MealyMachine<Vec<char>, char, bool>) -> EquivalenceAnswer<char> {
+            Err(vec!['+'])
+        }
+    }
+
+    #[test]
+    fn a_word_the_hypothesis_gets_right_is_refused() {
+        let mut learner = MealyLearner::new(Alphabet::new(['+']), Box::new(Wrong));
+        assert!(matches!(learner.learn(), Err(TeacherError::Other(_))));
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+use crate::alphabet::Alphabet;
+use crate::automaton::Automaton;
+use crate::teacher::Teacher;
+
+
+/// How well a hypothesis agrees with a teacher on a set of test words, counting the teacher's
+/// answer as the truth. Useful when learning with an approximate equivalence oracle, where the
+/// result need not be exact.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub true_negatives: usize,
+    pub false_negatives: usize,
+}
+
+impl Metrics {
+    /// Classifies every word in `words` with both `hypothesis` and `teacher`. The empty word is
+    /// passed to the teacher as an empty slice.
+    pub fn measure<StateId, T>(hypothesis: &Automaton<StateId, T>, teacher: &dyn Teacher<T>, words: impl IntoIterator<Item = Vec<T>>) -> Metrics
+    where
+        StateId: Eq + Hash + Clone,
+        T: Eq + Hash + Clone + Debug + Default,
+    {
+        let mut metrics = Metrics::default();
+        for word in words {
+            let predicted = hypothesis.accepts(&word);
+            match (predicted, teacher.membership_query(&word)) {
+                (true, true) => metrics.true_positives += 1,
+                (true, false) => metrics.false_positives += 1,
+                (false, false) => metrics.true_negatives += 1,
+                (false, true) => metrics.false_negatives += 1,
+            }
+        }
+        metrics
+    }
+
+    /// `measure` over every word on `alphabet` of length at most `max_len`.
+    pub fn exhaustive<StateId, T>(hypothesis: &Automaton<StateId, T>, teacher: &dyn Teacher<T>, alphabet: &Alphabet<T>, max_len: usize) -> Metrics
+    where
+        StateId: Eq + Hash + Clone,
+        T: Eq + Hash + Clone + Debug + Default,
+    {
+        let mut words: Vec<Vec<T>> = vec![Vec::new()];
+        let mut layer: Vec<Vec<T>> = vec![Vec::new()];
+        for _ in 0..max_len {
+            layer = layer.iter()
+                .flat_map(|word| alphabet.iter().map(move |a| {
+                    let mut next = word.clone();
+                    next.push(a.clone());
+                    next
+                }))
+                .collect();
+            words.extend(layer.iter().cloned());
+        }
+        Metrics::measure(hypothesis, teacher, words)
+    }
+
+    pub fn total(&self) -> usize {
+        self.true_positives + self.false_positives + self.true_negatives + self.false_negatives
+    }
+
+    /// Share of accepted words that the teacher accepts too; 1 when nothing is accepted.
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    /// Share of the teacher's positive words that are accepted; 1 when there are none.
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.true_positives + self.true_negatives, self.total())
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "precision {:.4}, recall {:.4}, F1 {:.4} over {} words", self.precision(), self.recall(), self.f1(), self.total())
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        1.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::automaton::{Automaton, DfaState};
+
+
+#[derive(Clone, Debug)]
+pub struct NfaState<StateId, TransitionLabel> {
+    state_id: StateId,
+    is_accepting: bool,
+    transitions: HashMap<TransitionLabel, HashSet<StateId>>,
+    epsilon_transitions: HashSet<StateId>,
+}
+
+impl<StateId, TransitionLabel> NfaState<StateId, TransitionLabel> {
+    pub fn new(state_id: StateId, is_accepting: bool) -> Self {
+        NfaState {
+            state_id,
+            is_accepting,
+            transitions: HashMap::new(),
+            epsilon_transitions: HashSet::new(),
+        }
+    }
+
+    pub fn get_state_id(&self) -> &StateId {
+        &self.state_id
+    }
+
+    pub fn is_accepting(&self) -> bool {
+        self.is_accepting
+    }
+}
+
+
+/// A nondeterministic automaton with epsilon moves, used as the intermediate form for
+/// constructions such as concatenation and Kleene star before determinizing back to an
+/// `Automaton`. Unlike `Automaton` it may start in several states at once.
+#[derive(Clone, Debug)]
+pub struct Nfa<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone> {
+    states: HashMap<StateId, NfaState<StateId, TransitionLabel>>,
+    initial_states: HashSet<StateId>,
+}
+
+impl<StateId: Eq + Hash + Clone, TransitionLabel: Eq + Hash + Clone> Nfa<StateId, TransitionLabel> {
+
+    pub fn new(initial_state: StateId, is_accepting: bool) -> Self {
+        let mut states = HashMap::new();
+        states.insert(initial_state.clone(), NfaState::new(initial_state.clone(), is_accepting));
+
+        Nfa {
+            states,
+            initial_states: HashSet::from([initial_state]),
+        }
+    }
+
+    /// An NFA starting in all of `initial_states`, none of them accepting yet. Without any, it
+    /// accepts nothing.
+    pub fn from_initial_states(initial_states: impl IntoIterator<Item = StateId>) -> Self {
+        let mut nfa = Nfa { states: HashMap::new(), initial_states: HashSet::new() };
+        for state_id in initial_states {
+            nfa.add_initial_state(state_id);
+        }
+        nfa
+    }
+
+    pub fn get_states(&self) -> &HashMap<StateId, NfaState<StateId, TransitionLabel>> {
+        &self.states
+    }
+
+    pub fn get_initial_states(&self) -> &HashSet<StateId> {
+        &self.initial_states
+    }
+
+    /// Makes `state_id` initial too, adding it as a rejecting state if it is new.
+    pub fn add_initial_state(&mut self, state_id: StateId) {
+        self.ensure_state(&state_id);
+        self.initial_states.insert(state_id);
+    }
+
+    pub fn add_state(&mut self, state_id: StateId, is_accepting: bool) {
+        if !self.states.contains_key(&state_id) {
+            self.states.insert(state_id.clone(), NfaState::new(state_id, is_accepting));
+        }
+    }
+
+    pub fn set_accepting(&mut self, state_id: &StateId, is_accepting: bool) {
+        if let Some(state) = self.states.get_mut(state_id) {
+            state.is_accepting = is_accepting;
+        }
+    }
+
+    pub fn add_transition(&mut self, from: &StateId, to: &StateId, transition_label: &TransitionLabel) {
+        self.ensure_state(to);
+        self.ensure_state(from)
+            .transitions
+            .entry(transition_label.clone())
+            .or_default()
+            .insert(to.clone());
+    }
+
+    pub fn add_epsilon_transition(&mut self, from: &StateId, to: &StateId) {
+        self.ensure_state(to);
+        self.ensure_state(from).epsilon_transitions.insert(to.clone());
+    }
+
+    fn ensure_state(&mut self, state_id: &StateId) -> &mut NfaState<StateId, TransitionLabel> {
+        self.states
+            .entry(state_id.clone())
+            .or_insert_with(|| NfaState::new(state_id.clone(), false))
+    }
+
+    /// All states reachable from `states` using only epsilon moves, `states` included.
+    pub fn epsilon_closure<'a>(&self, states: impl IntoIterator<Item = &'a StateId>) -> HashSet<StateId>
+    where
+        StateId: 'a,
+    {
+        let mut closure: HashSet<StateId> = HashSet::new();
+        let mut stack: Vec<&StateId> = states.into_iter().collect();
+
+        while let Some(state_id) = stack.pop() {
+            if closure.insert(state_id.clone())
+                && let Some(state) = self.states.get(state_id) {
+                    stack.extend(state.epsilon_transitions.iter());
+                }
+        }
+        closure
+    }
+
+    /// Whether some run on `word` ends in an accepting state, following the set of states
+    /// the input could have led to.
+    pub fn accepts(&self, word: &[TransitionLabel]) -> bool {
+        let mut current = self.epsilon_closure(&self.initial_states);
+        for symbol in word {
+            let targets: Vec<&StateId> = current.iter()
+                .filter_map(|state_id| self.states[state_id].transitions.get(symbol))
+                .flatten()
+                .collect();
+            current = self.epsilon_closure(targets);
+            if current.is_empty() {
+                return false;
+            }
+        }
+        current.iter().any(|state_id| self.states[state_id].is_accepting())
+    }
+}
+
+impl<StateId, TransitionLabel> Nfa<StateId, TransitionLabel>
+where
+    StateId: Eq + Hash + Clone + Ord,
+    TransitionLabel: Eq + Hash + Clone,
+{
+    /// Converts to an equivalent DFA by the subset construction.
+    ///
+    /// Every DFA state is identified by the sorted set of NFA states the input could have led
+    /// to, and is accepting when any of its members is. Only subsets reachable from the epsilon
+    /// closure of the initial states are built. The empty subset is only materialized as the
+    /// initial state of an NFA without initial states, so words that fall off the NFA simply
+    /// have no transition in the result.
+    pub fn determinize(&self) -> Automaton<Vec<StateId>, TransitionLabel> {
+        let start = self.subset_id(self.epsilon_closure(&self.initial_states));
+        let mut automaton = Automaton::new(DfaState::new(start.clone(), self.is_accepting_subset(&start)));
+
+        let mut seen: HashSet<Vec<StateId>> = HashSet::from([start.clone()]);
+        let mut queue: VecDeque<Vec<StateId>> = VecDeque::from([start]);
+
+        while let Some(subset) = queue.pop_front() {
+            let mut moves: HashMap<&TransitionLabel, HashSet<&StateId>> = HashMap::new();
+            for state_id in &subset {
+                for (label, targets) in &self.states[state_id].transitions {
+                    moves.entry(label).or_default().extend(targets);
+                }
+            }
+
+            let from = DfaState::new(subset.clone(), self.is_accepting_subset(&subset));
+            for (label, targets) in moves {
+                let target = self.subset_id(self.epsilon_closure(targets));
+                let to = DfaState::new(target.clone(), self.is_accepting_subset(&target));
+                automaton.add_transition(&from, &to, label);
+
+                if seen.insert(target.clone()) {
+                    queue.push_back(target);
+                }
+            }
+        }
+        automaton
+    }
+
+    fn subset_id(&self, states: HashSet<StateId>) -> Vec<StateId> {
+        let mut subset: Vec<StateId> = states.into_iter().collect();
+        subset.sort();
+        subset
+    }
+
+    fn is_accepting_subset(&self, subset: &[StateId]) -> bool {
+        subset.iter().any(|state_id| self.states[state_id].is_accepting())
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use crate::alphabet::Alphabet;
+use crate::automaton::{Automaton, DfaState};
+use crate::nfa::Nfa;
+
+
+/// Why `compile` rejected a pattern. Positions count characters from 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegexCompileError {
+    /// The pattern ended where more was expected, e.g. inside a group or class.
+    UnexpectedEnd,
+    /// A character that cannot stand where it does, e.g. an unmatched `)` or a leading `*`.
+    Unexpected { position: usize, found: char },
+    /// `^` other than at the start of a top-level alternative, or `$` other than at its end.
+    MisplacedAnchor { position: usize },
+    /// A construct outside the supported subset, such as a backreference or lookaround.
+    Unsupported { position: usize, construct: String },
+}
+
+impl fmt::Display for RegexCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexCompileError::UnexpectedEnd => write!(f, "pattern ends unexpectedly"),
+            RegexCompileError::Unexpected { position, found } => write!(f, "unexpected {:?} at {}", found, position),
+            RegexCompileError::MisplacedAnchor { position } => write!(f, "anchor at {} is not at the start or end of an alternative", position),
+            RegexCompileError::Unsupported { position, construct } => write!(f, "{} at {} is not supported", construct, position),
+        }
+    }
+}
+
+impl std::error::Error for RegexCompileError {}
+
+// A parsed pattern. Character sets are already restricted to the alphabet.
+#[derive(Clone, Debug)]
+enum Node {
+    Empty,
+    Set(Vec<char>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat { node: Box<Node>, min: u32, max: Option<u32> },
+}
+
+// A top-level alternative with its anchors.
+struct Branch {
+    node: Node,
+    starts_anchored: bool,
+    ends_anchored: bool,
+}
+
+// The largest bound accepted in `{n,m}`, to keep the expanded NFA reasonable.
+const MAX_REPETITION: u32 = 1000;
+
+/*
+Recursive descent over the characters of the pattern:
+
+    pattern := branch ('|' branch)*           (top level only: '^'? concat '$'?)
+    alt     := concat ('|' concat)*
+    concat  := repeat*
+    repeat  := atom ('*' | '+' | '?' | '{n}' | '{n,}' | '{n,m}')*
+    atom    := '(' ('?:')? alt ')' | '[' '^'? class ']' | '.' | '\' escape | literal
+ */
+struct Parser<'a> {
+    pattern: Vec<char>,
+    position: usize,
+    alphabet: &'a Alphabet<char>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.pattern.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Result<char, RegexCompileError> {
+        let c = self.peek().ok_or(RegexCompileError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn unexpected(&self) -> RegexCompileError {
+        match self.peek() {
+            Some(found) => RegexCompileError::Unexpected { position: self.position, found },
+            None => RegexCompileError::UnexpectedEnd,
+        }
+    }
+
+    // The alphabet symbols `matches` accepts.
+    fn set(&self, matches: impl Fn(char) -> bool) -> Node {
+        Node::Set(self.alphabet.iter().copied().filter(|c| matches(*c)).collect())
+    }
+
+    fn pattern(&mut self) -> Result<Vec<Branch>, RegexCompileError> {
+        let mut branches = Vec::new();
+        loop {
+            let starts_anchored = self.eat('^');
+            let node = self.concat()?;
+            let ends_anchored = self.eat('$');
+            branches.push(Branch { node, starts_anchored, ends_anchored });
+            match self.peek() {
+                None => return Ok(branches),
+                Some('|') => self.position += 1,
+                Some(_) if ends_anchored => return Err(RegexCompileError::MisplacedAnchor { position: self.position - 1 }),
+                Some('$') => return Err(RegexCompileError::MisplacedAnchor { position: self.position }),
+                Some(_) => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn alt(&mut self) -> Result<Node, RegexCompileError> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 { branches.pop().unwrap() } else { Node::Alt(branches) })
+    }
+
+    fn concat(&mut self) -> Result<Node, RegexCompileError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '|' | ')' | '$' => break,
+                '^' => return Err(RegexCompileError::MisplacedAnchor { position: self.position }),
+                _ => nodes.push(self.repeat()?),
+            }
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn repeat(&mut self) -> Result<Node, RegexCompileError> {
+        let mut node = self.atom()?;
+        loop {
+            let (min, max) = if self.eat('*') {
+                (0, None)
+            } else if self.eat('+') {
+                (1, None)
+            } else if self.eat('?') {
+                (0, Some(1))
+            } else if self.eat('{') {
+                self.bounds()?
+            } else {
+                return Ok(node);
+            };
+            // Laziness changes where a match is, not whether there is one.
+            self.eat('?');
+            node = Node::Repeat { node: Box::new(node), min, max };
+        }
+    }
+
+    // `n}`, `n,}` or `n,m}` after a `{`.
+    fn bounds(&mut self) -> Result<(u32, Option<u32>), RegexCompileError> {
+        let start = self.position;
+        let min = self.number()?.ok_or_else(|| self.unexpected())?;
+        let max = if self.eat(',') { self.number()? } else { Some(min) };
+        if !self.eat('}') {
+            return Err(self.unexpected());
+        }
+        if max.is_some_and(|max| max < min) || min.max(max.unwrap_or(0)) > MAX_REPETITION {
+            return Err(RegexCompileError::Unsupported { position: start, construct: format!("repetition {{{}}}", self.pattern[start..self.position - 1].iter().collect::<String>()) });
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Result<Option<u32>, RegexCompileError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Ok(None);
+        }
+        let digits: String = self.pattern[start..self.position].iter().collect();
+        digits.parse().map(Some).map_err(|_| RegexCompileError::Unsupported { position: start, construct: format!("repetition count {}", digits) })
+    }
+
+    fn atom(&mut self) -> Result<Node, RegexCompileError> {
+        let position = self.position;
+        match self.next()? {
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err(RegexCompileError::Unsupported { position, construct: "group flag or lookaround".to_string() });
+                }
+                let node = self.alt()?;
+                if self.peek() == Some('$') {
+                    return Err(RegexCompileError::MisplacedAnchor { position: self.position });
+                }
+                if !self.eat(')') {
+                    return Err(self.unexpected());
+                }
+                Ok(node)
+            }
+            '[' => self.class(),
+            '.' => Ok(self.set(|c| c != '\n')),
+            '\\' => {
+                let class = self.escape()?;
+                Ok(self.set(class))
+            }
+            '*' | '+' | '?' | '{' | ')' => {
+                self.position = position;
+                Err(self.unexpected())
+            }
+            literal => Ok(self.set(|c| c == literal)),
+        }
+    }
+
+    // What an escape after `\` matches. Classes are ASCII, e.g. `\w` is `[0-9A-Za-z_]`.
+    fn escape(&mut self) -> Result<Box<dyn Fn(char) -> bool>, RegexCompileError> {
+        let position = self.position;
+        Ok(match self.next()? {
+            'd' => Box::new(|c: char| c.is_ascii_digit()),
+            'D' => Box::new(|c: char| !c.is_ascii_digit()),
+            'w' => Box::new(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+            'W' => Box::new(|c: char| !(c.is_ascii_alphanumeric() || c == '_')),
+            's' => Box::new(|c: char| c.is_ascii_whitespace()),
+            'S' => Box::new(|c: char| !c.is_ascii_whitespace()),
+            'n' => Box::new(|c: char| c == '\n'),
+            't' => Box::new(|c: char| c == '\t'),
+            'r' => Box::new(|c: char| c == '\r'),
+            c if c.is_ascii_alphanumeric() => return Err(RegexCompileError::Unsupported { position: position - 1, construct: format!("escape \\{}", c) }),
+            c => Box::new(move |other: char| other == c),
+        })
+    }
+
+    // A class after `[`, up to and including its `]`.
+    fn class(&mut self) -> Result<Node, RegexCompileError> {
+        let negated = self.eat('^');
+        let mut parts: Vec<Box<dyn Fn(char) -> bool>> = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.next()?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            if c == '[' && self.peek() == Some(':') {
+                return Err(RegexCompileError::Unsupported { position: self.position - 1, construct: "POSIX class".to_string() });
+            }
+            if c == '\\' {
+                parts.push(self.escape()?);
+                continue;
+            }
+            if self.peek() == Some('-') && self.pattern.get(self.position + 1).is_some_and(|end| *end != ']') {
+                self.position += 1;
+                let end = match self.next()? {
+                    '\\' => self.next()?,
+                    end => end,
+                };
+                if end < c {
+                    return Err(RegexCompileError::Unsupported { position: self.position - 1, construct: format!("reversed range {}-{}", c, end) });
+                }
+                parts.push(Box::new(move |other: char| (c..=end).contains(&other)));
+            } else {
+                parts.push(Box::new(move |other: char| other == c));
+            }
+        }
+        Ok(self.set(|c| parts.iter().any(|part| part(c)) != negated))
+    }
+}
+
+// Thompson construction: adds `node` between fresh states and returns them.
+fn build(nfa: &mut Nfa<usize, char>, next_id: &mut usize, node: &Node) -> (usize, usize) {
+    let mut fresh = || {
+        *next_id += 1;
+        *next_id - 1
+    };
+    let (start, end) = (fresh(), fresh());
+    match node {
+        Node::Empty => nfa.add_epsilon_transition(&start, &end),
+        Node::Set(symbols) => {
+            nfa.add_state(start, false);
+            nfa.add_state(end, false);
+            for symbol in symbols {
+                nfa.add_transition(&start, &end, symbol);
+            }
+        }
+        Node::Concat(nodes) => {
+            let mut last = start;
+            for node in nodes {
+                let (inner_start, inner_end) = build(nfa, next_id, node);
+                nfa.add_epsilon_transition(&last, &inner_start);
+                last = inner_end;
+            }
+            nfa.add_epsilon_transition(&last, &end);
+        }
+        Node::Alt(nodes) => {
+            for node in nodes {
+                let (inner_start, inner_end) = build(nfa, next_id, node);
+                nfa.add_epsilon_transition(&start, &inner_start);
+                nfa.add_epsilon_transition(&inner_end, &end);
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            let mut last = start;
+            for _ in 0..*min {
+                let (inner_start, inner_end) = build(nfa, next_id, node);
+                nfa.add_epsilon_transition(&last, &inner_start);
+                last = inner_end;
+            }
+            match max {
+                None => {
+                    let (inner_start, inner_end) = build(nfa, next_id, node);
+                    nfa.add_epsilon_transition(&last, &inner_start);
+                    nfa.add_epsilon_transition(&inner_end, &inner_start);
+                    nfa.add_epsilon_transition(&inner_end, &end);
+                }
+                Some(max) => {
+                    for _ in *min..*max {
+                        let (inner_start, inner_end) = build(nfa, next_id, node);
+                        nfa.add_epsilon_transition(&last, &inner_start);
+                        nfa.add_epsilon_transition(&last, &end);
+                        last = inner_end;
+                    }
+                }
+            }
+            nfa.add_epsilon_transition(&last, &end);
+        }
+    }
+    (start, end)
+}
+
+/// Compiles `pattern` to the minimal DFA over `alphabet` of the words it matches under
+/// `Regex::is_match`, i.e. somewhere inside unless anchored. Supported are concatenation,
+/// `|`, groups `(..)` and `(?:..)`, `*`, `+`, `?`, `{n}`, `{n,}`, `{n,m}`, `.`, classes such as
+/// `[a-c_]` and `[^ab]`, the ASCII escapes `\d \w \s` and their negations, and `^`/`$` at the
+/// start and end of top-level alternatives. Characters outside `alphabet` match nothing.
+pub fn compile(pattern: &str, alphabet: &Alphabet<char>) -> Result<Automaton<usize, char>, RegexCompileError> {
+    let mut parser = Parser { pattern: pattern.chars().collect(), position: 0, alphabet };
+    let branches = parser.pattern()?;
+
+    let mut nfa = Nfa::new(0, false);
+    let mut next_id = 1;
+    let anything = Node::Repeat { node: Box::new(Node::Set(alphabet.symbols().to_vec())), min: 0, max: None };
+    for branch in branches {
+        let mut parts = Vec::new();
+        if !branch.starts_anchored {
+            parts.push(anything.clone());
+        }
+        parts.push(branch.node);
+        if !branch.ends_anchored {
+            parts.push(anything.clone());
+        }
+        let (start, end) = build(&mut nfa, &mut next_id, &Node::Concat(parts));
+        nfa.add_epsilon_transition(&0, &start);
+        nfa.set_accepting(&end, true);
+    }
+    Ok(nfa.determinize().minimize())
+}
+
+/// `compile` for words over multi-character symbols, joined directly as `RegexTeacher` reads
+/// them: the pattern is compiled over the characters of the symbols, and a symbol leads
+/// wherever its spelling does.
+pub fn compile_joined(pattern: &str, alphabet: &Alphabet<String>) -> Result<Automaton<usize, String>, RegexCompileError> {
+    let characters: BTreeSet<char> = alphabet.iter().flat_map(|symbol| symbol.chars()).collect();
+    let dfa = compile(pattern, &Alphabet::new(characters))?;
+    let state = |id: usize| DfaState::new(id, dfa.get_state(&id).is_some_and(|state| state.is_accepting()));
+    let spell = |id: usize, symbol: &String| symbol.chars()
+        .try_fold(id, |id, c| dfa.get_state(&id).and_then(|state| state.transition(&c)).copied());
+
+    let start = *dfa.get_initial_state().expect("compiled automata have their initial state").get_state_id();
+    let mut joined = Automaton::new(state(start));
+    let mut seen = HashSet::from([start]);
+    let mut pending = vec![start];
+    while let Some(id) = pending.pop() {
+        for symbol in alphabet {
+            if let Some(target) = spell(id, symbol) {
+                joined.add_transition(&state(id), &state(target), symbol);
+                if seen.insert(target) {
+                    pending.push(target);
+                }
+            }
+        }
+    }
+    Ok(joined.minimize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|symbol| symbol.to_string()).collect()
+    }
+
+    #[test]
+    fn joined_matches_may_cross_symbol_boundaries() {
+        let alphabet = Alphabet::new(["ab".to_string(), "c".to_string()]);
+        let joined = compile_joined("bc", &alphabet).unwrap();
+        assert!(joined.accepts(&word(&["ab", "c"])));
+        assert!(joined.accepts(&word(&["c", "ab", "c", "c"])));
+        assert!(!joined.accepts(&word(&["c", "ab"])));
+        assert!(!joined.accepts(&word(&[])));
+    }
+}
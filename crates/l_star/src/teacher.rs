@@ -1,12 +1,204 @@
 use std::collections::HashSet;
 use std::hash::Hash;
-use std::fmt::Debug;
+use std::cell::RefCell;
+use std::fmt::{self, Debug};
+use std::rc::Rc;
 
-use crate::automaton::Automaton;
+use crate::automaton::{Automaton, MealyMachine, MooreMachine};
+
+/// Why a teacher could not answer a query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TeacherError {
+    /// The teacher may not be asked any more queries, e.g. because they are billed.
+    BudgetExhausted,
+    /// Any other failure, such as an oracle that cannot be reached.
+    Other(String),
+}
+
+impl fmt::Display for TeacherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TeacherError::BudgetExhausted => write!(f, "query budget exhausted"),
+            TeacherError::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for TeacherError {}
+
+/// The answer to an equivalence query: `Ok(())` for a correct hypothesis, or a counterexample,
+/// a word the hypothesis classifies wrongly.
+pub type EquivalenceAnswer<T> = Result<(), Vec<T>>;
+
+/// Like `EquivalenceAnswer`, but with any number of counterexamples, at least one.
+pub type EquivalenceBatch<T> = Result<(), Vec<Vec<T>>>;
+
+// How oracles built with `from_fn` hold their membership source.
+pub(crate) type Membership<T> = Box<dyn Fn(&[T]) -> bool>;
 
 pub trait Teacher<T: Eq + Hash + Clone + Debug + Default> {
 
-    fn membership_query(&self, states: Vec<T>) -> bool;
+    fn membership_query(&self, states: &[T]) -> bool;
+
+    /// `Ok(())` if the hypothesis is correct, otherwise a counterexample.
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T>;
+
+    /// The old form of `equivalence_query`, which every teacher now implements instead.
+    #[deprecated(note = "call `equivalence_query`, which returns a single counterexample")]
+    fn validate_hypothesis(&self, automaton: Automaton<Vec<T>, T>) -> Result<bool, HashSet<Vec<T>>> {
+        self.equivalence_query(automaton).map(|()| true).map_err(|counterexample| HashSet::from([counterexample]))
+    }
+
+    /// What the learner calls instead of `membership_query`. Teachers that can fail override
+    /// this one, after which the learner stops with a `LearnError`.
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        Ok(self.membership_query(states))
+    }
+
+    /// Answers `words` in order. The learner asks for all the cells a round adds to its table
+    /// in one batch, so override this when batches are cheaper than single queries; by
+    /// default each word goes to `try_membership_query`.
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        words.iter().map(|word| self.try_membership_query(word)).collect()
+    }
+
+    /// What the learner calls instead of `equivalence_query`; see `try_membership_query`.
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        Ok(self.equivalence_query(automaton))
+    }
+
+    /// What the learner actually asks: it adds every counterexample returned to its table
+    /// before building the next hypothesis. Override this when several counterexamples come
+    /// cheaply; by default there is the one from `try_equivalence_query`.
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        Ok(self.try_equivalence_query(automaton)?.map_err(|counterexample| vec![counterexample]))
+    }
+
+    /// `try_equivalence_query_batch` in the shape of `validate_hypothesis`: `Ok(true)` if the
+    /// hypothesis is correct, never `Ok(false)`, otherwise every counterexample. Panics if the
+    /// teacher fails, like `equivalence_query`.
+    fn validate_hypothesis_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<bool, Vec<Vec<T>>> {
+        self.try_equivalence_query_batch(automaton).unwrap_or_else(|err| panic!("{}", err)).map(|()| true)
+    }
+}
+
+// Lets a caller keep a handle on a teacher it hands to a `Learner`, e.g. to read statistics
+// afterwards.
+impl<T: Eq + Hash + Clone + Debug + Default, U: Teacher<T> + ?Sized> Teacher<T> for Rc<U> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        (**self).membership_query(states)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        (**self).equivalence_query(automaton)
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        (**self).try_membership_query(states)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        (**self).try_membership_query_batch(words)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        (**self).try_equivalence_query(automaton)
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        (**self).try_equivalence_query_batch(automaton)
+    }
+}
+
+/// A teacher whose answers arrive asynchronously, e.g. from a remote service; see
+/// `Learner::learn_async`. Both queries can fail, like `Teacher`'s `try_` methods.
+#[cfg(feature = "async")]
+pub trait AsyncTeacher<T: Eq + Hash + Clone + Debug + Default> {
+
+    fn membership_query(&self, states: &[T]) -> impl Future<Output = Result<bool, TeacherError>>;
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> impl Future<Output = Result<EquivalenceAnswer<T>, TeacherError>>;
+}
+
+/// A teacher that needs `&mut self` to answer, e.g. one driving a system that has to be reset
+/// before every query. Hand it to a `Learner` inside a `RefCell`, which is a `Teacher`.
+pub trait StatefulTeacher<T: Eq + Hash + Clone + Debug + Default> {
+
+    fn membership_query(&mut self, states: &[T]) -> bool;
+
+    fn equivalence_query(&mut self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T>;
+
+    /// See `Teacher::try_membership_query`.
+    fn try_membership_query(&mut self, states: &[T]) -> Result<bool, TeacherError> {
+        Ok(self.membership_query(states))
+    }
+
+    /// See `Teacher::try_equivalence_query`.
+    fn try_equivalence_query(&mut self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        Ok(self.equivalence_query(automaton))
+    }
+}
+
+// Queries never overlap, so the borrow is always free.
+impl<T: Eq + Hash + Clone + Debug + Default, S: StatefulTeacher<T>> Teacher<T> for RefCell<S> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.borrow_mut().membership_query(states)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.borrow_mut().equivalence_query(automaton)
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        self.borrow_mut().try_membership_query(states)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        self.borrow_mut().try_equivalence_query(automaton)
+    }
+}
+
+/// A teacher for a Mealy machine target, which answers each word with outputs rather than
+/// acceptance. Counterexamples follow `Teacher`'s conventions.
+pub trait MealyTeacher<I: Eq + Hash + Clone + Debug, O: Eq + Clone + Debug> {
+
+    /// The output the target produces for each symbol of `word`, in order.
+    fn output_query(&mut self, word: &[I]) -> Vec<O>;
+
+    /// `Ok(())` if `hypothesis` produces the target's outputs on every word, or else a word on
+    /// which it does not.
+    fn equivalence_query(&mut self, hypothesis: &MealyMachine<Vec<I>, I, O>) -> EquivalenceAnswer<I>;
+
+    /// See `Teacher::try_membership_query`.
+    fn try_output_query(&mut self, word: &[I]) -> Result<Vec<O>, TeacherError> {
+        Ok(self.output_query(word))
+    }
+
+    /// See `Teacher::try_equivalence_query`.
+    fn try_equivalence_query(&mut self, hypothesis: &MealyMachine<Vec<I>, I, O>) -> Result<EquivalenceAnswer<I>, TeacherError> {
+        Ok(self.equivalence_query(hypothesis))
+    }
+}
+
+/// A teacher for a Moore machine target, which classifies each word by the output of the state
+/// it reaches. `BooleanMooreTeacher` makes any `Teacher` one with `O = bool`.
+pub trait MooreTeacher<I: Eq + Hash + Clone + Debug, O: Eq + Hash + Clone + Debug> {
+
+    fn classify(&self, word: &[I]) -> O;
+
+    /// `Ok(())` if `hypothesis` classifies every word like the target, or else a word it
+    /// classifies differently.
+    fn equivalence_query(&self, hypothesis: &MooreMachine<Vec<I>, I, O>) -> EquivalenceAnswer<I>;
+
+    /// See `Teacher::try_membership_query`.
+    fn try_classify(&self, word: &[I]) -> Result<O, TeacherError> {
+        Ok(self.classify(word))
+    }
 
-    fn validate_hypothesis(&self, automaton: Automaton<Vec<T>, T>) -> Result<bool, HashSet<Vec<T>>>;
-}
\ No newline at end of file
+    /// See `Teacher::try_equivalence_query`.
+    fn try_equivalence_query(&self, hypothesis: &MooreMachine<Vec<I>, I, O>) -> Result<EquivalenceAnswer<I>, TeacherError> {
+        Ok(self.equivalence_query(hypothesis))
+    }
+}
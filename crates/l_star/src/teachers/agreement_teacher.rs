@@ -0,0 +1,158 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// A word the two teachers of an `AgreementTeacher` classify differently, without the
+/// learner's `T::default()` padding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Disagreement<T> {
+    pub word: Vec<T>,
+    pub first: bool,
+    pub second: bool,
+}
+
+/// Compares two teachers of what should be the same language, e.g. a regex and a hand-written
+/// predicate, turning the learner into a discrepancy finder. Membership queries go to both and
+/// are answered by `First`, noting any disagreement. The learner thus learns `First`'s
+/// language, and once `First` accepts a hypothesis, `Second`'s counterexample to it is a word
+/// the two disagree on, which is noted and returned as the counterexample. Answered by
+/// `First`, the learner keeps its hypothesis, so a word already returned once accepts it
+/// instead, and learning stops. `disagreements` holds what was found, in order; none means the
+/// teachers agree as far as their equivalence oracles can tell.
+pub struct AgreementTeacher<T, First, Second> {
+    first: First,
+    second: Second,
+    disagreements: RefCell<Vec<Disagreement<T>>>,
+    // Disagreements already returned from equivalence queries.
+    returned: RefCell<HashSet<Vec<T>>>,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, First: Teacher<T>, Second: Teacher<T>> AgreementTeacher<T, First, Second> {
+    pub fn new(first: First, second: Second) -> Self {
+        AgreementTeacher {
+            first,
+            second,
+            disagreements: RefCell::new(Vec::new()),
+            returned: RefCell::new(HashSet::new()),
+            _symbols: PhantomData,
+        }
+    }
+
+    pub fn first(&self) -> &First {
+        &self.first
+    }
+
+    pub fn second(&self) -> &Second {
+        &self.second
+    }
+
+    pub fn disagreements(&self) -> Vec<Disagreement<T>> {
+        self.disagreements.borrow().clone()
+    }
+
+    // Notes a disagreement the first time its word comes up.
+    fn compare(&self, states: &[T], first: bool, second: bool) {
+        if first == second {
+            return;
+        }
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        let mut disagreements = self.disagreements.borrow_mut();
+        if !disagreements.iter().any(|disagreement| disagreement.word == word) {
+            disagreements.push(Disagreement { word, first, second });
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, First: Teacher<T>, Second: Teacher<T>> Teacher<T> for AgreementTeacher<T, First, Second> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        let first = self.first.try_membership_query(states)?;
+        self.compare(states, first, self.second.try_membership_query(states)?);
+        Ok(first)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        let first = self.first.try_membership_query_batch(words)?;
+        let second = self.second.try_membership_query_batch(words)?;
+        for ((word, first), second) in words.iter().zip(&first).zip(second) {
+            self.compare(word, *first, second);
+        }
+        Ok(first)
+    }
+
+    // `Second`'s counterexample only shows a disagreement if the hypothesis is right about
+    // `First` on it, which an approximate equivalence oracle for `First` does not promise;
+    // otherwise it goes to the learner like one from `First`.
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        if let Err(counterexample) = self.first.try_equivalence_query(automaton.clone())? {
+            return Ok(Err(counterexample));
+        }
+        let Err(counterexample) = self.second.try_equivalence_query(automaton.clone())? else {
+            return Ok(Ok(()));
+        };
+        let first = self.first.try_membership_query(&counterexample)?;
+        self.compare(&counterexample, first, self.second.try_membership_query(&counterexample)?);
+        let word: Vec<T> = counterexample.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        if automaton.accepts(&word) == first && !self.returned.borrow_mut().insert(word) {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(counterexample))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::Alphabet;
+    use crate::automaton::DfaState;
+    use crate::learner::Learner;
+    use crate::teachers::dfa_teacher::DfaTeacher;
+    use std::rc::Rc;
+
+    fn teacher(pattern: &str) -> DfaTeacher<char> {
+        DfaTeacher::from_regex(pattern, &Alphabet::new(['a', 'b'])).unwrap()
+    }
+
+    #[test]
+    fn a_disagreement_is_returned_once_and_then_accepted() {
+        let agreement = AgreementTeacher::new(teacher("^(ab)*$"), teacher("^((ab)*|ba)$"));
+        // `(ab)*`, right about `First` everywhere.
+        let state = |id: &[char]| DfaState::new(id.to_vec(), id.is_empty());
+        let mut hypothesis = Automaton::new(state(&[]));
+        hypothesis.add_transition(&state(&[]), &state(&['a']), &'a');
+        hypothesis.add_transition(&state(&['a']), &state(&[]), &'b');
+
+        assert_eq!(agreement.equivalence_query(hypothesis.clone()), Err(vec!['b', 'a']));
+        assert_eq!(agreement.disagreements(), vec![Disagreement { word: vec!['b', 'a'], first: false, second: true }]);
+        assert_eq!(agreement.equivalence_query(hypothesis), Ok(()));
+    }
+
+    #[test]
+    fn learning_finds_where_the_teachers_differ() {
+        let agreement = Rc::new(AgreementTeacher::new(teacher("^(ab)*$"), teacher("^((ab)*|ba)$")));
+        let learned = Learner::new(Alphabet::new(['a', 'b']), Box::new(Rc::clone(&agreement))).learn().unwrap();
+        assert_eq!(learned.state_count(), 3);
+        assert!(learned.accepts(&['a', 'b', 'a', 'b']));
+        assert!(!learned.accepts(&['b', 'a']));
+        assert!(agreement.disagreements().iter().any(|disagreement| disagreement.word == ['b', 'a'] && disagreement.second));
+
+        let agreeing = Rc::new(AgreementTeacher::new(teacher("^(ab)*$"), teacher("^(ab)*$")));
+        Learner::new(Alphabet::new(['a', 'b']), Box::new(Rc::clone(&agreeing))).learn().unwrap();
+        assert!(agreeing.disagreements().is_empty());
+    }
+}
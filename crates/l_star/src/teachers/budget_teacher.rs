@@ -0,0 +1,115 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// Passes at most a fixed number of queries on to `Inner`; after that, queries fail with
+/// `TeacherError::BudgetExhausted` and `Learner::learn` stops with the last hypothesis. Put a
+/// `CachingTeacher` around it so that repeated words do not use up the budget.
+pub struct BudgetTeacher<T, Inner> {
+    inner: Inner,
+    membership_budget: Option<usize>,
+    equivalence_budget: Option<usize>,
+    membership_queries: Cell<usize>,
+    equivalence_queries: Cell<usize>,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> BudgetTeacher<T, Inner> {
+    /// No limits until `with_membership_budget` or `with_equivalence_budget`.
+    pub fn new(inner: Inner) -> Self {
+        BudgetTeacher {
+            inner,
+            membership_budget: None,
+            equivalence_budget: None,
+            membership_queries: Cell::new(0),
+            equivalence_queries: Cell::new(0),
+            _symbols: PhantomData,
+        }
+    }
+
+    pub fn with_membership_budget(mut self, queries: usize) -> Self {
+        self.membership_budget = Some(queries);
+        self
+    }
+
+    pub fn with_equivalence_budget(mut self, queries: usize) -> Self {
+        self.equivalence_budget = Some(queries);
+        self
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Membership queries passed on so far.
+    pub fn membership_queries(&self) -> usize {
+        self.membership_queries.get()
+    }
+
+    /// Equivalence queries passed on so far.
+    pub fn equivalence_queries(&self) -> usize {
+        self.equivalence_queries.get()
+    }
+}
+
+// Counts `queries` queries against `budget`, unless they would go over it.
+fn spend(used: &Cell<usize>, budget: Option<usize>, queries: usize) -> Result<(), TeacherError> {
+    if budget.is_some_and(|budget| used.get() + queries > budget) {
+        return Err(TeacherError::BudgetExhausted);
+    }
+    used.set(used.get() + queries);
+    Ok(())
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> Teacher<T> for BudgetTeacher<T, Inner> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        spend(&self.membership_queries, self.membership_budget, 1)?;
+        self.inner.try_membership_query(states)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        // A batch goes on whole or not at all, so that only words `Inner` saw are counted.
+        spend(&self.membership_queries, self.membership_budget, words.len())?;
+        self.inner.try_membership_query_batch(words)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        spend(&self.equivalence_queries, self.equivalence_budget, 1)?;
+        self.inner.try_equivalence_query(automaton)
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        spend(&self.equivalence_queries, self.equivalence_budget, 1)?;
+        self.inner.try_equivalence_query_batch(automaton)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::teachers::fn_teacher::FnTeacher;
+
+    #[test]
+    fn a_batch_over_the_budget_counts_nothing() {
+        let teacher = BudgetTeacher::new(FnTeacher::new(|word: &[char]| word.len().is_multiple_of(2))).with_membership_budget(3);
+        let words = vec![vec!['a'], vec!['a', 'b'], vec!['b']];
+        assert_eq!(teacher.try_membership_query_batch(&words[..2]), Ok(vec![false, true]));
+        assert_eq!(teacher.try_membership_query_batch(&words), Err(TeacherError::BudgetExhausted));
+        assert_eq!(teacher.membership_queries(), 2);
+        assert_eq!(teacher.try_membership_query(&['b']), Ok(false));
+        assert_eq!(teacher.membership_queries(), 3);
+    }
+}
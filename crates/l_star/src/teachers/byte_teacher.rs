@@ -0,0 +1,74 @@
+use crate::teacher::{EquivalenceAnswer, Teacher};
+use crate::teachers::fn_teacher::FnTeacher;
+use crate::automaton::Automaton;
+use std::fmt::{self, Display};
+
+
+/// A byte as a learner symbol. The learner pads words with `T::default()`, which for `u8` would
+/// be the real byte 0x00, so `Byte::default()` is padding instead and every byte stays an
+/// ordinary symbol. Bytes are written as two hex digits, so an access string reads as a hex
+/// dump; padding is written as nothing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Byte(Option<u8>);
+
+impl Byte {
+    /// `None` for padding.
+    pub fn value(self) -> Option<u8> {
+        self.0
+    }
+
+    /// Every byte, in order: the alphabet of an unconstrained binary protocol.
+    pub fn all() -> impl Iterator<Item = Byte> {
+        (0..=u8::MAX).map(Byte::from)
+    }
+}
+
+impl From<u8> for Byte {
+    fn from(byte: u8) -> Self {
+        Byte(Some(byte))
+    }
+}
+
+impl Display for Byte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(byte) => write!(f, "{:02x}", byte),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A teacher for byte strings, e.g. the messages of a binary protocol, made from a closure
+/// over `&[u8]`. Equivalence queries are those of `FnTeacher`.
+pub struct ByteTeacher {
+    inner: FnTeacher<Byte>,
+}
+
+impl ByteTeacher {
+    pub fn new(membership: impl Fn(&[u8]) -> bool + 'static) -> Self {
+        ByteTeacher {
+            inner: FnTeacher::new(move |word: &[Byte]| {
+                let bytes: Vec<u8> = word.iter().filter_map(|byte| byte.value()).collect();
+                membership(&bytes)
+            }),
+        }
+    }
+
+    /// See `FnTeacher::with_equivalence`.
+    pub fn with_equivalence(mut self, equivalence: impl Fn(&Automaton<Vec<Byte>, Byte>) -> Result<(), Vec<Byte>> + 'static) -> Self {
+        self.inner = self.inner.with_equivalence(equivalence);
+        self
+    }
+}
+
+impl Teacher<Byte> for ByteTeacher {
+
+    fn membership_query(&self, states: &[Byte]) -> bool {
+        self.inner.membership_query(states)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<Byte>, Byte>) -> EquivalenceAnswer<Byte> {
+        self.inner.equivalence_query(automaton)
+    }
+}
@@ -0,0 +1,177 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// Memoizes the membership answers of `Inner`, so that each word reaches it once; equivalence
+/// queries are passed straight through. Words are cached without the learner's `T::default()`
+/// padding, so `[ε, a]` and `[a]` share an entry. Wrap it in an `Rc` to keep reading the
+/// statistics after handing it to a `Learner`.
+pub struct CachingTeacher<T, Inner> {
+    inner: Inner,
+    trie: RefCell<Vec<TrieNode<T>>>,
+    hits: Cell<usize>,
+    misses: Cell<usize>,
+    _symbols: PhantomData<T>,
+}
+
+// One node per cached word or prefix of one; node 0 is the empty word.
+struct TrieNode<T> {
+    children: HashMap<T, usize>,
+    answer: Option<bool>,
+}
+
+impl<T> TrieNode<T> {
+    fn new() -> Self {
+        TrieNode { children: HashMap::new(), answer: None }
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> CachingTeacher<T, Inner> {
+    pub fn new(inner: Inner) -> Self {
+        CachingTeacher {
+            inner,
+            trie: RefCell::new(vec![TrieNode::new()]),
+            hits: Cell::new(0),
+            misses: Cell::new(0),
+            _symbols: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Membership queries answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.get()
+    }
+
+    /// Membership queries passed on to the inner teacher.
+    pub fn misses(&self) -> usize {
+        self.misses.get()
+    }
+
+    /// Number of cached words.
+    pub fn cache_len(&self) -> usize {
+        self.trie.borrow().iter().filter(|node| node.answer.is_some()).count()
+    }
+
+    // The node for `word`, created along with its missing prefixes.
+    fn node(&self, word: &[T]) -> usize {
+        let mut trie = self.trie.borrow_mut();
+        let mut node = 0;
+        for symbol in word {
+            node = match trie[node].children.get(symbol) {
+                Some(child) => *child,
+                None => {
+                    let child = trie.len();
+                    trie[node].children.insert(symbol.clone(), child);
+                    trie.push(TrieNode::new());
+                    child
+                }
+            };
+        }
+        node
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> Teacher<T> for CachingTeacher<T, Inner> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.inner.equivalence_query(automaton)
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        let node = self.node(&word);
+        if let Some(answer) = self.trie.borrow()[node].answer {
+            self.hits.set(self.hits.get() + 1);
+            return Ok(answer);
+        }
+        self.misses.set(self.misses.get() + 1);
+        let answer = self.inner.try_membership_query(states)?;
+        self.trie.borrow_mut()[node].answer = Some(answer);
+        Ok(answer)
+    }
+
+    // Only the words not cached yet are passed on, as one batch, each of them once.
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        let nodes: Vec<usize> = words.iter()
+            .map(|word| self.node(&word.iter().filter(|symbol| **symbol != T::default()).cloned().collect::<Vec<T>>()))
+            .collect();
+        let mut asked = HashSet::new();
+        let missing: Vec<usize> = (0..words.len())
+            .filter(|index| self.trie.borrow()[nodes[*index]].answer.is_none() && asked.insert(nodes[*index]))
+            .collect();
+        if !missing.is_empty() {
+            let batch: Vec<Vec<T>> = missing.iter().map(|index| words[*index].clone()).collect();
+            let answers = self.inner.try_membership_query_batch(&batch)?;
+            if answers.len() != batch.len() {
+                return Err(TeacherError::Other(format!("{} membership queries got {} answers", batch.len(), answers.len())));
+            }
+            let mut trie = self.trie.borrow_mut();
+            for (index, answer) in missing.iter().zip(answers) {
+                trie[nodes[*index]].answer = Some(answer);
+            }
+        }
+        self.misses.set(self.misses.get() + missing.len());
+        self.hits.set(self.hits.get() + words.len() - missing.len());
+        let trie = self.trie.borrow();
+        Ok(nodes.iter().map(|node| trie[*node].answer.expect("every word was answered")).collect())
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        self.inner.try_equivalence_query(automaton)
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        self.inner.try_equivalence_query_batch(automaton)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::Alphabet;
+    use crate::learner::Learner;
+    use crate::teachers::counting_teacher::CountingTeacher;
+    use crate::teachers::regex_teacher::RegexTeacher;
+    use std::rc::Rc;
+
+    fn counted_regex() -> Rc<CountingTeacher<String, RegexTeacher>> {
+        let alphabet = Alphabet::new(["a".to_string(), "b".to_string()]);
+        let teacher = RegexTeacher::new_anchored("(a|b)*a(a|b)").unwrap().with_exact_equivalence(&alphabet).unwrap();
+        Rc::new(CountingTeacher::new(teacher))
+    }
+
+    fn learn(teacher: Box<dyn Teacher<String>>) -> usize {
+        let mut learner = Learner::new(Alphabet::new(["a".to_string(), "b".to_string()]), teacher);
+        learner.learn().unwrap().state_count()
+    }
+
+    #[test]
+    fn the_inner_teacher_gets_fewer_queries() {
+        let uncached = counted_regex();
+        let states = learn(Box::new(uncached.clone()));
+
+        let counted = counted_regex();
+        let cached = Rc::new(CachingTeacher::new(counted.clone()));
+        assert_eq!(learn(Box::new(cached.clone())), states);
+
+        let without = uncached.stats().membership_queries;
+        let with = counted.stats().membership_queries;
+        assert!(with < without, "{} queries with the cache, {} without", with, without);
+        assert_eq!(cached.misses(), with);
+        assert_eq!(cached.hits() + cached.misses(), without);
+        assert_eq!(cached.cache_len(), with);
+    }
+}
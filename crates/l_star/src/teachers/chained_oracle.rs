@@ -0,0 +1,135 @@
+use crate::teacher::{EquivalenceAnswer, Membership, Teacher};
+use crate::automaton::Automaton;
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::rc::Rc;
+
+
+/// What one stage of a `ChainedOracle` did so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StageStats {
+    pub name: String,
+    /// Hypotheses this stage tested, i.e. that all earlier stages passed.
+    pub hypotheses: usize,
+    /// Hypotheses this stage found a counterexample for.
+    pub counterexamples: usize,
+    pub membership_queries: usize,
+}
+
+impl fmt::Display for StageStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} counterexamples in {} hypotheses, {} membership queries", self.name, self.counterexamples, self.hypotheses, self.membership_queries)
+    }
+}
+
+// What the stages' memberships share: the source, the budget and the hypothesis under test.
+struct Shared<T: Eq + Hash + Clone> {
+    membership: Membership<T>,
+    query_budget: Cell<Option<usize>>,
+    queries: Cell<usize>,
+    hypothesis: RefCell<Option<Automaton<Vec<T>, T>>>,
+}
+
+/// The membership source of a `ChainedOracle` as handed to its stages; build each stage
+/// `from_teacher` on it. Once the chain's query budget for the current hypothesis is used up,
+/// it answers like the hypothesis, so that the remaining tests pass without asking anything.
+pub struct ChainMembership<T: Eq + Hash + Clone> {
+    shared: Rc<Shared<T>>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> Teacher<T> for ChainMembership<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let shared = &self.shared;
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        if shared.query_budget.get().is_some_and(|budget| shared.queries.get() >= budget)
+            && let Some(hypothesis) = shared.hypothesis.borrow().as_ref() {
+                return hypothesis.accepts(&word);
+            }
+        shared.queries.set(shared.queries.get() + 1);
+        (shared.membership)(&word)
+    }
+
+    fn equivalence_query(&self, _automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        panic!("ChainMembership only answers membership queries")
+    }
+}
+
+/// An equivalence oracle running several others in turn, cheapest first, e.g. random words,
+/// then random walks, then the W-method: a hypothesis gets the first stage's counterexamples
+/// if there are any, else the next stage's, and is accepted once every stage passed it. All
+/// stages share one membership source and, with `with_query_budget`, one budget per
+/// hypothesis.
+pub struct ChainedOracle<T: Eq + Hash + Clone> {
+    shared: Rc<Shared<T>>,
+    stages: Vec<Box<dyn Teacher<T>>>,
+    stats: RefCell<Vec<StageStats>>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> ChainedOracle<T> {
+    /// Answers membership queries with `membership`. Has no stages, and so accepts every
+    /// hypothesis, until `then` adds some.
+    pub fn from_fn(membership: impl Fn(&[T]) -> bool + 'static) -> Self {
+        ChainedOracle {
+            shared: Rc::new(Shared {
+                membership: Box::new(membership),
+                query_budget: Cell::new(None),
+                queries: Cell::new(0),
+                hypothesis: RefCell::new(None),
+            }),
+            stages: Vec::new(),
+            stats: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Answers membership queries with `teacher`, replacing its equivalence queries.
+    pub fn from_teacher(teacher: impl Teacher<T> + 'static) -> Self {
+        Self::from_fn(move |word| teacher.membership_query(word))
+    }
+
+    /// Appends a stage built on the shared membership source, e.g.
+    /// `.then("W-method", |membership| WMethodOracle::from_teacher(alphabet, membership))`.
+    pub fn then<Stage: Teacher<T> + 'static>(mut self, name: impl Into<String>, stage: impl FnOnce(ChainMembership<T>) -> Stage) -> Self {
+        self.stages.push(Box::new(stage(ChainMembership { shared: Rc::clone(&self.shared) })));
+        self.stats.get_mut().push(StageStats { name: name.into(), ..StageStats::default() });
+        self
+    }
+
+    /// The most membership queries all stages together may ask about one hypothesis.
+    pub fn with_query_budget(self, query_budget: usize) -> Self {
+        self.shared.query_budget.set(Some(query_budget));
+        self
+    }
+
+    /// Per stage, in chain order.
+    pub fn stats(&self) -> Vec<StageStats> {
+        self.stats.borrow().clone()
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> Teacher<T> for ChainedOracle<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        (self.shared.membership)(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.shared.queries.set(0);
+        *self.shared.hypothesis.borrow_mut() = Some(automaton.clone());
+        let mut verdict = Ok(());
+        for (stage, stats) in self.stages.iter().zip(self.stats.borrow_mut().iter_mut()) {
+            let before = self.shared.queries.get();
+            stats.hypotheses += 1;
+            verdict = stage.equivalence_query(automaton.clone());
+            stats.membership_queries += self.shared.queries.get() - before;
+            if verdict.is_err() {
+                stats.counterexamples += 1;
+                break;
+            }
+        }
+        *self.shared.hypothesis.borrow_mut() = None;
+        verdict
+    }
+}
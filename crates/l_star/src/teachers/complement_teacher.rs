@@ -0,0 +1,57 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Teacher, TeacherError};
+use crate::alphabet::Alphabet;
+use crate::automaton::Automaton;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// Teaches the complement of `Inner`'s language over `alphabet`: membership answers are
+/// negated, and a hypothesis is complemented with `Automaton::complement` and checked by
+/// `Inner`, whose counterexamples are counterexamples here too.
+pub struct ComplementTeacher<T, Inner> {
+    inner: Inner,
+    alphabet: Alphabet<T>,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> ComplementTeacher<T, Inner> {
+    pub fn new(inner: Inner, alphabet: Alphabet<T>) -> Self {
+        ComplementTeacher { inner, alphabet, _symbols: PhantomData }
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    fn complemented(&self, automaton: &Automaton<Vec<T>, T>) -> Automaton<Vec<T>, T> {
+        automaton.complement(&self.alphabet).with_access_words()
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> Teacher<T> for ComplementTeacher<T, Inner> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        Ok(!self.inner.try_membership_query(states)?)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        Ok(self.inner.try_membership_query_batch(words)?.into_iter().map(|answer| !answer).collect())
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        self.inner.try_equivalence_query(self.complemented(&automaton))
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        self.inner.try_equivalence_query_batch(self.complemented(&automaton))
+    }
+}
@@ -0,0 +1,136 @@
+use crate::teacher::{Teacher, TeacherError};
+use crate::alphabet::Alphabet;
+use crate::automaton::{Automaton, DfaState};
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+
+/// Something `check_teacher_consistency` caught a teacher doing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inconsistency<T> {
+    /// `membership_query` answered differently once the word was padded with `T::default()`
+    /// the way the learner writes the empty word.
+    PaddingChangesAnswer { word: Vec<T> },
+    /// A counterexample on which the hypothesis already agrees with `membership_query`.
+    NotACounterexample { word: Vec<T>, member: bool },
+    /// A counterexample with a symbol outside the alphabet.
+    ForeignSymbol { word: Vec<T> },
+    /// The teacher failed to answer at all.
+    Failed(TeacherError),
+}
+
+impl<T: Debug> fmt::Display for Inconsistency<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inconsistency::PaddingChangesAnswer { word } => write!(f, "padding {:?} with the empty symbol changes its membership", word),
+            Inconsistency::NotACounterexample { word, member } => {
+                write!(f, "counterexample {:?} is {} by both the hypothesis and membership_query", word, if *member { "accepted" } else { "rejected" })
+            }
+            Inconsistency::ForeignSymbol { word } => write!(f, "counterexample {:?} is not over the alphabet", word),
+            Inconsistency::Failed(err) => write!(f, "teacher failed: {}", err),
+        }
+    }
+}
+
+/// Checks that a teacher's equivalence queries agree with its membership queries. Every word of
+/// at most `max_len` symbols is asked, and for each `k <= max_len` the minimal DFA that agrees
+/// with the answers up to length `k` (and rejects everything longer) is offered as a
+/// hypothesis, with state ids padded the way the learner pads them. Every counterexample must be
+/// a word the hypothesis gets wrong.
+pub fn check_teacher_consistency<T: Eq + Hash + Clone + Debug + Default>(teacher: &dyn Teacher<T>, alphabet: &Alphabet<T>, max_len: usize) -> Result<(), Vec<Inconsistency<T>>> {
+    let mut found = Vec::new();
+    let member = |word: &[T]| teacher.try_membership_query(word);
+
+    let mut answers: HashMap<Vec<T>, bool> = HashMap::new();
+    let mut layer: Vec<Vec<T>> = vec![Vec::new()];
+    for len in 0..=max_len {
+        for word in &layer {
+            let answer = member(word).map_err(|err| vec![Inconsistency::Failed(err)])?;
+            let padded: Vec<T> = std::iter::once(T::default()).chain(word.iter().cloned()).collect();
+            if member(&padded).map_err(|err| vec![Inconsistency::Failed(err)])? != answer {
+                found.push(Inconsistency::PaddingChangesAnswer { word: word.clone() });
+            }
+            answers.insert(word.clone(), answer);
+        }
+        if len < max_len {
+            layer = layer.iter()
+                .flat_map(|word| alphabet.iter().map(move |symbol| extended(word, symbol)))
+                .collect();
+        }
+    }
+
+    for len in 0..=max_len {
+        let hypothesis = prefix_tree(alphabet, &answers, len);
+        let counterexample = match teacher.try_equivalence_query(hypothesis.clone()) {
+            Ok(Ok(())) => continue,
+            Ok(Err(counterexample)) => counterexample,
+            Err(err) => {
+                found.push(Inconsistency::Failed(err));
+                break;
+            }
+        };
+        let word: Vec<T> = counterexample.into_iter().filter(|symbol| *symbol != T::default()).collect();
+        if !word.iter().all(|symbol| alphabet.contains(symbol)) {
+            found.push(Inconsistency::ForeignSymbol { word });
+            continue;
+        }
+        let answer = match answers.get(&word) {
+            Some(answer) => *answer,
+            None => match member(&word) {
+                Ok(answer) => answer,
+                Err(err) => {
+                    found.push(Inconsistency::Failed(err));
+                    continue;
+                }
+            },
+        };
+        if hypothesis.accepts(&word) == answer {
+            found.push(Inconsistency::NotACounterexample { word, member: answer });
+        }
+    }
+
+    if found.is_empty() { Ok(()) } else { Err(found) }
+}
+
+// The minimal DFA accepting exactly the accepted words of at most `len` symbols, its states
+// named by padded access words.
+fn prefix_tree<T: Eq + Hash + Clone + Default>(alphabet: &Alphabet<T>, answers: &HashMap<Vec<T>, bool>, len: usize) -> Automaton<Vec<T>, T> {
+    let state = |word: &Vec<T>| DfaState::new(word.clone(), answers[word]);
+    let mut tree = Automaton::new(state(&Vec::new()));
+    let mut layer: Vec<Vec<T>> = vec![Vec::new()];
+    for _ in 0..len {
+        let mut next = Vec::new();
+        for word in &layer {
+            for symbol in alphabet.iter() {
+                let longer = extended(word, symbol);
+                tree.add_state(state(&longer));
+                tree.add_transition(&state(word), &state(&longer), symbol);
+                next.push(longer);
+            }
+        }
+        layer = next;
+    }
+
+    let minimal = tree.minimize();
+    let access_words = minimal.state_cover(alphabet);
+    let names: HashMap<&usize, Vec<T>> = access_words.iter()
+        .map(|word| (*minimal.trace(word).last().unwrap(), std::iter::once(T::default()).chain(word.iter().cloned()).collect()))
+        .collect();
+    let named = |id: &usize| DfaState::new(names[id].clone(), minimal.get_state(id).unwrap().is_accepting());
+    let initial = minimal.get_initial_state().unwrap();
+    let mut automaton = Automaton::new(named(initial.get_state_id()));
+    for (id, state) in minimal.get_states() {
+        automaton.add_state(named(id));
+        for (symbol, target) in state.transitions() {
+            automaton.add_transition(&named(id), &named(target), symbol);
+        }
+    }
+    automaton
+}
+
+fn extended<T: Clone>(word: &[T], symbol: &T) -> Vec<T> {
+    let mut longer = word.to_vec();
+    longer.push(symbol.clone());
+    longer
+}
@@ -0,0 +1,109 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+
+/// A corpus word a `CorpusOracle` returned as a counterexample.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CorpusCounterexample {
+    pub line: usize,
+    /// The line as it is in the file.
+    pub text: String,
+}
+
+/// An equivalence oracle replaying a fixed corpus, e.g. traces captured from production, one
+/// word per line with symbols separated by whitespace. Every hypothesis is run on the whole
+/// corpus and the first word, in file order, on which it disagrees with `Inner` is the
+/// counterexample; one without any disagreement goes on to `Inner`'s equivalence query, so
+/// put the corpus in front of more speculative oracles. `Inner` is asked about the corpus once,
+/// in a single batch, at the first equivalence query. Blank lines are skipped: the empty
+/// word is always in the learner's table, so no hypothesis gets it wrong.
+pub struct CorpusOracle<Inner> {
+    inner: Inner,
+    words: Vec<(usize, String)>,
+    labels: RefCell<Option<Vec<bool>>>,
+    counterexamples: RefCell<Vec<CorpusCounterexample>>,
+}
+
+impl<Inner: Teacher<String>> CorpusOracle<Inner> {
+    pub fn from_file(path: impl AsRef<Path>, inner: Inner) -> io::Result<Self> {
+        let mut words = Vec::new();
+        for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                words.push((index + 1, line));
+            }
+        }
+        Ok(CorpusOracle {
+            inner,
+            words,
+            labels: RefCell::new(None),
+            counterexamples: RefCell::new(Vec::new()),
+        })
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Number of words in the corpus.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The counterexamples taken from the corpus so far, in the order they were returned.
+    pub fn counterexamples(&self) -> Vec<CorpusCounterexample> {
+        self.counterexamples.borrow().clone()
+    }
+
+    fn symbols(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn labels(&self) -> Result<Vec<bool>, TeacherError> {
+        if let Some(labels) = self.labels.borrow().as_ref() {
+            return Ok(labels.clone());
+        }
+        let words: Vec<Vec<String>> = self.words.iter().map(|(_, text)| Self::symbols(text)).collect();
+        let labels = self.inner.try_membership_query_batch(&words)?;
+        *self.labels.borrow_mut() = Some(labels.clone());
+        Ok(labels)
+    }
+}
+
+impl<Inner: Teacher<String>> Teacher<String> for CorpusOracle<Inner> {
+
+    fn membership_query(&self, states: &[String]) -> bool {
+        self.inner.membership_query(states)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<String>, String>) -> EquivalenceAnswer<String> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[String]) -> Result<bool, TeacherError> {
+        self.inner.try_membership_query(states)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<String>]) -> Result<Vec<bool>, TeacherError> {
+        self.inner.try_membership_query_batch(words)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<String>, String>) -> Result<EquivalenceAnswer<String>, TeacherError> {
+        for ((line, text), label) in self.words.iter().zip(self.labels()?) {
+            let word = Self::symbols(text);
+            if automaton.accepts(&word) != label {
+                self.counterexamples.borrow_mut().push(CorpusCounterexample { line: *line, text: text.clone() });
+                return Ok(Err(word));
+            }
+        }
+        self.inner.try_equivalence_query(automaton)
+    }
+}
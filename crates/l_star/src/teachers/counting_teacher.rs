@@ -0,0 +1,112 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::RefCell;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// What a `CountingTeacher` has been asked. Word lengths leave out the learner's
+/// `T::default()` padding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    pub membership_queries: usize,
+    pub equivalence_queries: usize,
+    /// Symbols over all membership queries.
+    pub queried_symbols: usize,
+    pub longest_word: usize,
+    /// `word_lengths[n]` membership queries were for words of length `n`.
+    pub word_lengths: Vec<usize>,
+}
+
+impl QueryStats {
+    pub fn mean_word_length(&self) -> f64 {
+        if self.membership_queries == 0 {
+            0.0
+        } else {
+            self.queried_symbols as f64 / self.membership_queries as f64
+        }
+    }
+}
+
+impl fmt::Display for QueryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} membership queries ({} symbols, longest {}), {} equivalence queries",
+            self.membership_queries, self.queried_symbols, self.longest_word, self.equivalence_queries)
+    }
+}
+
+/// Passes every query on to `Inner` and counts them. Wrap it in an `Rc` to keep reading
+/// `stats` after handing it to a `Learner`.
+pub struct CountingTeacher<T, Inner> {
+    inner: Inner,
+    stats: RefCell<QueryStats>,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> CountingTeacher<T, Inner> {
+    pub fn new(inner: Inner) -> Self {
+        CountingTeacher {
+            inner,
+            stats: RefCell::new(QueryStats::default()),
+            _symbols: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    pub fn stats(&self) -> QueryStats {
+        self.stats.borrow().clone()
+    }
+
+    pub fn reset(&self) {
+        *self.stats.borrow_mut() = QueryStats::default();
+    }
+
+    fn count_membership(&self, states: &[T]) {
+        let len = states.iter().filter(|symbol| **symbol != T::default()).count();
+        let mut stats = self.stats.borrow_mut();
+        stats.membership_queries += 1;
+        stats.queried_symbols += len;
+        stats.longest_word = stats.longest_word.max(len);
+        if stats.word_lengths.len() <= len {
+            stats.word_lengths.resize(len + 1, 0);
+        }
+        stats.word_lengths[len] += 1;
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> Teacher<T> for CountingTeacher<T, Inner> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.count_membership(states);
+        self.inner.membership_query(states)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.stats.borrow_mut().equivalence_queries += 1;
+        self.inner.equivalence_query(automaton)
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        self.count_membership(states);
+        self.inner.try_membership_query(states)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        words.iter().for_each(|word| self.count_membership(word));
+        self.inner.try_membership_query_batch(words)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        self.stats.borrow_mut().equivalence_queries += 1;
+        self.inner.try_equivalence_query(automaton)
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        self.stats.borrow_mut().equivalence_queries += 1;
+        self.inner.try_equivalence_query_batch(automaton)
+    }
+}
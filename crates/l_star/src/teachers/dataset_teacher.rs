@@ -0,0 +1,181 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+
+/// What `DatasetTeacher` answers for a word that is not in the dataset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnknownWord {
+    /// Answer with this label, e.g. `false` for the closed-world assumption.
+    Label(bool),
+    /// Fail the query, which stops the learner with a `LearnError`.
+    Error,
+}
+
+/// A teacher answering from a dataset of labeled words, with symbols of any length. The
+/// records are read line by line into a trie, and equivalence queries walk that trie
+/// alongside the hypothesis, so the dataset is held in memory once and shared prefixes are
+/// only run once. The counterexample is the mismatching record that comes first in the file.
+pub struct DatasetTeacher {
+    trie: Vec<TrieNode>,
+    unknown_word: UnknownWord,
+}
+
+// Node 0 is the empty word. A node knows its parent and the symbol leading to it, so that a
+// counterexample can be spelled out, and the line of its record, if it has one.
+struct TrieNode {
+    children: HashMap<String, usize>,
+    parent: Option<(usize, String)>,
+    record: Option<(usize, bool)>,
+}
+
+impl DatasetTeacher {
+    /// Reads lines of comma-separated symbols followed by a `0` or `1` label, e.g. `a,b,1`; a
+    /// line holding just a label is the empty word. Whitespace around fields is ignored, as
+    /// are empty lines.
+    pub fn from_csv(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(path, |line| {
+            let (word, label) = line.rsplit_once(',').unwrap_or(("", line));
+            let label = match label.trim() {
+                "1" => true,
+                "0" => false,
+                _ => return Err("label must be 0 or 1".to_string()),
+            };
+            let word = if word.trim().is_empty() { Vec::new() } else { word.split(',').map(|symbol| symbol.trim().to_string()).collect() };
+            Ok((word, label))
+        })
+    }
+
+    pub fn with_unknown_word(mut self, unknown_word: UnknownWord) -> Self {
+        self.unknown_word = unknown_word;
+        self
+    }
+
+    /// Number of records.
+    pub fn len(&self) -> usize {
+        self.trie.iter().filter(|node| node.record.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn read(path: impl AsRef<Path>, parse: impl Fn(&str) -> Result<(Vec<String>, bool), String>) -> io::Result<Self> {
+        let mut teacher = DatasetTeacher {
+            trie: vec![TrieNode { children: HashMap::new(), parent: None, record: None }],
+            unknown_word: UnknownWord::Label(false),
+        };
+        for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}: {:?}", index + 1, reason, line));
+            let (word, label) = parse(&line).map_err(invalid)?;
+            teacher.insert(word, index + 1, label).map_err(invalid)?;
+        }
+        Ok(teacher)
+    }
+
+    // A word given twice with the same label is kept at its first line.
+    fn insert(&mut self, word: Vec<String>, line: usize, label: bool) -> Result<(), String> {
+        let mut node = 0;
+        for symbol in word {
+            node = match self.trie[node].children.get(&symbol) {
+                Some(child) => *child,
+                None => {
+                    self.trie.push(TrieNode { children: HashMap::new(), parent: Some((node, symbol.clone())), record: None });
+                    let child = self.trie.len() - 1;
+                    self.trie[node].children.insert(symbol, child);
+                    child
+                }
+            };
+        }
+        match self.trie[node].record {
+            Some((first, previous)) if previous != label => Err(format!("word is labeled {} on line {}", previous as u8, first)),
+            Some(_) => Ok(()),
+            None => {
+                self.trie[node].record = Some((line, label));
+                Ok(())
+            }
+        }
+    }
+
+    fn word(&self, mut node: usize) -> Vec<String> {
+        let mut word = Vec::new();
+        while let Some((parent, symbol)) = &self.trie[node].parent {
+            word.push(symbol.clone());
+            node = *parent;
+        }
+        word.reverse();
+        word
+    }
+
+    fn label(&self, states: &[String]) -> Option<bool> {
+        let mut node = 0;
+        for symbol in states.iter().filter(|symbol| !symbol.is_empty()) {
+            node = *self.trie[node].children.get(symbol)?;
+        }
+        self.trie[node].record.map(|(_, label)| label)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl DatasetTeacher {
+    /// Reads one JSON object per line, `{"word": ["a", "b"], "label": 1}`, with a label of `0`,
+    /// `1`, `false` or `true`.
+    pub fn from_jsonl(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(path, |line| {
+            let record: serde_json::Value = serde_json::from_str(line).map_err(|err| err.to_string())?;
+            let word = record["word"].as_array()
+                .and_then(|symbols| symbols.iter().map(|symbol| symbol.as_str().map(str::to_string)).collect::<Option<Vec<String>>>())
+                .ok_or("`word` must be an array of strings")?;
+            let label = match &record["label"] {
+                serde_json::Value::Bool(label) => *label,
+                label if label.as_u64() == Some(1) => true,
+                label if label.as_u64() == Some(0) => false,
+                _ => return Err("`label` must be 0, 1, false or true".to_string()),
+            };
+            Ok((word, label))
+        })
+    }
+}
+
+impl Teacher<String> for DatasetTeacher {
+
+    fn membership_query(&self, states: &[String]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // Depth first through the trie, following the hypothesis along.
+    fn equivalence_query(&self, automaton: Automaton<Vec<String>, String>) -> EquivalenceAnswer<String> {
+        let mut first_mismatch: Option<(usize, usize)> = None;
+        let mut stack = vec![(0, automaton.get_initial_state())];
+        while let Some((node, state)) = stack.pop() {
+            if let Some((line, label)) = self.trie[node].record
+                && state.is_some_and(|state| state.is_accepting()) != label
+                && first_mismatch.is_none_or(|(first, _)| line < first) {
+                    first_mismatch = Some((line, node));
+                }
+            for (symbol, child) in &self.trie[node].children {
+                let next = state.and_then(|state| state.transition(symbol)).and_then(|target| automaton.get_state(target));
+                stack.push((*child, next));
+            }
+        }
+        match first_mismatch {
+            Some((_, node)) => Err(self.word(node)),
+            None => Ok(()),
+        }
+    }
+
+    fn try_membership_query(&self, states: &[String]) -> Result<bool, TeacherError> {
+        match (self.label(states), self.unknown_word) {
+            (Some(label), _) => Ok(label),
+            (None, UnknownWord::Label(label)) => Ok(label),
+            (None, UnknownWord::Error) => Err(TeacherError::Other(format!("word {:?} is not in the dataset", states))),
+        }
+    }
+}
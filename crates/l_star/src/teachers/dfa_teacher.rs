@@ -0,0 +1,77 @@
+use crate::teacher::{EquivalenceAnswer, Teacher};
+use crate::alphabet::Alphabet;
+use crate::automaton::{Automaton, DenseAutomaton};
+use crate::regex_compile::{self, RegexCompileError};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/// A teacher for a known target automaton, with exact equivalence queries: a hypothesis is
+/// compared with the target through their product, and a shortest word they disagree on is
+/// returned as the counterexample.
+pub struct DfaTeacher<T> {
+    target: DenseAutomaton<T>,
+}
+
+impl<T: Eq + Hash + Clone> DfaTeacher<T> {
+    pub fn new<StateId: Eq + Hash + Clone>(target: &Automaton<StateId, T>) -> Self {
+        DfaTeacher {
+            target: DenseAutomaton::from(target),
+        }
+    }
+}
+
+impl DfaTeacher<char> {
+    /// A teacher for the words over `alphabet` that `pattern` matches, compiled with
+    /// `regex_compile::compile`: exact and without learning the pattern first, but only for the
+    /// regex subset supported there.
+    pub fn from_regex(pattern: &str, alphabet: &Alphabet<char>) -> Result<Self, RegexCompileError> {
+        Ok(DfaTeacher::new(&regex_compile::compile(pattern, alphabet)?))
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> Teacher<T> for DfaTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        // The learner writes the empty word as `T::default()`, so those symbols are not input.
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        self.target.accepts(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        match self.target.distinguishing_word(&DenseAutomaton::from(&automaton)) {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learner::Learner;
+    use regex::Regex;
+
+    #[test]
+    fn learns_a_compiled_regex() {
+        let alphabet = Alphabet::new(['a', 'b', 'c']);
+        let pattern = "^(ab|c)*b?$|cc";
+        let teacher = DfaTeacher::from_regex(pattern, &alphabet).unwrap();
+        let learned = Learner::new(alphabet, Box::new(teacher)).learn().unwrap();
+
+        let regex = Regex::new(pattern).unwrap();
+        let mut words = vec![String::new()];
+        for _ in 0..=5 {
+            for word in &words {
+                assert_eq!(learned.accepts_str(word), regex.is_match(word), "{:?}", word);
+            }
+            words = words.iter().flat_map(|word| ['a', 'b', 'c'].map(|symbol| format!("{}{}", word, symbol))).collect();
+        }
+    }
+
+    #[test]
+    fn a_malformed_pattern_is_an_error() {
+        let alphabet = Alphabet::new(['a']);
+        assert!(matches!(DfaTeacher::from_regex("(a", &alphabet), Err(RegexCompileError::UnexpectedEnd)));
+    }
+}
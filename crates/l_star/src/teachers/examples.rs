@@ -0,0 +1,142 @@
+//! Ready-made targets for trying the learner out, each a `DfaTeacher` over a reference
+//! automaton, so that equivalence queries are exact.
+
+use crate::teachers::dfa_teacher::DfaTeacher;
+use crate::alphabet::Alphabet;
+use crate::automaton::{Automaton, DfaState};
+use std::collections::HashMap;
+
+/// Words over `alphabet` in which `symbol` occurs a multiple of `k` times, 0 included; a `k`
+/// of 0 counts as 1.
+pub fn count_divisible(alphabet: &Alphabet<char>, symbol: char, k: usize) -> DfaTeacher<char> {
+    let k = k.max(1);
+    let state = |count: usize| DfaState::new(count, count == 0);
+    let mut automaton = Automaton::new(state(0));
+    for count in 0..k {
+        for a in alphabet {
+            let next = if *a == symbol { (count + 1) % k } else { count };
+            automaton.add_transition(&state(count), &state(next), a);
+        }
+    }
+    DfaTeacher::new(&automaton)
+}
+
+/// Words over `alphabet` with an even number of `symbol`s.
+pub fn even_count(alphabet: &Alphabet<char>, symbol: char) -> DfaTeacher<char> {
+    count_divisible(alphabet, symbol, 2)
+}
+
+/// Words over `alphabet` containing `substring`. State `i` has matched the first `i`
+/// characters of it, as in Knuth–Morris–Pratt.
+pub fn contains(alphabet: &Alphabet<char>, substring: &str) -> DfaTeacher<char> {
+    let pattern: Vec<char> = substring.chars().collect();
+    let state = |matched: usize| DfaState::new(matched, matched == pattern.len());
+    let mut automaton = Automaton::new(state(0));
+    for matched in 0..=pattern.len() {
+        for a in alphabet {
+            let next = if matched == pattern.len() {
+                matched
+            } else {
+                // The longest prefix of the pattern that ends the text read so far.
+                let mut read = pattern[..matched].to_vec();
+                read.push(*a);
+                (0..=read.len()).rev().find(|len| *len <= pattern.len() && read.ends_with(&pattern[..*len])).unwrap_or(0)
+            };
+            automaton.add_transition(&state(matched), &state(next), a);
+        }
+    }
+    DfaTeacher::new(&automaton)
+}
+
+/// Balanced words of `open` and `close` of length at most `max_len`, such as `(())()`. A
+/// state is the length read and the number of brackets still open, kept only while they can
+/// all be closed in time.
+pub fn balanced(open: char, close: char, max_len: usize) -> DfaTeacher<char> {
+    let mut ids: HashMap<(usize, usize), usize> = HashMap::from([((0, 0), 0)]);
+    let mut pending: Vec<(usize, usize)> = vec![(0, 0)];
+    let mut edges = Vec::new();
+    while let Some((len, depth)) = pending.pop() {
+        if len == max_len {
+            continue;
+        }
+        let successors = [(open, Some(depth + 1)), (close, depth.checked_sub(1))];
+        for (symbol, next_depth) in successors {
+            if let Some(next_depth) = next_depth
+                && next_depth < max_len - len {
+                let next = (len + 1, next_depth);
+                let next_id = ids.len();
+                let id = *ids.entry(next).or_insert_with(|| {
+                    pending.push(next);
+                    next_id
+                });
+                edges.push((ids[&(len, depth)], symbol, id));
+            }
+        }
+    }
+
+    let accepting: Vec<usize> = ids.iter().filter(|((_, depth), _)| *depth == 0).map(|(_, id)| *id).collect();
+    let state = |id: usize| DfaState::new(id, accepting.contains(&id));
+    let mut automaton = Automaton::new(state(0));
+    for (from, symbol, to) in &edges {
+        automaton.add_transition(&state(*from), &state(*to), symbol);
+    }
+    DfaTeacher::new(&automaton)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learner::Learner;
+
+    // Learns `teacher` and checks the result against `expected` on every word over `symbols` of
+    // at most `max_len` symbols.
+    fn check(symbols: &[char], teacher: DfaTeacher<char>, expected: impl Fn(&[char]) -> bool, max_len: usize) {
+        let learned = Learner::new(Alphabet::new(symbols.to_vec()), Box::new(teacher)).learn().unwrap();
+        let mut layer = vec![Vec::new()];
+        for _ in 0..=max_len {
+            for word in &layer {
+                assert_eq!(learned.accepts(word), expected(word), "{:?}", word.iter().collect::<String>());
+            }
+            layer = layer.iter()
+                .flat_map(|word| symbols.iter().map(move |symbol| [word.as_slice(), &[*symbol]].concat()))
+                .collect();
+        }
+    }
+
+    fn occurrences(word: &[char], symbol: char) -> usize {
+        word.iter().filter(|c| **c == symbol).count()
+    }
+
+    #[test]
+    fn counts() {
+        let ab = Alphabet::new(['a', 'b']);
+        check(&['a', 'b'], count_divisible(&ab, 'a', 3), |word| occurrences(word, 'a').is_multiple_of(3), 8);
+        check(&['a', 'b'], count_divisible(&ab, 'a', 0), |_| true, 8);
+        check(&['a', 'b'], even_count(&ab, 'b'), |word| occurrences(word, 'b').is_multiple_of(2), 8);
+    }
+
+    #[test]
+    fn contains_falls_back_along_the_pattern() {
+        let abc = Alphabet::new(['a', 'b', 'c']);
+        for substring in ["abab", "aab", "abcab", ""] {
+            let expected = |word: &[char]| word.iter().collect::<String>().contains(substring);
+            check(&['a', 'b', 'c'], contains(&abc, substring), expected, 7);
+        }
+    }
+
+    #[test]
+    fn balanced_up_to_the_length_bound() {
+        let expected = |word: &[char]| {
+            let mut depth: usize = 0;
+            for c in word {
+                match c {
+                    '(' => depth += 1,
+                    _ if depth == 0 => return false,
+                    _ => depth -= 1,
+                }
+            }
+            depth == 0 && word.len() <= 6
+        };
+        check(&['(', ')'], balanced('(', ')', 6), expected, 9);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+
+/// What `FileTeacher` answers for a word that is not in the dataset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnknownWordPolicy {
+    /// Closed-world assumption: anything not listed as positive is rejected.
+    Reject,
+    /// Fail the query with a `TeacherError`, which stops the learner with a `LearnError`.
+    Error,
+}
+
+/// A teacher answering from a dataset of labeled examples, one `word\tlabel` per line with
+/// label `0` or `1`. Every character of `word` is one symbol. Equivalence queries return the
+/// first example in file order that the hypothesis misclassifies.
+pub struct FileTeacher {
+    labels: HashMap<String, bool>,
+    // The distinct examples in file order.
+    examples: Vec<(String, bool)>,
+    unknown_word_policy: UnknownWordPolicy,
+}
+
+impl FileTeacher {
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        let mut labels = HashMap::new();
+        let mut examples = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = |reason: &str| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}: {:?}", index + 1, reason, line),
+            );
+
+            let (word, label) = line.rsplit_once('\t').ok_or_else(|| invalid("expected `word\\tlabel`"))?;
+            let label = match label.trim() {
+                "1" => true,
+                "0" => false,
+                _ => return Err(invalid("label must be 0 or 1")),
+            };
+            match labels.insert(word.to_string(), label) {
+                None => examples.push((word.to_string(), label)),
+                Some(previous) if previous != label => return Err(invalid("word is labeled both 0 and 1")),
+                Some(_) => {}
+            }
+        }
+
+        Ok(FileTeacher {
+            labels,
+            examples,
+            unknown_word_policy: UnknownWordPolicy::Reject,
+        })
+    }
+
+    pub fn with_unknown_word_policy(mut self, policy: UnknownWordPolicy) -> Self {
+        self.unknown_word_policy = policy;
+        self
+    }
+
+    fn symbols(word: &str) -> Vec<String> {
+        word.chars().map(|c| c.to_string()).collect()
+    }
+}
+
+
+impl Teacher<String> for FileTeacher {
+
+    fn membership_query(&self, states: &[String]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<String>, String>) -> EquivalenceAnswer<String> {
+        for (word, label) in &self.examples {
+            let symbols = Self::symbols(word);
+            if automaton.accepts(&symbols) != *label {
+                return Err(symbols);
+            }
+        }
+        Ok(())
+    }
+
+    fn try_membership_query(&self, states: &[String]) -> Result<bool, TeacherError> {
+        let input = states.join("");
+        match (self.labels.get(&input), self.unknown_word_policy) {
+            (Some(label), _) => Ok(*label),
+            (None, UnknownWordPolicy::Reject) => Ok(false),
+            (None, UnknownWordPolicy::Error) => Err(TeacherError::Other(format!("word {:?} is not in the dataset", input))),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::teacher::{EquivalenceAnswer, Teacher};
+use crate::automaton::{Automaton, DenseAutomaton, DfaState};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/// A teacher for a finite language given as its words, e.g. the command sequences a spec
+/// allows. Equivalence queries are exact: the words are built into a trie-shaped automaton,
+/// one state per prefix, and a hypothesis is compared with it like in `DfaTeacher`.
+pub struct FiniteLanguageTeacher<T> {
+    words: HashSet<Vec<T>>,
+    target: DenseAutomaton<T>,
+}
+
+impl<T: Eq + Hash + Clone + Default> FiniteLanguageTeacher<T> {
+    /// `T::default()` symbols in `words` are dropped, as in queries.
+    pub fn new(words: HashSet<Vec<T>>) -> Self {
+        let words: HashSet<Vec<T>> = words.into_iter()
+            .map(|word| word.into_iter().filter(|symbol| *symbol != T::default()).collect())
+            .collect();
+        let state = |prefix: &[T]| DfaState::new(prefix.to_vec(), words.contains(prefix));
+        let mut trie = Automaton::new(state(&[]));
+        for word in &words {
+            for end in 1..=word.len() {
+                trie.add_transition(&state(&word[..end - 1]), &state(&word[..end]), &word[end - 1]);
+            }
+        }
+        FiniteLanguageTeacher {
+            target: DenseAutomaton::from(&trie),
+            words,
+        }
+    }
+
+    pub fn words(&self) -> &HashSet<Vec<T>> {
+        &self.words
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> Teacher<T> for FiniteLanguageTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        self.words.contains(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        match self.target.distinguishing_word(&DenseAutomaton::from(&automaton)) {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+}
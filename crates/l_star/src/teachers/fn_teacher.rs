@@ -0,0 +1,74 @@
+use crate::teacher::{EquivalenceAnswer, Membership, Teacher};
+use crate::automaton::Automaton;
+use crate::random::SplitMix64;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+type Equivalence<T> = Box<dyn Fn(&Automaton<Vec<T>, T>) -> Result<(), Vec<T>>>;
+
+/// A teacher made from closures, for quick experiments. The membership closure sees words
+/// without the learner's `T::default()` padding. Without `with_equivalence`, hypotheses are
+/// checked on 1000 random words of up to 16 symbols over their own labels, so a wrong
+/// hypothesis can slip through, but not silently on every run.
+pub struct FnTeacher<T: Eq + Hash + Clone> {
+    membership: Membership<T>,
+    equivalence: Option<Equivalence<T>>,
+    rng: RefCell<SplitMix64>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Ord> FnTeacher<T> {
+    pub fn new(membership: impl Fn(&[T]) -> bool + 'static) -> Self {
+        FnTeacher {
+            membership: Box::new(membership),
+            equivalence: None,
+            rng: RefCell::new(SplitMix64::new(0)),
+        }
+    }
+
+    /// Answers equivalence queries with `equivalence`, which returns a counterexample when the
+    /// hypothesis is wrong.
+    pub fn with_equivalence(mut self, equivalence: impl Fn(&Automaton<Vec<T>, T>) -> Result<(), Vec<T>> + 'static) -> Self {
+        self.equivalence = Some(Box::new(equivalence));
+        self
+    }
+
+    // The default equivalence check.
+    fn random_counterexample(&self, automaton: &Automaton<Vec<T>, T>) -> Option<Vec<T>> {
+        let labels: Vec<T> = automaton.get_states().values()
+            .flat_map(|state| state.transitions().map(|(label, _)| label.clone()))
+            .collect::<BTreeSet<T>>()
+            .into_iter()
+            .collect();
+        let mut rng = self.rng.borrow_mut();
+        for _ in 0..1000 {
+            let len = if labels.is_empty() { 0 } else { rng.below(17) };
+            let word: Vec<T> = (0..len).map(|_| labels[rng.below(labels.len())].clone()).collect();
+            if automaton.accepts(&word) != self.membership_query(&word) {
+                return Some(word);
+            }
+        }
+        None
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Ord> Teacher<T> for FnTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        (self.membership)(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        let counterexample = match &self.equivalence {
+            Some(equivalence) => equivalence(&automaton).err(),
+            None => self.random_counterexample(&automaton),
+        };
+        match counterexample {
+            Some(word) => Err(word),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use crate::teachers::recording_teacher::HypothesisRecord;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::thread;
+use std::time::Duration;
+
+
+/// Why a remote teacher did not answer.
+#[derive(Debug)]
+pub enum HttpError {
+    /// The server could not be reached, or did not answer in time, on every attempt.
+    Transport(String),
+    /// The server answered with an error status.
+    Status { status: u16, body: String },
+    /// The server answered with a body that is not the expected JSON.
+    Malformed(String),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Transport(reason) => write!(f, "could not reach the teacher: {}", reason),
+            HttpError::Status { status, body } => write!(f, "the teacher answered with status {}: {}", status, body.trim()),
+            HttpError::Malformed(reason) => write!(f, "unexpected answer from the teacher: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<HttpError> for TeacherError {
+    fn from(err: HttpError) -> Self {
+        TeacherError::Other(err.to_string())
+    }
+}
+
+#[derive(Serialize)]
+struct MembershipRequest<'a, T> {
+    word: &'a [T],
+}
+
+#[derive(Deserialize)]
+struct MembershipReply {
+    accepted: bool,
+}
+
+#[derive(Serialize)]
+struct EquivalenceRequest<T> {
+    hypothesis: HypothesisRecord<T>,
+}
+
+#[derive(Deserialize)]
+struct EquivalenceReply<T> {
+    equivalent: Option<bool>,
+    counterexample: Option<Vec<T>>,
+}
+
+/// A teacher behind an HTTP service. Membership queries are posted to `<base>/membership` as
+/// `{"word": [...]}` and answered with `{"accepted": bool}`; hypotheses are posted to
+/// `<base>/equivalence` as `{"hypothesis": HypothesisRecord}` and answered with
+/// `{"equivalent": true}` or `{"counterexample": [...]}`.
+///
+/// Transport failures, `429` and `5xx` answers are retried with exponential backoff; anything
+/// else the server gets wrong stops the learner with a `TeacherError` instead of a panic.
+pub struct HttpTeacher<T> {
+    base_url: String,
+    agent: ureq::Agent,
+    retries: u32,
+    backoff: Duration,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Serialize + DeserializeOwned> HttpTeacher<T> {
+    /// A 10 second timeout and 3 retries, starting 100 ms apart.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let timeout = Duration::from_secs(10);
+        HttpTeacher {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+            retries: 3,
+            backoff: Duration::from_millis(100),
+            _symbols: PhantomData,
+        }
+    }
+
+    /// The longest a single request may take, connecting included.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.agent = ureq::AgentBuilder::new().timeout(timeout).build();
+        self
+    }
+
+    /// How often a transient failure is retried before giving up.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// The wait before the first retry; it doubles with every further one.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn membership(&self, word: &[T]) -> Result<bool, HttpError> {
+        let word: Vec<T> = word.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        let reply: MembershipReply = self.post("membership", &MembershipRequest { word: &word })?;
+        Ok(reply.accepted)
+    }
+
+    pub fn equivalence(&self, automaton: &Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, HttpError> {
+        let reply: EquivalenceReply<T> = self.post("equivalence", &EquivalenceRequest { hypothesis: HypothesisRecord::from(automaton) })?;
+        match (reply.equivalent, reply.counterexample) {
+            (_, Some(counterexample)) => Ok(Err(counterexample)),
+            (Some(true), None) => Ok(Ok(())),
+            _ => Err(HttpError::Malformed("neither \"equivalent\": true nor a counterexample".to_string())),
+        }
+    }
+
+    fn post<Reply: DeserializeOwned>(&self, endpoint: &str, request: &impl Serialize) -> Result<Reply, HttpError> {
+        let url = format!("{}/{}", self.base_url, endpoint);
+        let body = serde_json::to_string(request).map_err(|err| HttpError::Malformed(err.to_string()))?;
+        let mut wait = self.backoff;
+        let mut attempt = 0;
+        let response = loop {
+            let err = match self.agent.post(&url).set("Content-Type", "application/json").send_string(&body) {
+                Ok(response) => break response,
+                Err(ureq::Error::Status(status, response)) => HttpError::Status { status, body: response.into_string().unwrap_or_default() },
+                Err(ureq::Error::Transport(transport)) => HttpError::Transport(transport.to_string()),
+            };
+            let transient = match &err {
+                HttpError::Status { status, .. } => *status == 429 || *status >= 500,
+                _ => true,
+            };
+            if !transient || attempt == self.retries {
+                return Err(err);
+            }
+            thread::sleep(wait);
+            wait *= 2;
+            attempt += 1;
+        };
+        let text = response.into_string().map_err(|err| HttpError::Transport(err.to_string()))?;
+        serde_json::from_str(&text).map_err(|err| HttpError::Malformed(format!("{} in {:?}", err, text)))
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Serialize + DeserializeOwned> Teacher<T> for HttpTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        Ok(self.membership(states)?)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        Ok(self.equivalence(&automaton)?)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::alphabet::Alphabet;
+use crate::automaton::Automaton;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+
+/// A teacher that asks a person. Membership queries are shown as the joined word and answered
+/// `y` or `n`; for equivalence queries the hypothesis is written as DOT to a temporary file and
+/// the answer is `accept` or a counterexample as space-separated symbols (`ε` for the empty
+/// word). Invalid input is asked again, and no word is asked twice. When the input ends, the
+/// learner stops with `TeacherError::Other`.
+pub struct InteractiveTeacher<T: Eq + Hash + Clone> {
+    alphabet: Alphabet<T>,
+    input: RefCell<Box<dyn BufRead>>,
+    output: RefCell<Box<dyn Write>>,
+    answers: RefCell<HashMap<Vec<T>, bool>>,
+    queries: Cell<usize>,
+    hypotheses: Cell<usize>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Display> InteractiveTeacher<T> {
+    /// Asks on stdout and reads stdin.
+    pub fn new(alphabet: Alphabet<T>) -> Self {
+        Self::with_io(alphabet, io::BufReader::new(io::stdin()), io::stdout())
+    }
+
+    pub fn with_io(alphabet: Alphabet<T>, input: impl BufRead + 'static, output: impl Write + 'static) -> Self {
+        InteractiveTeacher {
+            alphabet,
+            input: RefCell::new(Box::new(input)),
+            output: RefCell::new(Box::new(output)),
+            answers: RefCell::new(HashMap::new()),
+            queries: Cell::new(0),
+            hypotheses: Cell::new(0),
+        }
+    }
+
+    // Shows `prompt` and reads one trimmed line.
+    fn ask(&self, prompt: &str) -> Result<String, TeacherError> {
+        let failed = |err: io::Error| TeacherError::Other(err.to_string());
+        let mut output = self.output.borrow_mut();
+        write!(output, "{}", prompt).map_err(failed)?;
+        output.flush().map_err(failed)?;
+        let mut line = String::new();
+        if self.input.borrow_mut().read_line(&mut line).map_err(failed)? == 0 {
+            return Err(TeacherError::Other("input closed".to_string()));
+        }
+        Ok(line.trim().to_string())
+    }
+
+    fn tell(&self, message: &str) -> Result<(), TeacherError> {
+        writeln!(self.output.borrow_mut(), "{}", message).map_err(|err| TeacherError::Other(err.to_string()))
+    }
+
+    fn show(word: &[T]) -> String {
+        let joined: String = word.iter().map(|symbol| symbol.to_string()).collect();
+        if joined.is_empty() { "ε".to_string() } else { joined }
+    }
+
+    // A space-separated word over the alphabet, or the first token that is no symbol.
+    fn parse(&self, line: &str) -> Result<Vec<T>, String> {
+        line.split_whitespace()
+            .filter(|token| *token != "ε")
+            .map(|token| self.alphabet.iter().find(|symbol| symbol.to_string() == token).cloned().ok_or_else(|| token.to_string()))
+            .collect()
+    }
+
+    fn write_dot(&self, automaton: &Automaton<Vec<T>, T>) -> Result<PathBuf, TeacherError> {
+        self.hypotheses.set(self.hypotheses.get() + 1);
+        let path = std::env::temp_dir().join(format!("hypothesis-{}-{}.dot", std::process::id(), self.hypotheses.get()));
+        std::fs::write(&path, automaton.to_dot()).map_err(|err| TeacherError::Other(err.to_string()))?;
+        Ok(path)
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Display> Teacher<T> for InteractiveTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        if let Some(answer) = self.answers.borrow().get(&word) {
+            return Ok(*answer);
+        }
+        self.queries.set(self.queries.get() + 1);
+        let prompt = format!("[{}] is {} in the language? (y/n) ", self.queries.get(), Self::show(&word));
+        let answer = loop {
+            match self.ask(&prompt)?.to_lowercase().as_str() {
+                "y" | "yes" => break true,
+                "n" | "no" => break false,
+                _ => self.tell("please answer y or n")?,
+            }
+        };
+        self.answers.borrow_mut().insert(word, answer);
+        Ok(answer)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        let path = self.write_dot(&automaton)?;
+        self.tell(&format!("hypothesis with {} states written to {}", automaton.state_count(), path.display()))?;
+        loop {
+            let line = self.ask("accept, or type a counterexample as space-separated symbols: ")?;
+            if line.eq_ignore_ascii_case("accept") {
+                return Ok(Ok(()));
+            }
+            if line.is_empty() {
+                continue;
+            }
+            match self.parse(&line) {
+                Err(token) => self.tell(&format!("{:?} is not a symbol of the alphabet", token))?,
+                // A word you already answered that the hypothesis gets right cannot be a counterexample.
+                Ok(word) if self.answers.borrow().get(&word) == Some(&automaton.accepts(&word)) => {
+                    self.tell(&format!("the hypothesis already classifies {} as you answered", Self::show(&word)))?;
+                }
+                Ok(word) => return Ok(Err(word)),
+            }
+        }
+    }
+}
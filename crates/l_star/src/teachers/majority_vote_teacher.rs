@@ -0,0 +1,137 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// When `MajorityVoteTeacher` holds a vote on a membership query.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Voting {
+    /// On every query.
+    #[default]
+    Always,
+    /// Only when a word asked again gets a different answer than the first time; until then
+    /// each word is asked once.
+    OnDisagreement,
+}
+
+/// Smooths over a noisy `Inner` by asking each membership query several times and answering
+/// with the majority. Counterexamples are checked the same way before they reach the learner:
+/// one the hypothesis already classifies like the majority is dropped, and when every
+/// counterexample of an equivalence query is dropped the query is repeated, up to once per
+/// vote, after which the hypothesis is accepted. Put a `CachingTeacher` around it, since the
+/// learner may not terminate when one word gets two different answers.
+pub struct MajorityVoteTeacher<T, Inner> {
+    inner: Inner,
+    votes: usize,
+    voting: Voting,
+    answers: RefCell<HashMap<Vec<T>, bool>>,
+    flips: Cell<usize>,
+    spurious_counterexamples: Cell<usize>,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> MajorityVoteTeacher<T, Inner> {
+    /// Three votes on every query.
+    pub fn new(inner: Inner) -> Self {
+        MajorityVoteTeacher {
+            inner,
+            votes: 3,
+            voting: Voting::Always,
+            answers: RefCell::new(HashMap::new()),
+            flips: Cell::new(0),
+            spurious_counterexamples: Cell::new(0),
+            _symbols: PhantomData,
+        }
+    }
+
+    /// Panics unless `votes` is odd, so that there is always a majority.
+    pub fn with_votes(mut self, votes: usize) -> Self {
+        assert!(votes % 2 == 1, "the number of votes must be odd, got {}", votes);
+        self.votes = votes;
+        self
+    }
+
+    pub fn with_voting(mut self, voting: Voting) -> Self {
+        self.voting = voting;
+        self
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Membership queries on which the inner teacher contradicted itself.
+    pub fn flips(&self) -> usize {
+        self.flips.get()
+    }
+
+    /// Counterexamples dropped because the majority agreed with the hypothesis.
+    pub fn spurious_counterexamples(&self) -> usize {
+        self.spurious_counterexamples.get()
+    }
+
+    // The majority of `votes` answers, stopping as soon as one answer has it.
+    fn vote(&self, word: &[T]) -> Result<bool, TeacherError> {
+        let (mut yes, mut no) = (0, 0);
+        let majority = self.votes / 2 + 1;
+        while yes < majority && no < majority {
+            if self.inner.try_membership_query(word)? {
+                yes += 1;
+            } else {
+                no += 1;
+            }
+        }
+        if yes > 0 && no > 0 {
+            self.flips.set(self.flips.get() + 1);
+        }
+        Ok(yes > no)
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> Teacher<T> for MajorityVoteTeacher<T, Inner> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        if self.voting == Voting::Always {
+            return self.vote(&word);
+        }
+        let fresh = self.inner.try_membership_query(&word)?;
+        let previous = self.answers.borrow().get(&word).copied();
+        let answer = match previous {
+            Some(previous) if previous != fresh => {
+                self.flips.set(self.flips.get() + 1);
+                self.vote(&word)?
+            }
+            _ => fresh,
+        };
+        self.answers.borrow_mut().insert(word, answer);
+        Ok(answer)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        for _ in 0..self.votes {
+            let counterexample = match self.inner.try_equivalence_query(automaton.clone())? {
+                Ok(()) => return Ok(Ok(())),
+                Err(counterexample) => counterexample,
+            };
+            let word: Vec<T> = counterexample.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+            if self.vote(&word)? != automaton.accepts(&word) {
+                return Ok(Err(counterexample));
+            }
+            self.spurious_counterexamples.set(self.spurious_counterexamples.get() + 1);
+        }
+        Ok(Ok(()))
+    }
+}
@@ -0,0 +1,47 @@
+use crate::teacher::{EquivalenceAnswer, MealyTeacher};
+use crate::automaton::MealyMachine;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/// A teacher for a known target Mealy machine, with exact equivalence queries: a shortest word
+/// on which the hypothesis and the target produce different outputs is the counterexample.
+/// The target should have a transition for every input in every state; where it has none,
+/// output queries are answered only up to the missing transition.
+pub struct MealyMachineTeacher<StateId: Eq + Hash + Clone + Debug, I: Eq + Hash + Clone, O: Clone> {
+    target: MealyMachine<StateId, I, O>,
+}
+
+impl<StateId, I, O> MealyMachineTeacher<StateId, I, O>
+where
+    StateId: Eq + Hash + Clone + Debug,
+    I: Eq + Hash + Clone + Debug,
+    O: Eq + Clone + Debug,
+{
+    pub fn new(target: MealyMachine<StateId, I, O>) -> Self {
+        MealyMachineTeacher { target }
+    }
+
+    pub fn target(&self) -> &MealyMachine<StateId, I, O> {
+        &self.target
+    }
+}
+
+impl<StateId, I, O> MealyTeacher<I, O> for MealyMachineTeacher<StateId, I, O>
+where
+    StateId: Eq + Hash + Clone + Debug,
+    I: Eq + Hash + Clone + Debug,
+    O: Eq + Clone + Debug,
+{
+
+    fn output_query(&mut self, word: &[I]) -> Vec<O> {
+        self.target.run(word)
+    }
+
+    fn equivalence_query(&mut self, hypothesis: &MealyMachine<Vec<I>, I, O>) -> EquivalenceAnswer<I> {
+        match self.target.distinguishing_word(hypothesis) {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+}
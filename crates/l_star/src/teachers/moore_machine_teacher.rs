@@ -0,0 +1,92 @@
+use crate::teacher::{EquivalenceAnswer, MooreTeacher, Teacher, TeacherError};
+use crate::automaton::{Automaton, MooreMachine};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// A teacher for a known target Moore machine, with exact equivalence queries: a shortest word
+/// the hypothesis and the target classify differently is the counterexample. Words running
+/// into a missing transition of the target cannot be classified, so the target should have a
+/// transition for every input in every state.
+pub struct MooreMachineTeacher<StateId: Eq + Hash + Clone + Debug, I: Eq + Hash + Clone, O: Clone> {
+    target: MooreMachine<StateId, I, O>,
+}
+
+impl<StateId, I, O> MooreMachineTeacher<StateId, I, O>
+where
+    StateId: Eq + Hash + Clone + Debug,
+    I: Eq + Hash + Clone + Debug,
+    O: Eq + Hash + Clone + Debug,
+{
+    pub fn new(target: MooreMachine<StateId, I, O>) -> Self {
+        MooreMachineTeacher { target }
+    }
+
+    pub fn target(&self) -> &MooreMachine<StateId, I, O> {
+        &self.target
+    }
+}
+
+impl<StateId, I, O> MooreTeacher<I, O> for MooreMachineTeacher<StateId, I, O>
+where
+    StateId: Eq + Hash + Clone + Debug,
+    I: Eq + Hash + Clone + Debug,
+    O: Eq + Hash + Clone + Debug,
+{
+
+    fn classify(&self, word: &[I]) -> O {
+        self.try_classify(word).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, hypothesis: &MooreMachine<Vec<I>, I, O>) -> EquivalenceAnswer<I> {
+        match self.target.distinguishing_word(hypothesis) {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+
+    fn try_classify(&self, word: &[I]) -> Result<O, TeacherError> {
+        self.target.output(word).cloned()
+            .ok_or_else(|| TeacherError::Other(format!("the target has no transition along {:?}", word)))
+    }
+}
+
+/// A `Teacher` seen as a `MooreTeacher` with boolean outputs, `true` for accepted words, so that
+/// DFA targets can be learned as Moore machines.
+pub struct BooleanMooreTeacher<T, Inner> {
+    inner: Inner,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> BooleanMooreTeacher<T, Inner> {
+    pub fn new(inner: Inner) -> Self {
+        BooleanMooreTeacher {
+            inner,
+            _symbols: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> MooreTeacher<T, bool> for BooleanMooreTeacher<T, Inner> {
+
+    fn classify(&self, word: &[T]) -> bool {
+        self.inner.membership_query(word)
+    }
+
+    fn equivalence_query(&self, hypothesis: &MooreMachine<Vec<T>, T, bool>) -> EquivalenceAnswer<T> {
+        self.inner.equivalence_query(Automaton::from(hypothesis))
+    }
+
+    fn try_classify(&self, word: &[T]) -> Result<bool, TeacherError> {
+        self.inner.try_membership_query(word)
+    }
+
+    fn try_equivalence_query(&self, hypothesis: &MooreMachine<Vec<T>, T, bool>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        self.inner.try_equivalence_query(Automaton::from(hypothesis))
+    }
+}
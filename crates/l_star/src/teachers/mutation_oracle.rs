@@ -0,0 +1,183 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Membership, Teacher, TeacherError};
+use crate::alphabet::Alphabet;
+use crate::automaton::Automaton;
+use crate::random::SplitMix64;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+// Mutations stacked onto one word to make a candidate: 1 up to this many.
+const MAX_STACKED_MUTATIONS: usize = 4;
+
+// Fresh words are sampled with lengths uniform up to this, as by `RandomWordOracle`.
+const FRESH_MAX_LEN: usize = 16;
+
+/// An approximate equivalence oracle in the manner of a mutational fuzzer: it keeps every
+/// counterexample it has found in a corpus and tests each hypothesis on mutants of the corpus
+/// words, made by inserting, deleting, replacing or duplicating symbols and by splicing two
+/// words together. Counterexamples tend to share structure, e.g. a deep protocol state reached
+/// by a long prefix, which mutants keep and uniformly sampled words rarely hit. To keep
+/// exploring, one mutant in `n + 1` for a corpus of `n` words is of a freshly sampled word
+/// instead, so an empty corpus, the start unless `with_corpus` adds to it, means random testing.
+pub struct MutationOracle<T> {
+    membership: Membership<T>,
+    alphabet: Alphabet<T>,
+    mutations: usize,
+    max_len: usize,
+    counterexamples: usize,
+    corpus: RefCell<Vec<Vec<T>>>,
+    rng: RefCell<SplitMix64>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> MutationOracle<T> {
+    /// Answers membership queries with `membership`. Defaults to 1000 mutants per hypothesis,
+    /// words up to 64 symbols, one counterexample per hypothesis, and seed 0.
+    pub fn from_fn(alphabet: Alphabet<T>, membership: impl Fn(&[T]) -> bool + 'static) -> Self {
+        MutationOracle {
+            membership: Box::new(membership),
+            alphabet,
+            mutations: 1000,
+            max_len: 64,
+            counterexamples: 1,
+            corpus: RefCell::new(Vec::new()),
+            rng: RefCell::new(SplitMix64::new(0)),
+        }
+    }
+
+    /// Answers membership queries with `teacher`, replacing its equivalence queries.
+    pub fn from_teacher(alphabet: Alphabet<T>, teacher: impl Teacher<T> + 'static) -> Self {
+        Self::from_fn(alphabet, move |word| teacher.membership_query(word))
+    }
+
+    /// Mutants tested per hypothesis.
+    pub fn with_mutations(mut self, mutations: usize) -> Self {
+        self.mutations = mutations;
+        self
+    }
+
+    /// Mutations that would make a word longer than `max_len` are skipped.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Hands the learner up to `limit` distinct counterexamples at once, as in
+    /// `RandomWordOracle::with_counterexamples`.
+    pub fn with_counterexamples(mut self, limit: usize) -> Self {
+        self.counterexamples = limit.max(1);
+        self
+    }
+
+    /// Adds words to start the corpus with, e.g. known interesting traces or the
+    /// counterexamples of an earlier session.
+    pub fn with_corpus(self, words: impl IntoIterator<Item = Vec<T>>) -> Self {
+        for word in words {
+            self.add_to_corpus(word);
+        }
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = RefCell::new(SplitMix64::new(seed));
+        self
+    }
+
+    /// The corpus so far, in the order words were added.
+    pub fn corpus(&self) -> Vec<Vec<T>> {
+        self.corpus.borrow().clone()
+    }
+
+    fn add_to_corpus(&self, word: Vec<T>) {
+        let word: Vec<T> = word.into_iter().filter(|symbol| *symbol != T::default()).collect();
+        let mut corpus = self.corpus.borrow_mut();
+        if !corpus.contains(&word) {
+            corpus.push(word);
+        }
+    }
+
+    fn mutant(&self, rng: &mut SplitMix64) -> Vec<T> {
+        if self.alphabet.is_empty() {
+            return Vec::new();
+        }
+        let corpus = self.corpus.borrow();
+        let mut word = match corpus.get(rng.below(corpus.len() + 1)) {
+            Some(word) => word.clone(),
+            None => (0..rng.below(FRESH_MAX_LEN.min(self.max_len) + 1)).map(|_| self.random_symbol(rng)).collect(),
+        };
+        for _ in 0..=rng.below(MAX_STACKED_MUTATIONS) {
+            let symbol = self.random_symbol(rng);
+            match rng.below(5) {
+                0 if word.len() < self.max_len => word.insert(rng.below(word.len() + 1), symbol),
+                1 if !word.is_empty() => {
+                    word.remove(rng.below(word.len()));
+                }
+                2 if !word.is_empty() => {
+                    let at = rng.below(word.len());
+                    word[at] = symbol;
+                }
+                3 if !word.is_empty() => {
+                    let start = rng.below(word.len());
+                    let end = start + 1 + rng.below(word.len() - start);
+                    if word.len() + end - start <= self.max_len {
+                        let segment: Vec<T> = word[start..end].to_vec();
+                        word.splice(end..end, segment);
+                    }
+                }
+                4 if !corpus.is_empty() => {
+                    let other = &corpus[rng.below(corpus.len())];
+                    let cut = rng.below(word.len() + 1);
+                    let other_cut = rng.below(other.len() + 1);
+                    word.truncate(cut);
+                    word.extend(other[other_cut..].iter().take(self.max_len.saturating_sub(cut)).cloned());
+                }
+                _ => {}
+            }
+        }
+        word
+    }
+
+    fn random_symbol(&self, rng: &mut SplitMix64) -> T {
+        self.alphabet.symbol(rng.below(self.alphabet.len())).clone()
+    }
+
+    // Up to `limit` distinct mutants the hypothesis gets wrong, in the order found; they join
+    // the corpus.
+    fn find_counterexamples(&self, automaton: &Automaton<Vec<T>, T>, limit: usize) -> Vec<Vec<T>> {
+        let mut rng = self.rng.borrow_mut();
+        let mut found: Vec<Vec<T>> = Vec::new();
+        for _ in 0..self.mutations {
+            let word = self.mutant(&mut rng);
+            if automaton.accepts(&word) != self.membership_query(&word) && !found.contains(&word) {
+                found.push(word);
+                if found.len() == limit {
+                    break;
+                }
+            }
+        }
+        for word in &found {
+            self.add_to_corpus(word.clone());
+        }
+        found
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> Teacher<T> for MutationOracle<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        (self.membership)(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        match self.find_counterexamples(&automaton, 1).pop() {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        let found = self.find_counterexamples(&automaton, self.counterexamples);
+        Ok(if found.is_empty() { Ok(()) } else { Err(found) })
+    }
+}
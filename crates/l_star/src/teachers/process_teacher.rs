@@ -0,0 +1,217 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::{Cell, RefCell};
+use std::ffi::OsStr;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+use std::io::{self, BufRead, BufReader, Write};
+use std::marker::PhantomData;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+
+/// Why a `ProcessTeacher` did not get an answer.
+#[derive(Debug)]
+pub enum ProcessError {
+    /// The program could not be started.
+    Spawn(io::Error),
+    /// Writing to or reading from the child failed.
+    Io(io::Error),
+    /// The child closed its output, usually because it exited.
+    Exited,
+    /// The child did not answer within the timeout; it has been killed.
+    Timeout(Duration),
+    /// The child answered something the protocol does not allow.
+    Malformed { request: String, reply: String },
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::Spawn(err) => write!(f, "could not start the teacher process: {}", err),
+            ProcessError::Io(err) => write!(f, "could not talk to the teacher process: {}", err),
+            ProcessError::Exited => write!(f, "the teacher process exited"),
+            ProcessError::Timeout(timeout) => write!(f, "the teacher process did not answer within {:?}", timeout),
+            ProcessError::Malformed { request, reply } => write!(f, "the teacher process answered {:?} to {:?}", reply, request),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+impl From<ProcessError> for TeacherError {
+    fn from(err: ProcessError) -> Self {
+        TeacherError::Other(err.to_string())
+    }
+}
+
+/// A teacher running as a child process, spoken to over its stdin and stdout one line at a
+/// time, so that systems written in any language can be learned. Symbols are written with
+/// `Display` and read back with `FromStr`, separated by spaces.
+///
+/// A membership query is `MQ a b a`, answered `1` or `0`. An equivalence query is `EQ`
+/// followed by the hypothesis with states numbered from 0 and ending in `END`:
+///
+/// ```text
+/// EQ
+/// STATES 3
+/// INITIAL 0
+/// ACCEPTING 0 2
+/// TRANSITION 0 a 1
+/// END
+/// ```
+///
+/// and answered `OK` or `CEX a b b` (`CEX` alone is the empty word). The child is killed when
+/// the teacher is dropped.
+pub struct ProcessTeacher<T> {
+    child: RefCell<Child>,
+    stdin: RefCell<ChildStdin>,
+    replies: Receiver<io::Result<String>>,
+    killed: Cell<bool>,
+    timeout: Option<Duration>,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Display + FromStr> ProcessTeacher<T> {
+    /// Starts `program` with `args`; its stderr is passed through. There is no timeout until
+    /// `with_timeout`.
+    pub fn spawn(program: impl AsRef<OsStr>, args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> Result<Self, ProcessError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(ProcessError::Spawn)?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // Replies are read on their own thread so that waiting for one can time out.
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(ProcessTeacher {
+            child: RefCell::new(child),
+            stdin: RefCell::new(stdin),
+            replies,
+            killed: Cell::new(false),
+            timeout: None,
+            _symbols: PhantomData,
+        })
+    }
+
+    /// How long to wait for each reply before killing the child.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn membership(&self, word: &[T]) -> Result<bool, ProcessError> {
+        let request = format!("MQ{}", symbols(word.iter().filter(|symbol| **symbol != T::default())));
+        match self.ask(&request)?.trim() {
+            "1" => Ok(true),
+            "0" => Ok(false),
+            reply => Err(ProcessError::Malformed { request, reply: reply.to_string() }),
+        }
+    }
+
+    pub fn equivalence(&self, automaton: &Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, ProcessError> {
+        let reply = self.ask(&serialize(automaton))?;
+        let reply = reply.trim();
+        if reply == "OK" {
+            return Ok(Ok(()));
+        }
+        let malformed = || ProcessError::Malformed { request: "EQ".to_string(), reply: reply.to_string() };
+        let mut tokens = reply.split_whitespace();
+        if tokens.next() != Some("CEX") {
+            return Err(malformed());
+        }
+        let counterexample = tokens.map(|token| token.parse().map_err(|_| malformed())).collect::<Result<Vec<T>, _>>()?;
+        Ok(Err(counterexample))
+    }
+
+    // Sends `request` and waits for the one-line reply.
+    fn ask(&self, request: &str) -> Result<String, ProcessError> {
+        // A killed child may live on in its own children, which keep its output open.
+        if self.killed.get() {
+            return Err(ProcessError::Exited);
+        }
+        let mut stdin = self.stdin.borrow_mut();
+        writeln!(stdin, "{}", request).and_then(|_| stdin.flush()).map_err(|err| match err.kind() {
+            io::ErrorKind::BrokenPipe => ProcessError::Exited,
+            _ => ProcessError::Io(err),
+        })?;
+        let reply = match self.timeout {
+            Some(timeout) => self.replies.recv_timeout(timeout).map_err(|err| match err {
+                RecvTimeoutError::Timeout => {
+                    let _ = self.child.borrow_mut().kill();
+                    self.killed.set(true);
+                    ProcessError::Timeout(timeout)
+                }
+                RecvTimeoutError::Disconnected => ProcessError::Exited,
+            })?,
+            None => self.replies.recv().map_err(|_| ProcessError::Exited)?,
+        };
+        reply.map_err(ProcessError::Io)
+    }
+}
+
+fn symbols<'a, T: Display + 'a>(word: impl Iterator<Item = &'a T>) -> String {
+    word.map(|symbol| format!(" {}", symbol)).collect()
+}
+
+// The `EQ` request for `automaton`, states numbered breadth-first from the initial one.
+fn serialize<T: Eq + Hash + Clone + Display>(automaton: &Automaton<Vec<T>, T>) -> String {
+    let (numbered, _) = automaton.with_opaque_ids();
+    let mut states: Vec<_> = numbered.get_states().values().collect();
+    states.sort_by_key(|state| state.get_state_id().index());
+
+    let mut request = format!("EQ\nSTATES {}\nINITIAL 0\nACCEPTING", states.len());
+    for state in states.iter().filter(|state| state.is_accepting()) {
+        request.push_str(&format!(" {}", state.get_state_id().index()));
+    }
+    for state in &states {
+        let mut transitions: Vec<(String, usize)> = state.transitions().map(|(symbol, target)| (symbol.to_string(), target.index())).collect();
+        transitions.sort();
+        for (symbol, target) in transitions {
+            request.push_str(&format!("\nTRANSITION {} {} {}", state.get_state_id().index(), symbol, target));
+        }
+    }
+    request.push_str("\nEND");
+    request
+}
+
+impl<T> Drop for ProcessTeacher<T> {
+    fn drop(&mut self) {
+        let child = self.child.get_mut();
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + Display + FromStr> Teacher<T> for ProcessTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        Ok(self.membership(states)?)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        Ok(self.equivalence(&automaton)?)
+    }
+}
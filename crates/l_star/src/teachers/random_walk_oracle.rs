@@ -0,0 +1,104 @@
+use crate::teacher::{EquivalenceAnswer, Membership, Teacher};
+use crate::alphabet::Alphabet;
+use crate::automaton::Automaton;
+use crate::random::SplitMix64;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/// An approximate equivalence oracle that walks the hypothesis instead of sampling words
+/// independently: from the initial state it keeps appending random symbols, checking every
+/// prefix walked against the membership source, and restarts with probability
+/// `reset_probability` per step or once `step_limit` symbols are reached. Walks share their
+/// prefixes, so states behind long mandatory prefixes are reached far more often than by
+/// `RandomWordOracle`. A hypothesis is accepted once `query_budget` prefixes agreed.
+pub struct RandomWalkOracle<T> {
+    membership: Membership<T>,
+    alphabet: Alphabet<T>,
+    step_limit: usize,
+    reset_probability: f64,
+    query_budget: usize,
+    rng: RefCell<SplitMix64>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> RandomWalkOracle<T> {
+    /// Answers membership queries with `membership`. Defaults to walks of at most 64 steps, a
+    /// reset probability of 0.05, 10000 queries per hypothesis, and seed 0.
+    pub fn from_fn(alphabet: Alphabet<T>, membership: impl Fn(&[T]) -> bool + 'static) -> Self {
+        RandomWalkOracle {
+            membership: Box::new(membership),
+            alphabet,
+            step_limit: 64,
+            reset_probability: 0.05,
+            query_budget: 10000,
+            rng: RefCell::new(SplitMix64::new(0)),
+        }
+    }
+
+    /// Answers membership queries with `teacher`, replacing its equivalence queries.
+    pub fn from_teacher(alphabet: Alphabet<T>, teacher: impl Teacher<T> + 'static) -> Self {
+        Self::from_fn(alphabet, move |word| teacher.membership_query(word))
+    }
+
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    pub fn with_reset_probability(mut self, reset_probability: f64) -> Self {
+        self.reset_probability = reset_probability;
+        self
+    }
+
+    pub fn with_query_budget(mut self, query_budget: usize) -> Self {
+        self.query_budget = query_budget;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = RefCell::new(SplitMix64::new(seed));
+        self
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> Teacher<T> for RandomWalkOracle<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        (self.membership)(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        if self.query_budget == 0 {
+            return Ok(());
+        }
+        if automaton.accepts(&[]) != self.membership_query(&[]) {
+            return Err(Vec::new());
+        }
+        if self.alphabet.is_empty() {
+            return Ok(());
+        }
+
+        let mut rng = self.rng.borrow_mut();
+        let mut word: Vec<T> = Vec::new();
+        // `None` once the walk has left the hypothesis through a missing transition.
+        let mut state = automaton.get_initial_state();
+        for _ in 1..self.query_budget {
+            if word.len() >= self.step_limit || rng.next_f64() < self.reset_probability {
+                word.clear();
+                state = automaton.get_initial_state();
+            }
+            let symbol = self.alphabet.symbol(rng.below(self.alphabet.len())).clone();
+            state = state
+                .and_then(|state| state.transition(&symbol))
+                .and_then(|target| automaton.get_state(target));
+            word.push(symbol);
+            let expected = state.is_some_and(|state| state.is_accepting());
+            if expected != self.membership_query(&word) {
+                return Err(word);
+            }
+        }
+        Ok(())
+    }
+}
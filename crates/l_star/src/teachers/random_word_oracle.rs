@@ -0,0 +1,157 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Membership, Teacher, TeacherError};
+use crate::alphabet::Alphabet;
+use crate::automaton::Automaton;
+use crate::random::SplitMix64;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/// How long the words sampled by `RandomWordOracle` are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LengthDistribution {
+    /// Every length from 0 to `max_len` equally likely.
+    Uniform { max_len: usize },
+    /// Length `k` with probability `(1 - p)^k p`, where `p = 1 / (mean + 1)`.
+    Geometric { mean: f64 },
+}
+
+/// An approximate equivalence oracle for black-box targets: each hypothesis is compared with the
+/// membership source on randomly sampled words, and the first word they disagree on is the
+/// counterexample, unless `with_counterexamples` asks for more. A hypothesis surviving every
+/// sample is accepted, so the result is only as good as the sample; `Metrics` can quantify it
+/// afterwards.
+pub struct RandomWordOracle<T> {
+    membership: Membership<T>,
+    alphabet: Alphabet<T>,
+    samples: usize,
+    lengths: LengthDistribution,
+    counterexamples: usize,
+    rng: RefCell<SplitMix64>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> RandomWordOracle<T> {
+    /// Answers membership queries with `membership`. Defaults to 1000 samples per hypothesis,
+    /// lengths uniform up to 16, one counterexample per hypothesis, and seed 0.
+    pub fn from_fn(alphabet: Alphabet<T>, membership: impl Fn(&[T]) -> bool + 'static) -> Self {
+        RandomWordOracle {
+            membership: Box::new(membership),
+            alphabet,
+            samples: 1000,
+            lengths: LengthDistribution::Uniform { max_len: 16 },
+            counterexamples: 1,
+            rng: RefCell::new(SplitMix64::new(0)),
+        }
+    }
+
+    /// Answers membership queries with `teacher`, replacing its equivalence queries.
+    pub fn from_teacher(alphabet: Alphabet<T>, teacher: impl Teacher<T> + 'static) -> Self {
+        Self::from_fn(alphabet, move |word| teacher.membership_query(word))
+    }
+
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_lengths(mut self, lengths: LengthDistribution) -> Self {
+        self.lengths = lengths;
+        self
+    }
+
+    /// Keeps sampling after the first counterexample and hands the learner up to `limit`
+    /// distinct ones at once, so that fewer hypotheses have to be built.
+    pub fn with_counterexamples(mut self, limit: usize) -> Self {
+        self.counterexamples = limit.max(1);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = RefCell::new(SplitMix64::new(seed));
+        self
+    }
+
+    fn sample(&self, rng: &mut SplitMix64) -> Vec<T> {
+        let len = match self.lengths {
+            LengthDistribution::Uniform { max_len } => rng.below(max_len + 1),
+            LengthDistribution::Geometric { mean } => {
+                let stop = 1.0 / (mean.max(0.0) + 1.0);
+                let mut len = 0;
+                while rng.next_f64() >= stop {
+                    len += 1;
+                }
+                len
+            }
+        };
+        if self.alphabet.is_empty() {
+            return Vec::new();
+        }
+        (0..len).map(|_| self.alphabet.symbol(rng.below(self.alphabet.len())).clone()).collect()
+    }
+
+    // Up to `limit` distinct sampled words the hypothesis gets wrong, in the order found.
+    fn find_counterexamples(&self, automaton: &Automaton<Vec<T>, T>, limit: usize) -> Vec<Vec<T>> {
+        let mut rng = self.rng.borrow_mut();
+        let mut found: Vec<Vec<T>> = Vec::new();
+        for _ in 0..self.samples {
+            let word = self.sample(&mut rng);
+            if automaton.accepts(&word) != self.membership_query(&word) && !found.contains(&word) {
+                found.push(word);
+                if found.len() == limit {
+                    break;
+                }
+            }
+        }
+        found
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> Teacher<T> for RandomWordOracle<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        (self.membership)(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        match self.find_counterexamples(&automaton, 1).pop() {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        let found = self.find_counterexamples(&automaton, self.counterexamples);
+        Ok(if found.is_empty() { Ok(()) } else { Err(found) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::DfaState;
+
+    fn contains_a(word: &[char]) -> bool {
+        word.contains(&'a')
+    }
+
+    // One state looping on both symbols, accepting everything or nothing.
+    fn constant(accepting: bool) -> Automaton<Vec<char>, char> {
+        let state = DfaState::new(Vec::new(), accepting);
+        let mut automaton = Automaton::new(state.clone());
+        automaton.add_transition(&state, &state, &'a');
+        automaton.add_transition(&state, &state, &'b');
+        automaton
+    }
+
+    #[test]
+    fn validate_hypothesis_batch_returns_every_counterexample() {
+        let oracle = RandomWordOracle::from_fn(Alphabet::new(['a', 'b']), contains_a).with_counterexamples(3);
+        let counterexamples = oracle.validate_hypothesis_batch(constant(false)).unwrap_err();
+        assert_eq!(counterexamples.len(), 3);
+        assert!(counterexamples.iter().all(|word| contains_a(word)));
+
+        let oracle = RandomWordOracle::from_fn(Alphabet::new(['a', 'b']), |_: &[char]| true).with_counterexamples(3);
+        assert_eq!(oracle.validate_hypothesis_batch(constant(true)), Ok(true));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::{Automaton, DfaState};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
+
+
+type Verdict<T> = Result<(), Vec<T>>;
+type Hypothesis<T> = Automaton<Vec<T>, T>;
+type EventSink<T> = Box<dyn FnMut(&TraceEvent<T>) -> Result<(), TeacherError>>;
+
+/// Everything a teacher was asked during a run, in order.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueryTrace<T> {
+    pub events: Vec<TraceEvent<T>>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraceEvent<T> {
+    Membership { word: Vec<T>, answer: bool },
+    Equivalence { hypothesis: HypothesisRecord<T>, verdict: Verdict<T> },
+}
+
+/// A hypothesis as plain lists, so that it can be serialized with the trace.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HypothesisRecord<T> {
+    pub initial: Vec<T>,
+    pub states: Vec<(Vec<T>, bool)>,
+    pub transitions: Vec<(Vec<T>, T, Vec<T>)>,
+}
+
+impl<T: Eq + Hash + Clone> HypothesisRecord<T> {
+    pub fn to_automaton(&self) -> Automaton<Vec<T>, T> {
+        let accepting: HashMap<&Vec<T>, bool> = self.states.iter().map(|(state_id, is_accepting)| (state_id, *is_accepting)).collect();
+        let state = |state_id: &Vec<T>| DfaState::new(state_id.clone(), accepting.get(state_id).copied().unwrap_or(false));
+        let mut automaton = Automaton::new(state(&self.initial));
+        for (state_id, _) in &self.states {
+            automaton.add_state(state(state_id));
+        }
+        for (from, label, to) in &self.transitions {
+            automaton.add_transition(&state(from), &state(to), label);
+        }
+        automaton
+    }
+}
+
+impl<T: Eq + Hash + Clone> From<&Automaton<Vec<T>, T>> for HypothesisRecord<T> {
+    fn from(automaton: &Automaton<Vec<T>, T>) -> Self {
+        let states = automaton.get_states();
+        HypothesisRecord {
+            initial: automaton.get_initial_state().map(|state| state.get_state_id().clone()).unwrap_or_default(),
+            states: states.iter().map(|(state_id, state)| (state_id.clone(), state.is_accepting())).collect(),
+            transitions: states.iter()
+                .flat_map(|(state_id, state)| state.transitions().map(move |(label, target)| (state_id.clone(), label.clone(), target.clone())))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> QueryTrace<T> {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Reads a query log written through `RecordingTeacher::with_log_writer`. A log cut short,
+    /// e.g. by a crash, reads up to its last complete line.
+    pub fn from_json_lines(log: &str) -> Result<Self, QueryLogError> {
+        let mut lines = log.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let header: LogHeader = match lines.next() {
+            Some((_, line)) => serde_json::from_str(line).map_err(|err| QueryLogError::Invalid { line: 1, reason: err.to_string() })?,
+            None => return Ok(QueryTrace { events: Vec::new() }),
+        };
+        if header.version != QUERY_LOG_VERSION {
+            return Err(QueryLogError::UnsupportedVersion(header.version));
+        }
+        let mut events = Vec::new();
+        for (index, line) in lines {
+            match serde_json::from_str(line) {
+                Ok(event) => events.push(event),
+                Err(err) if err.is_eof() => break,
+                Err(err) => return Err(QueryLogError::Invalid { line: index + 1, reason: err.to_string() }),
+            }
+        }
+        Ok(QueryTrace { events })
+    }
+}
+
+/*
+The query log is JSON lines. The first line is the header `{"version":2}`; every further line
+is one `TraceEvent` in serde's default representation, in the order the queries were answered:
+
+    {"Membership":{"word":["","a"],"answer":true}}
+    {"Equivalence":{"hypothesis":{"initial":[..],"states":[..],"transitions":[..]},"verdict":{"Err":["a","b"]}}}
+
+Words are written as the learner asked them, `T::default()` padding included, and a verdict is `{"Ok":null}`
+or `{"Err":counterexample}`. The version changes whenever an existing log could no
+longer be read.
+ */
+#[cfg(feature = "serde")]
+const QUERY_LOG_VERSION: u64 = 2;
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct LogHeader {
+    version: u64,
+}
+
+/// Why `QueryTrace::from_json_lines` could not read a log.
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum QueryLogError {
+    /// The log was written in a format this version does not read.
+    UnsupportedVersion(u64),
+    /// Line `line` (from 1) is not a header or event.
+    Invalid { line: usize, reason: String },
+}
+
+#[cfg(feature = "serde")]
+impl std::fmt::Display for QueryLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryLogError::UnsupportedVersion(version) => write!(f, "query log version {} is not supported (expected {})", version, QUERY_LOG_VERSION),
+            QueryLogError::Invalid { line, reason } => write!(f, "query log line {}: {}", line, reason),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for QueryLogError {}
+
+// Where `RecordingTeacher` appends its log; the header goes out with the first event.
+#[cfg(feature = "serde")]
+struct QueryLog {
+    writer: Box<dyn std::io::Write>,
+    started: bool,
+}
+
+#[cfg(feature = "serde")]
+impl QueryLog {
+    fn append<T: serde::Serialize>(&mut self, event: &TraceEvent<T>) -> Result<(), TeacherError> {
+        let failed = |err: &dyn std::fmt::Display| TeacherError::Other(format!("could not write the query log: {}", err));
+        let mut lines = String::new();
+        if !self.started {
+            lines.push_str(&serde_json::to_string(&LogHeader { version: QUERY_LOG_VERSION }).map_err(|err| failed(&err))?);
+            lines.push('\n');
+        }
+        lines.push_str(&serde_json::to_string(event).map_err(|err| failed(&err))?);
+        lines.push('\n');
+        self.writer.write_all(lines.as_bytes()).and_then(|_| self.writer.flush()).map_err(|err| failed(&err))?;
+        self.started = true;
+        Ok(())
+    }
+}
+
+
+/// Passes queries on to another teacher and records them with their answers. Clones share the
+/// teacher and the trace, so keep one to read the trace after handing the other to a `Learner`.
+pub struct RecordingTeacher<T> {
+    teacher: Rc<dyn Teacher<T>>,
+    trace: Rc<RefCell<QueryTrace<T>>>,
+    log: Rc<RefCell<Option<EventSink<T>>>>,
+}
+
+impl<T> Clone for RecordingTeacher<T> {
+    fn clone(&self) -> Self {
+        RecordingTeacher {
+            teacher: Rc::clone(&self.teacher),
+            trace: Rc::clone(&self.trace),
+            log: Rc::clone(&self.log),
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> RecordingTeacher<T> {
+    pub fn new(teacher: impl Teacher<T> + 'static) -> Self {
+        RecordingTeacher {
+            teacher: Rc::new(teacher),
+            trace: Rc::new(RefCell::new(QueryTrace { events: Vec::new() })),
+            log: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn trace(&self) -> QueryTrace<T> {
+        self.trace.borrow().clone()
+    }
+
+    // Records `event`, and logs it first so that a log never misses an event in the trace.
+    fn record(&self, event: TraceEvent<T>) -> Result<(), TeacherError> {
+        if let Some(log) = self.log.borrow_mut().as_mut() {
+            log(&event)?;
+        }
+        self.trace.borrow_mut().events.push(event);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Eq + Hash + Clone + Debug + Default + serde::Serialize> RecordingTeacher<T> {
+    /// Also appends every event to `writer` as it happens, as a versioned JSON lines log that
+    /// `QueryTrace::from_json_lines` reads back, so that a run that dies still leaves its
+    /// queries behind. Failing to write stops the learner.
+    pub fn with_log_writer(self, writer: impl std::io::Write + 'static) -> Self {
+        let mut log = QueryLog { writer: Box::new(writer), started: false };
+        *self.log.borrow_mut() = Some(Box::new(move |event| log.append(event)));
+        self
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> Teacher<T> for RecordingTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // Failed queries are not recorded, since there is no answer to replay.
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        let answer = self.teacher.try_membership_query(states)?;
+        self.record(TraceEvent::Membership { word: states.to_vec(), answer })?;
+        Ok(answer)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        let hypothesis = HypothesisRecord::from(&automaton);
+        let verdict = self.teacher.try_equivalence_query(automaton)?;
+        self.record(TraceEvent::Equivalence { hypothesis, verdict: verdict.clone() })?;
+        Ok(verdict)
+    }
+}
+
+
+/// Answers only from a recorded trace, for re-running the learner against a fixed session.
+/// A word or hypothesis the trace does not have, which means the learner now asks something it
+/// did not ask when the trace was recorded, fails the query.
+pub struct ReplayTeacher<T: Eq + Hash + Clone> {
+    answers: HashMap<Vec<T>, bool>,
+    verdicts: Vec<(Hypothesis<T>, Verdict<T>)>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> ReplayTeacher<T> {
+    pub fn new(trace: &QueryTrace<T>) -> Self {
+        let mut answers = HashMap::new();
+        let mut verdicts = Vec::new();
+        for event in &trace.events {
+            match event {
+                TraceEvent::Membership { word, answer } => {
+                    answers.insert(word.clone(), *answer);
+                }
+                TraceEvent::Equivalence { hypothesis, verdict } => verdicts.push((hypothesis.to_automaton(), verdict.clone())),
+            }
+        }
+        ReplayTeacher { answers, verdicts }
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> Teacher<T> for ReplayTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        match self.answers.get(states) {
+            Some(answer) => Ok(*answer),
+            None => Err(TeacherError::Other(format!("membership query {:?} is not in the trace", states))),
+        }
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        match self.verdicts.iter().find(|(hypothesis, _)| *hypothesis == automaton) {
+            Some((_, verdict)) => Ok(verdict.clone()),
+            None => Err(TeacherError::Other(format!("hypothesis with {} states is not in the trace", automaton.state_count()))),
+        }
+    }
+}
@@ -1,30 +1,736 @@
-use regex::Regex;
-use crate::teacher::Teacher;
-use crate::automaton::Automaton;
-use std::collections::HashSet;
+use regex::{Regex, RegexBuilder};
+use regex_automata::dfa::{dense, Automaton as _, StartKind};
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::Anchored;
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::alphabet::Alphabet;
+use crate::automaton::{Automaton, DenseAutomaton, DfaState};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
 pub struct RegexTeacher {
     regex: Regex,
+    // The regex's pattern without the builder's case-insensitivity, which is what the
+    // characters of the pattern are read from; matching still ignores case.
+    cased_pattern: String,
+    case_insensitive: bool,
+    unicode: bool,
+    verify_exact: Option<usize>,
+    bounded_equivalence: Option<usize>,
+    target: Option<DenseAutomaton<String>>,
+    separator: String,
+    dfa_size_limit: Option<usize>,
+}
+
+/// How a `RegexTeacher` matches words against its pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The whole word has to match, as if the pattern were wrapped in `^(?:...)$`.
+    Whole,
+    /// A word is accepted when any part of it matches, like `Regex::is_match`.
+    Substring,
+}
+
+/// Why `RegexTeacher::new` or `RegexTeacherBuilder::build` refused a pattern.
+#[derive(Debug)]
+pub enum RegexTeacherError {
+    /// The pattern does not compile, or its compiled form goes over a size limit.
+    Invalid { pattern: String, error: regex::Error },
+    /// The pattern is not anchored with `^` and `$`, so it would be matched against any part
+    /// of a word and accept nearly everything. Use `RegexTeacher::new_anchored`, or
+    /// `MatchMode::Substring` if that is intended.
+    Unanchored { pattern: String },
+    /// The declared alphabet makes joined words ambiguous; see `RegexTeacher::check_alphabet`.
+    AmbiguousAlphabet(AmbiguousSymbol),
+    /// Matching is case-insensitive and the declared alphabet has two symbols that only differ
+    /// in case, which would always get the same answers.
+    CaseVariants { first: String, second: String },
+}
+
+impl fmt::Display for RegexTeacherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexTeacherError::Invalid { pattern, error } => write!(f, "invalid regex pattern {:?}: {}", pattern, error),
+            RegexTeacherError::Unanchored { pattern } => write!(f, "regex pattern {:?} is not anchored with ^ and $, so it matches anywhere in a word", pattern),
+            RegexTeacherError::AmbiguousAlphabet(err) => write!(f, "{}", err),
+            RegexTeacherError::CaseVariants { first, second } => write!(f, "symbols {:?} and {:?} only differ in case, which case-insensitive matching ignores", first, second),
+        }
+    }
+}
+
+impl std::error::Error for RegexTeacherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RegexTeacherError::Invalid { error, .. } => Some(error),
+            RegexTeacherError::AmbiguousAlphabet(err) => Some(err),
+            RegexTeacherError::Unanchored { .. } | RegexTeacherError::CaseVariants { .. } => None,
+        }
+    }
+}
+
+/// A symbol that makes joined words ambiguous: with `"ab"`, `"a"` and `"b"`, the words
+/// `["ab"]` and `["a", "b"]` both read `"ab"`. See `RegexTeacher::with_separator`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmbiguousSymbol(pub String);
+
+impl fmt::Display for AmbiguousSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "symbol {:?} makes joined words ambiguous; use a separator that occurs in no symbol", self.0)
+    }
+}
+
+impl std::error::Error for AmbiguousSymbol {}
+
+/// Why `RegexTeacher::to_automaton` cannot give an exact automaton for the pattern.
+#[derive(Debug)]
+pub enum ExactEquivalenceError {
+    /// The pattern can match `symbol`, which is no character of any alphabet symbol.
+    OutsideAlphabet { symbol: char },
+    /// The pattern uses something the DFA compiler does not support, e.g. a Unicode `\b` next to
+    /// a non-ASCII symbol.
+    Unsupported(String),
+}
+
+impl fmt::Display for ExactEquivalenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExactEquivalenceError::OutsideAlphabet { symbol } => write!(f, "pattern can match {:?}, which is outside the alphabet", symbol),
+            ExactEquivalenceError::Unsupported(reason) => write!(f, "pattern cannot be compiled to a DFA: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ExactEquivalenceError {}
+
+/// Why `RegexTeacher::inferred_alphabet` cannot list the characters of a pattern.
+#[derive(Debug)]
+pub enum AlphabetInferenceError {
+    /// `class`, shortened if long, can match `size` characters, more than can sensibly be symbols, e.g. `.` or
+    /// `\w`.
+    LargeClass { class: String, size: usize },
+    Unsupported(String),
+}
+
+impl fmt::Display for AlphabetInferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphabetInferenceError::LargeClass { class, size } => write!(f, "class {:?} matches {} characters, more than the {} an inferred alphabet may have per class; list the alphabet instead", class, size, MAX_INFERRED_CLASS),
+            AlphabetInferenceError::Unsupported(reason) => write!(f, "pattern cannot be parsed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AlphabetInferenceError {}
+
+// The largest character class `inferred_alphabet` expands, enough for `[A-Za-z0-9]`.
+const MAX_INFERRED_CLASS: usize = 128;
+
+/// Why a learned hypothesis failed `RegexTeacher::verify_exact`.
+#[derive(Debug)]
+pub enum VerificationError {
+    /// The expression produced by `Automaton::to_regex` does not compile.
+    InvalidRegex(regex::Error),
+    /// The hypothesis disagrees with the teacher's pattern on `word`.
+    HypothesisMismatch { word: Vec<String> },
+    /// The hypothesis is right on `word` but the expression derived from it is not.
+    RoundTripMismatch { word: Vec<String>, regex: String },
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationError::InvalidRegex(err) => write!(f, "hypothesis regex does not compile: {}", err),
+            VerificationError::HypothesisMismatch { word } => write!(f, "hypothesis misclassifies {:?}", word.join("")),
+            VerificationError::RoundTripMismatch { word, regex } => write!(f, "regex {:?} derived from the hypothesis misclassifies {:?}", regex, word.join("")),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// Configures a `RegexTeacher` beyond its pattern, passing options on to
+/// `regex::RegexBuilder`; see `RegexTeacher::builder`. Case-insensitivity and Unicode are
+/// written into the pattern as inline flags, so that `to_automaton` sees them too. Under
+/// case-insensitivity, `to_automaton` accepts an alphabet with one case of each letter and
+/// `inferred_alphabet` lists the letters as the pattern spells them.
+pub struct RegexTeacherBuilder {
+    pattern: String,
+    mode: Option<MatchMode>,
+    case_insensitive: bool,
+    unicode: bool,
+    size_limit: Option<usize>,
+    dfa_size_limit: Option<usize>,
+    separator: String,
+    alphabet: Option<Alphabet<String>>,
+}
+
+impl RegexTeacherBuilder {
+    /// Without it, the pattern has to be anchored, as for `RegexTeacher::new`.
+    pub fn match_mode(mut self, mode: MatchMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.case_insensitive = yes;
+        self
+    }
+
+    /// With Unicode off, classes such as `\w` and case-insensitivity only cover ASCII. The
+    /// pattern must still only match valid UTF-8, since words are matched as strings.
+    pub fn unicode(mut self, yes: bool) -> Self {
+        self.unicode = yes;
+        self
+    }
+
+    /// The most memory the compiled pattern may take, as `RegexBuilder::size_limit`.
+    pub fn size_limit(mut self, bytes: usize) -> Self {
+        self.size_limit = Some(bytes);
+        self
+    }
+
+    /// The most memory the lazy DFA used for matching may take, as
+    /// `RegexBuilder::dfa_size_limit`. It also limits the full DFA that
+    /// `RegexTeacher::to_automaton` builds, which then fails instead of growing without bound.
+    pub fn dfa_size_limit(mut self, bytes: usize) -> Self {
+        self.dfa_size_limit = Some(bytes);
+        self
+    }
+
+    /// As `RegexTeacher::with_separator`.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Declares the alphabet the teacher will be asked about, so that `build` can check it:
+    /// joined words must not be ambiguous, and under case-insensitive matching no two symbols
+    /// may only differ in case.
+    pub fn alphabet(mut self, alphabet: impl Into<Alphabet<String>>) -> Self {
+        self.alphabet = Some(alphabet.into());
+        self
+    }
+
+    pub fn build(self) -> Result<RegexTeacher, RegexTeacherError> {
+        let pattern = &self.pattern;
+        let anchored = regex_syntax::parse(pattern)
+            .map(|hir| hir.properties().look_set_prefix().contains(Look::Start) && hir.properties().look_set_suffix().contains(Look::End));
+        if self.mode.is_none() && anchored.is_ok_and(|anchored| !anchored) {
+            return Err(RegexTeacherError::Unanchored { pattern: pattern.clone() });
+        }
+
+        let with_flags = |case_insensitive: bool| {
+            let flags = match (case_insensitive, self.unicode) {
+                (false, true) => "",
+                (true, true) => "(?i)",
+                (false, false) => "(?-u)",
+                (true, false) => "(?i-u)",
+            };
+            match self.mode.unwrap_or(MatchMode::Substring) {
+                MatchMode::Whole => format!("^(?:{}{})$", flags, pattern),
+                MatchMode::Substring => format!("{}{}", flags, pattern),
+            }
+        };
+        let full = with_flags(self.case_insensitive);
+        let mut builder = RegexBuilder::new(&full);
+        if let Some(bytes) = self.size_limit {
+            builder.size_limit(bytes);
+        }
+        if let Some(bytes) = self.dfa_size_limit {
+            builder.dfa_size_limit(bytes);
+        }
+        let regex = builder.build().map_err(|error| RegexTeacherError::Invalid { pattern: pattern.clone(), error })?;
+
+        let teacher = RegexTeacher {
+            regex,
+            cased_pattern: with_flags(false),
+            case_insensitive: self.case_insensitive,
+            unicode: self.unicode,
+            verify_exact: None,
+            bounded_equivalence: None,
+            target: None,
+            separator: self.separator,
+            dfa_size_limit: self.dfa_size_limit,
+        };
+        if let Some(alphabet) = &self.alphabet {
+            teacher.check_alphabet(alphabet).map_err(RegexTeacherError::AmbiguousAlphabet)?;
+            if self.case_insensitive {
+                check_case_variants(alphabet, self.unicode)?;
+            }
+        }
+        Ok(teacher)
+    }
+}
+
+// Fails on the first two symbols that are equal once lowercased, only in ASCII without Unicode.
+fn check_case_variants(alphabet: &Alphabet<String>, unicode: bool) -> Result<(), RegexTeacherError> {
+    let mut folded: HashMap<String, &String> = HashMap::new();
+    for symbol in alphabet {
+        let key = if unicode { symbol.to_lowercase() } else { symbol.to_ascii_lowercase() };
+        if let Some(first) = folded.insert(key, symbol) {
+            return Err(RegexTeacherError::CaseVariants { first: first.clone(), second: symbol.clone() });
+        }
+    }
+    Ok(())
 }
 
 impl RegexTeacher {
-    pub fn new(regex: String) -> Self {
+    /// Fails unless the pattern starts with `^` and ends with `$`; see `new_anchored` and
+    /// `with_match_mode` for patterns that do not.
+    pub fn new(pattern: &str) -> Result<Self, RegexTeacherError> {
+        Self::builder(pattern).build()
+    }
+
+    /// Matches whole words against `pattern`, anchored or not.
+    pub fn new_anchored(pattern: &str) -> Result<Self, RegexTeacherError> {
+        Self::with_match_mode(pattern, MatchMode::Whole)
+    }
+
+    pub fn with_match_mode(pattern: &str, mode: MatchMode) -> Result<Self, RegexTeacherError> {
+        Self::builder(pattern).match_mode(mode).build()
+    }
+
+    /// E.g. `RegexTeacher::builder("^[a-z]+$").case_insensitive(true).alphabet(alphabet).build()`.
+    pub fn builder(pattern: &str) -> RegexTeacherBuilder {
+        RegexTeacherBuilder {
+            pattern: pattern.to_string(),
+            mode: None,
+            case_insensitive: false,
+            unicode: true,
+            size_limit: None,
+            dfa_size_limit: None,
+            separator: String::new(),
+            alphabet: None,
+        }
+    }
+
+    /// Joins the symbols of a word with `separator` before matching, instead of directly, so
+    /// that multi-character symbols stay apart: with `" "`, `["ab"]` reads `"ab"` and
+    /// `["a", "b"]` reads `"a b"`. The pattern has to expect the separator.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Fails if two different words over `alphabet` read the same once joined, which would
+    /// give them the same answer. Without a separator that takes symbols like `"ab"` next to
+    /// `"a"` and `"b"`, while tokens like `"GET"` and `"POST"` are fine; with one, a symbol
+    /// containing the separator.
+    pub fn check_alphabet(&self, alphabet: &Alphabet<String>) -> Result<(), AmbiguousSymbol> {
+        let ambiguous = if self.separator.is_empty() {
+            first_ambiguous_symbol(alphabet.symbols())
+        } else {
+            alphabet.iter().find(|symbol| symbol.contains(self.separator.as_str()))
+        };
+        match ambiguous {
+            Some(symbol) => Err(AmbiguousSymbol(symbol.clone())),
+            None => Ok(()),
+        }
+    }
+
+    // The text a word is matched as. Empty symbols are the learner's padding for the empty
+    // word and are left out, so that they do not add separators.
+    fn join(&self, word: &[String]) -> String {
+        word.iter().filter(|symbol| !symbol.is_empty()).map(String::as_str).collect::<Vec<&str>>().join(&self.separator)
+    }
+
+    /// Answers equivalence queries exactly, by comparing each hypothesis with the pattern's
+    /// language over `alphabet` (see `to_automaton`) and returning a shortest word on which
+    /// they differ. Only patterns restricted to the characters of `alphabet` are supported.
+    pub fn with_exact_equivalence(mut self, alphabet: &Alphabet<String>) -> Result<Self, ExactEquivalenceError> {
+        self.target = Some(DenseAutomaton::from(&self.to_automaton(alphabet)?));
+        Ok(self)
+    }
+
+    /*
+    The pattern is compiled to an unanchored regex-automata DFA, which is then run over the
+    bytes of every alphabet symbol from each reachable DFA state. `is_match` only asks whether
+    some match exists, so once a match state is seen the word is accepted whatever follows,
+    which is one extra absorbing accepting state. The DFA reports matches one byte late, so a
+    state accepts when its end-of-input transition leads to a match state.
+     */
+    /// The language of the pattern, under `Regex::is_match` with symbols joined by the
+    /// separator, as an automaton over `alphabet`. Fails if some character the pattern can
+    /// match occurs in no symbol of `alphabet` nor the separator, in either case when matching
+    /// ignores case, since the result would then silently leave part of the pattern out.
+    pub fn to_automaton(&self, alphabet: &Alphabet<String>) -> Result<Automaton<usize, String>, ExactEquivalenceError> {
+        let fold = |c: char| if self.case_insensitive { fold_case(c, self.unicode) } else { c };
+        let characters: HashSet<char> = alphabet.iter().flat_map(|symbol| symbol.chars()).chain(self.separator.chars()).map(fold).collect();
+        let hir = regex_syntax::parse(&self.cased_pattern).map_err(|err| ExactEquivalenceError::Unsupported(err.to_string()))?;
+        check_characters(&hir, &|c| characters.contains(&fold(c)))?;
+
+        let unsupported = |err: &dyn std::error::Error| ExactEquivalenceError::Unsupported(err.to_string());
+        let dfa = dense::Builder::new()
+            .configure(dense::Config::new().start_kind(StartKind::Unanchored).unicode_word_boundary(true).dfa_size_limit(self.dfa_size_limit).determinize_size_limit(self.dfa_size_limit))
+            .build(self.regex.as_str())
+            .map_err(|err| unsupported(&err))?;
+        let start = dfa.start_state(&start::Config::new().anchored(Anchored::No)).map_err(|err| unsupported(&err))?;
+
+        // State 0 is the DFA's start state and state 1 "a match was already seen". Other states
+        // are DFA states paired with whether a symbol was read, after which the next symbol
+        // is preceded by the separator.
+        const MATCHED: usize = 1;
+        let mut ids: HashMap<(StateID, bool), usize> = HashMap::from([((start, false), 0)]);
+        let mut pending = vec![(start, false)];
+        let mut edges: Vec<(usize, String, usize)> = Vec::new();
+        let mut accepting: Vec<usize> = vec![MATCHED];
+
+        while let Some((state, started)) = pending.pop() {
+            let id = ids[&(state, started)];
+            if dfa.is_match_state(dfa.next_eoi_state(state)) {
+                accepting.push(id);
+            }
+            for symbol in alphabet {
+                let mut next = state;
+                let separator = if started { self.separator.as_bytes() } else { &[] };
+                for byte in separator.iter().chain(symbol.as_bytes()).copied() {
+                    next = dfa.next_state(next, byte);
+                    if dfa.is_match_state(next) || dfa.is_dead_state(next) {
+                        break;
+                    }
+                }
+                if dfa.is_quit_state(next) {
+                    return Err(ExactEquivalenceError::Unsupported(format!("the DFA gives up on {:?}", symbol)));
+                }
+                if dfa.is_match_state(next) {
+                    edges.push((id, symbol.clone(), MATCHED));
+                } else if !dfa.is_dead_state(next) {
+                    let next_id = ids.len() + 1;
+                    let target = *ids.entry((next, true)).or_insert_with(|| {
+                        pending.push((next, true));
+                        next_id
+                    });
+                    edges.push((id, symbol.clone(), target));
+                }
+            }
+        }
+
+        let state = |id: usize| DfaState::new(id, accepting.contains(&id));
+        let mut automaton = Automaton::new(state(0));
+        automaton.add_state(state(MATCHED));
+        for symbol in alphabet {
+            automaton.add_transition(&state(MATCHED), &state(MATCHED), symbol);
+        }
+        for (from, symbol, to) in &edges {
+            automaton.add_transition(&state(*from), &state(*to), symbol);
+        }
+        Ok(automaton)
+    }
 
-        RegexTeacher { 
-            regex: Regex::new(&regex).expect("Invalid regex pattern"),
+    /// Self-check for CI-style runs: every hypothesis the teacher would accept is first turned
+    /// back into a regex with `Automaton::to_regex` and compared with the pattern on all words
+    /// over the hypothesis' alphabet of length up to `max_len`. A derived regex that does not
+    /// compile or disagrees with the pattern fails the query with a `TeacherError`.
+    pub fn with_exact_verification(mut self, max_len: usize) -> Self {
+        self.verify_exact = Some(max_len);
+        self
+    }
+
+    pub fn verify_exact(&self, automaton: &Automaton<Vec<String>, String>, max_len: usize) -> Result<(), VerificationError> {
+        let derived = automaton.to_regex();
+        let round_trip = Regex::new(&format!("^(?:{})$", derived)).map_err(VerificationError::InvalidRegex)?;
+
+        for word in words_breadth_first(automaton, max_len) {
+            let expected = self.regex.is_match(&self.join(&word));
+            if automaton.accepts(&word) != expected {
+                return Err(VerificationError::HypothesisMismatch { word });
+            }
+            // The derived expression knows nothing of the separator, so it reads the word joined
+            // directly.
+            if self.separator.is_empty() && round_trip.is_match(&word.join("")) != expected {
+                return Err(VerificationError::RoundTripMismatch { word, regex: derived });
+            }
         }
+        Ok(())
+    }
+
+    /// The characters the pattern can match, each as a symbol, leaving out those of the
+    /// separator; for `Learner::new`, so that the alphabet cannot fall out of step with the
+    /// pattern. Fails on a class too large to list, such as `.` or `\w`.
+    pub fn inferred_alphabet(&self) -> Result<HashSet<String>, AlphabetInferenceError> {
+        let hir = regex_syntax::parse(&self.cased_pattern).map_err(|err| AlphabetInferenceError::Unsupported(err.to_string()))?;
+        let mut characters = HashSet::new();
+        collect_characters(&hir, &mut characters)?;
+        Ok(characters.into_iter()
+            .filter(|c| !self.separator.contains(*c))
+            .map(String::from)
+            .collect())
+    }
+
+    /// Answers equivalence queries by checking the hypothesis against the pattern on every
+    /// word over its alphabet of length up to `max_len`, shortest first, so the counterexample
+    /// returned is a shortest one. Cheaper than `with_exact_equivalence`, but a hypothesis
+    /// that is only wrong on longer words is accepted.
+    pub fn with_bounded_equivalence(mut self, max_len: usize) -> Self {
+        self.bounded_equivalence = Some(max_len);
+        self
     }
 }
 
 
 impl Teacher<String> for RegexTeacher {
 
-    fn membership_query(&self, states: Vec<String>) -> bool {
-        let input = states.join("");
-        self.regex.is_match(&input)
+    fn membership_query(&self, states: &[String]) -> bool {
+        self.regex.is_match(&self.join(states))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<String>, String>) -> EquivalenceAnswer<String> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // A failed exact verification that is not a plain counterexample is a `TeacherError`, so
+    // that the learner stops with a `LearnError` carrying the hypothesis.
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<String>, String>) -> Result<EquivalenceAnswer<String>, TeacherError> {
+        if let Some(target) = &self.target
+            && let Some(counterexample) = target.distinguishing_word(&DenseAutomaton::from(&automaton)) {
+                return Ok(Err(counterexample));
+            }
+        if let Some(max_len) = self.bounded_equivalence
+            && let Some(word) = words_breadth_first(&automaton, max_len).find(|word| automaton.accepts(word) != self.membership_query(word)) {
+                return Ok(Err(word));
+            }
+        if let Some(max_len) = self.verify_exact {
+            match self.verify_exact(&automaton, max_len) {
+                Ok(()) => {}
+                // A word the hypothesis gets wrong is a genuine counterexample.
+                Err(VerificationError::HypothesisMismatch { word }) => return Ok(Err(word)),
+                Err(err) => return Err(TeacherError::Other(format!("exact verification failed: {}", err))),
+            }
+        }
+        Ok(Ok(()))
+    }
+}
+
+
+// All words over the labels of `automaton` of length up to `max_len`, by length and then in
+// label order, so that the same hypothesis always yields the same mismatch.
+fn words_breadth_first(automaton: &Automaton<Vec<String>, String>, max_len: usize) -> impl Iterator<Item = Vec<String>> {
+    let alphabet: Alphabet<String> = automaton.get_states().values()
+        .flat_map(|state| state.transitions().map(|(label, _)| label.clone()))
+        .collect::<HashSet<String>>()
+        .into();
+
+    (0..=max_len).scan(Vec::new(), move |layer: &mut Vec<Vec<String>>, len| {
+        *layer = if len == 0 {
+            vec![Vec::new()]
+        } else {
+            layer.iter()
+                .flat_map(|word| alphabet.iter().map(move |a| {
+                    let mut next = word.clone();
+                    next.push(a.clone());
+                    next
+                }))
+                .collect()
+        };
+        Some(layer.clone())
+    })
+    .flatten()
+}
+
+/*
+Sardinas and Patterson's test: where one symbol is a proper prefix of another, the rest of the
+longer one dangles. A dangling suffix is extended by each symbol it is a prefix of and
+shortened by each symbol that is a prefix of it, and two different words read the same exactly
+when some dangling suffix is a symbol itself. Every dangling suffix is a suffix of a symbol, so
+the search ends. The symbol reported is the longer one of the pair the ambiguity starts from.
+ */
+fn first_ambiguous_symbol(symbols: &[String]) -> Option<&String> {
+    let symbols: Vec<&String> = symbols.iter().filter(|symbol| !symbol.is_empty()).collect();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut pending: VecDeque<(&str, &String)> = VecDeque::new();
+    for shorter in &symbols {
+        for longer in &symbols {
+            if shorter != longer
+                && let Some(rest) = longer.strip_prefix(shorter.as_str())
+                && seen.insert(rest) {
+                    pending.push_back((rest, longer));
+                }
+        }
     }
 
-    fn validate_hypothesis(&self, automaton: Automaton<Vec<String>, String>) -> Result<bool, HashSet<Vec<String>>> {
-        Ok(true)
+    while let Some((dangling, origin)) = pending.pop_front() {
+        for symbol in &symbols {
+            if symbol.as_str() == dangling {
+                return Some(origin);
+            }
+            let next = symbol.strip_prefix(dangling).or_else(|| dangling.strip_prefix(symbol.as_str()));
+            if let Some(next) = next
+                && seen.insert(next) {
+                    pending.push_back((next, origin));
+                }
+        }
     }
-}
\ No newline at end of file
+    None
+}
+
+// Adds every character the pattern can match to `characters`.
+fn collect_characters(hir: &Hir, characters: &mut HashSet<char>) -> Result<(), AlphabetInferenceError> {
+    let mut class = |chars: Vec<char>| {
+        if chars.len() > MAX_INFERRED_CLASS {
+            return Err(AlphabetInferenceError::LargeClass { class: shortened(hir), size: chars.len() });
+        }
+        characters.extend(chars);
+        Ok(())
+    };
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Ok(()),
+        HirKind::Literal(literal) => class(String::from_utf8_lossy(&literal.0).chars().collect()),
+        // Ranges are summed before being expanded, so that `.` is not listed in full.
+        HirKind::Class(Class::Unicode(class_ranges)) => {
+            let size: usize = class_ranges.iter().map(|range| range.end() as usize - range.start() as usize + 1).sum();
+            if size > MAX_INFERRED_CLASS {
+                return Err(AlphabetInferenceError::LargeClass { class: shortened(hir), size });
+            }
+            class(class_ranges.iter().flat_map(|range| range.start()..=range.end()).collect())
+        }
+        HirKind::Class(Class::Bytes(class_ranges)) => class(class_ranges.iter().flat_map(|range| range.start()..=range.end()).map(char::from).collect()),
+        HirKind::Repetition(repetition) => collect_characters(&repetition.sub, characters),
+        HirKind::Capture(capture) => collect_characters(&capture.sub, characters),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().try_for_each(|hir| collect_characters(hir, characters)),
+    }
+}
+
+// The pattern text of `hir`, cut short: a class like `\w` expands to thousands of ranges.
+fn shortened(hir: &Hir) -> String {
+    let text = hir.to_string();
+    match text.char_indices().nth(32) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+// Fails on the first character the pattern can match that is not `known`.
+fn check_characters(hir: &Hir, known: &dyn Fn(char) -> bool) -> Result<(), ExactEquivalenceError> {
+    let check = |c: char| if known(c) { Ok(()) } else { Err(ExactEquivalenceError::OutsideAlphabet { symbol: c }) };
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Ok(()),
+        HirKind::Literal(literal) => String::from_utf8_lossy(&literal.0).chars().try_for_each(check),
+        HirKind::Class(Class::Unicode(class)) => class.iter().try_for_each(|range| (range.start()..=range.end()).try_for_each(check)),
+        HirKind::Class(Class::Bytes(class)) => class.iter().try_for_each(|range| (range.start()..=range.end()).try_for_each(|byte| check(byte as char))),
+        HirKind::Repetition(repetition) => check_characters(&repetition.sub, known),
+        HirKind::Capture(capture) => check_characters(&capture.sub, known),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().try_for_each(|hir| check_characters(hir, known)),
+    }
+}
+
+// One character for all the cases of `c`, such as `a` for `A` and `s` for `ſ`; only ASCII
+// letters are folded without Unicode, as the regex does.
+fn fold_case(c: char, unicode: bool) -> char {
+    if !unicode {
+        return c.to_ascii_lowercase();
+    }
+    let mut upper = c.to_uppercase();
+    let upper = match (upper.next(), upper.next()) {
+        (Some(upper), None) => upper,
+        _ => c,
+    };
+    let mut lower = upper.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) => lower,
+        _ => upper,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learner::Learner;
+
+    fn alphabet(symbols: &[&str]) -> Alphabet<String> {
+        Alphabet::new(symbols.iter().map(|symbol| symbol.to_string()))
+    }
+
+    fn word(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|symbol| symbol.to_string()).collect()
+    }
+
+    #[test]
+    fn joining_multi_character_symbols_is_refused_without_a_separator() {
+        let symbols = alphabet(&["ab", "a", "b"]);
+        let teacher = RegexTeacher::new("^ab$").unwrap();
+        assert_eq!(teacher.check_alphabet(&symbols), Err(AmbiguousSymbol("ab".to_string())));
+
+        let built = RegexTeacher::builder("^ab$").alphabet(symbols).build();
+        assert!(matches!(built, Err(RegexTeacherError::AmbiguousAlphabet(AmbiguousSymbol(symbol))) if symbol == "ab"));
+    }
+
+    #[test]
+    fn a_separator_tells_the_words_apart() {
+        let symbols = alphabet(&["ab", "a", "b"]);
+        let teacher = RegexTeacher::builder("^ab$").separator(" ").alphabet(symbols).build().unwrap();
+        assert!(teacher.membership_query(&word(&["ab"])));
+        assert!(!teacher.membership_query(&word(&["a", "b"])));
+        // The learner's padding adds no separator.
+        assert!(teacher.membership_query(&word(&["", "ab"])));
+    }
+
+    fn learn(teacher: RegexTeacher) -> Automaton<Vec<String>, String> {
+        let symbols = alphabet(&["a", "b"]);
+        let teacher = teacher.with_exact_equivalence(&symbols).unwrap();
+        Learner::new(symbols, Box::new(teacher)).learn().unwrap()
+    }
+
+    #[test]
+    fn anchoring_changes_what_is_learned() {
+        assert!(matches!(RegexTeacher::new("(ab)*"), Err(RegexTeacherError::Unanchored { .. })));
+
+        let anchored = learn(RegexTeacher::new_anchored("(ab)*").unwrap());
+        assert_eq!(anchored.state_count(), 3);
+        assert!(anchored.accepts(&word(&["a", "b", "a", "b"])));
+        assert!(!anchored.accepts(&word(&["b", "a"])));
+
+        // Every word has an empty match somewhere.
+        let substring = learn(RegexTeacher::with_match_mode("(ab)*", MatchMode::Substring).unwrap());
+        assert_eq!(substring.state_count(), 1);
+        assert!(substring.accepts(&word(&["b", "a"])));
+    }
+
+    #[test]
+    fn a_symbol_may_be_a_prefix_of_another() {
+        // `"a"` is a prefix of `"ab"`, but without a symbol `"b"` no joined text reads two ways.
+        let teacher = RegexTeacher::new_anchored("(a|ab)*").unwrap();
+        assert_eq!(teacher.check_alphabet(&alphabet(&["a", "ab"])), Ok(()));
+
+        // With `"b"` as well, a separator keeps the tokens apart while learning.
+        let symbols = alphabet(&["a", "ab", "b"]);
+        let teacher = RegexTeacher::builder("ab( ab)*").match_mode(MatchMode::Whole).separator(" ").alphabet(symbols.clone()).build().unwrap();
+        let teacher = teacher.with_exact_equivalence(&symbols).unwrap();
+        let learned = Learner::new(symbols, Box::new(teacher)).learn().unwrap();
+        assert!(learned.accepts(&word(&["ab", "ab"])));
+        assert!(!learned.accepts(&word(&["a", "b", "ab"])));
+        assert!(!learned.accepts(&word(&["ab", "a", "b"])));
+    }
+
+    #[test]
+    fn case_insensitive_patterns_work_over_one_case() {
+        let case_insensitive = |pattern: &str| RegexTeacher::builder(pattern).case_insensitive(true).alphabet(alphabet(&["a", "b"])).build().unwrap();
+        let learned = learn(case_insensitive("^(ab)*$"));
+        assert_eq!(learned.state_count(), 3);
+        assert!(learned.accepts(&word(&["a", "b"])));
+
+        // The pattern may spell its letters in the other case.
+        let learned = learn(case_insensitive("^(AB)*$"));
+        assert!(learned.accepts(&word(&["a", "b", "a", "b"])));
+        assert!(!learned.accepts(&word(&["a"])));
+
+        // A letter in no case of the alphabet is still refused.
+        let teacher = case_insensitive("^(ab)*c$");
+        assert!(matches!(teacher.to_automaton(&alphabet(&["a", "b"])), Err(ExactEquivalenceError::OutsideAlphabet { symbol: 'c' })));
+    }
+
+    #[test]
+    fn the_inferred_alphabet_of_a_case_insensitive_pattern_builds() {
+        let teacher = RegexTeacher::builder("^(aB)*$").case_insensitive(true).build().unwrap();
+        let inferred = teacher.inferred_alphabet().unwrap();
+        assert_eq!(inferred, HashSet::from(["a".to_string(), "B".to_string()]));
+
+        let symbols: Alphabet<String> = inferred.into();
+        let teacher = RegexTeacher::builder("^(aB)*$").case_insensitive(true).alphabet(symbols.clone()).build().unwrap();
+        let teacher = teacher.with_exact_equivalence(&symbols).unwrap();
+        let learned = Learner::new(symbols, Box::new(teacher)).learn().unwrap();
+        assert!(learned.accepts(&word(&["a", "B"])));
+        assert!(!learned.accepts(&word(&["B", "a"])));
+    }
+}
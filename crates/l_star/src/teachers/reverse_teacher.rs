@@ -0,0 +1,62 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+
+/// Teaches the reversal of `Inner`'s language: membership queries are reversed before they
+/// are passed on, and a hypothesis is reversed with `Automaton::reverse` and checked by `Inner`,
+/// its counterexamples reversed back. Learning through it and reversing the result gives
+/// `Inner`'s language again.
+pub struct ReverseTeacher<T, Inner> {
+    inner: Inner,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> ReverseTeacher<T, Inner> {
+    pub fn new(inner: Inner) -> Self {
+        ReverseTeacher { inner, _symbols: PhantomData }
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> Teacher<T> for ReverseTeacher<T, Inner> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        // Padding is dropped first, so that it does not end up at the end of the word.
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().rev().collect();
+        self.inner.try_membership_query(&word)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        let words: Vec<Vec<T>> = words.iter().map(|word| reversed(word.clone())).collect();
+        self.inner.try_membership_query_batch(&words)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        let verdict = self.inner.try_equivalence_query(automaton.reverse().with_access_words())?;
+        Ok(verdict.map_err(reversed))
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        let verdict = self.inner.try_equivalence_query_batch(automaton.reverse().with_access_words())?;
+        Ok(verdict.map_err(|counterexamples| counterexamples.into_iter().map(reversed).collect()))
+    }
+}
+
+// A counterexample of the reversed language, turned into one of the original.
+fn reversed<T: PartialEq + Default>(counterexample: Vec<T>) -> Vec<T> {
+    counterexample.into_iter().filter(|symbol| *symbol != T::default()).rev().collect()
+}
@@ -0,0 +1,94 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::teachers::file_teacher::UnknownWordPolicy;
+use crate::automaton::Automaton;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/// A teacher that learns from a stream of `(word, accepted)` observations, e.g. a
+/// `std::sync::mpsc::Receiver` fed from live traffic. A membership query for a word not seen
+/// yet reads further observations, blocking as the source does, until the word turns up; if
+/// the source ends first, the `UnknownWordPolicy` decides. Equivalence queries check the
+/// hypothesis against everything observed so far.
+///
+/// The first observation of a word wins; later contradicting ones are ignored.
+pub struct StreamTeacher<T> {
+    source: RefCell<Box<dyn Iterator<Item = (Vec<T>, bool)>>>,
+    observed: RefCell<HashMap<Vec<T>, bool>>,
+    // Observations in arrival order, so that counterexamples are reproducible.
+    order: RefCell<Vec<Vec<T>>>,
+    unknown_word_policy: UnknownWordPolicy,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> StreamTeacher<T> {
+    pub fn new(observations: impl IntoIterator<Item = (Vec<T>, bool)> + 'static) -> Self {
+        StreamTeacher {
+            source: RefCell::new(Box::new(observations.into_iter())),
+            observed: RefCell::new(HashMap::new()),
+            order: RefCell::new(Vec::new()),
+            unknown_word_policy: UnknownWordPolicy::Reject,
+        }
+    }
+
+    pub fn with_unknown_word_policy(mut self, policy: UnknownWordPolicy) -> Self {
+        self.unknown_word_policy = policy;
+        self
+    }
+
+    /// Records every observation the source has without blocking past its end. Useful before
+    /// an equivalence query when the source is finite.
+    pub fn drain(&self) {
+        while self.pull().is_some() {}
+    }
+
+    // Reads one observation, returning the word it was about.
+    fn pull(&self) -> Option<Vec<T>> {
+        let (word, accepted) = self.source.borrow_mut().next()?;
+        let word = strip_epsilon(&word);
+        if !self.observed.borrow().contains_key(&word) {
+            self.observed.borrow_mut().insert(word.clone(), accepted);
+            self.order.borrow_mut().push(word.clone());
+        }
+        Some(word)
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default> Teacher<T> for StreamTeacher<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        let observed = self.observed.borrow();
+        for word in self.order.borrow().iter() {
+            if automaton.accepts(word) != observed[word] {
+                return Err(word.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        let word = strip_epsilon(states);
+        loop {
+            if let Some(accepted) = self.observed.borrow().get(&word) {
+                return Ok(*accepted);
+            }
+            if self.pull().is_none() {
+                break;
+            }
+        }
+        match self.unknown_word_policy {
+            UnknownWordPolicy::Reject => Ok(false),
+            UnknownWordPolicy::Error => Err(TeacherError::Other(format!("word {:?} was never observed", word))),
+        }
+    }
+}
+
+// The learner writes the empty word as `T::default()`; observations do not.
+fn strip_epsilon<T: PartialEq + Default + Clone>(word: &[T]) -> Vec<T> {
+    word.iter().filter(|symbol| **symbol != T::default()).cloned().collect()
+}
@@ -0,0 +1,135 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use std::cell::{Cell, RefCell};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/// A system under learning driven one input at a time, such as a device or a network endpoint
+/// that can be put back into its initial state.
+pub trait Sul<I> {
+    /// Puts the system back into its initial state and reports whether it accepts there, i.e.
+    /// whether the empty word is in the language.
+    fn reset(&mut self) -> bool;
+
+    /// Feeds one input and reports whether the inputs since the last reset form an accepted
+    /// word.
+    fn step(&mut self, input: &I) -> bool;
+}
+
+/// Answers membership queries by resetting a `Sul` and feeding it the word. A word that extends
+/// the previous one is answered by feeding just the missing inputs, without a reset, and
+/// batches are answered in an order that makes this common. It only answers membership
+/// queries: build an equivalence oracle `from_teacher` on it.
+pub struct SulTeacher<I, S> {
+    run: RefCell<Run<I, S>>,
+    resets: Cell<usize>,
+    steps: Cell<usize>,
+}
+
+// The system and the inputs it has been fed since its last reset, with its last answer.
+struct Run<I, S> {
+    sul: S,
+    fed: Option<Vec<I>>,
+    accepting: bool,
+}
+
+impl<I: Eq + Hash + Clone + Debug + Default, S: Sul<I>> SulTeacher<I, S> {
+    pub fn new(sul: S) -> Self {
+        SulTeacher {
+            run: RefCell::new(Run { sul, fed: None, accepting: false }),
+            resets: Cell::new(0),
+            steps: Cell::new(0),
+        }
+    }
+
+    /// Resets so far.
+    pub fn resets(&self) -> usize {
+        self.resets.get()
+    }
+
+    /// Inputs fed so far.
+    pub fn steps(&self) -> usize {
+        self.steps.get()
+    }
+
+    pub fn into_inner(self) -> S {
+        self.run.into_inner().sul
+    }
+}
+
+impl<I: Eq + Hash + Clone + Debug + Default, S: Sul<I>> Teacher<I> for SulTeacher<I, S> {
+
+    fn membership_query(&self, states: &[I]) -> bool {
+        let word: Vec<I> = states.iter().filter(|symbol| **symbol != I::default()).cloned().collect();
+        let mut run = self.run.borrow_mut();
+        let run = &mut *run;
+        let fed = match run.fed.take() {
+            Some(fed) if word.starts_with(&fed) => fed,
+            _ => {
+                self.resets.set(self.resets.get() + 1);
+                run.accepting = run.sul.reset();
+                Vec::new()
+            }
+        };
+        for input in &word[fed.len()..] {
+            self.steps.set(self.steps.get() + 1);
+            run.accepting = run.sul.step(input);
+        }
+        run.fed = Some(word);
+        run.accepting
+    }
+
+    // Words are fed in depth-first order of their trie, so that each one is followed by the
+    // words extending it.
+    fn try_membership_query_batch(&self, words: &[Vec<I>]) -> Result<Vec<bool>, TeacherError> {
+        let mut answers = vec![false; words.len()];
+        for index in depth_first_order(words) {
+            answers[index] = self.membership_query(&words[index]);
+        }
+        Ok(answers)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<I>, I>) -> EquivalenceAnswer<I> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_equivalence_query(&self, _automaton: Automaton<Vec<I>, I>) -> Result<EquivalenceAnswer<I>, TeacherError> {
+        Err(TeacherError::Other("SulTeacher only answers membership queries".to_string()))
+    }
+}
+
+// A node of the trie of a batch: its children in the order first seen, and the words ending
+// there.
+struct TrieNode<'a, I> {
+    children: Vec<(&'a I, usize)>,
+    words: Vec<usize>,
+}
+
+// The indices of `words`, padding left out, in depth-first order of their trie.
+fn depth_first_order<I: PartialEq + Default>(words: &[Vec<I>]) -> Vec<usize> {
+    let mut trie = vec![TrieNode { children: Vec::new(), words: Vec::new() }];
+    for (index, word) in words.iter().enumerate() {
+        let mut node = 0;
+        for symbol in word.iter().filter(|symbol| **symbol != I::default()) {
+            node = match trie[node].children.iter().find(|(label, _)| *label == symbol) {
+                Some((_, child)) => *child,
+                None => {
+                    trie.push(TrieNode { children: Vec::new(), words: Vec::new() });
+                    let child = trie.len() - 1;
+                    trie[node].children.push((symbol, child));
+                    child
+                }
+            };
+        }
+        trie[node].words.push(index);
+    }
+
+    let mut order = Vec::with_capacity(words.len());
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        order.extend(&trie[node].words);
+        stack.extend(trie[node].children.iter().rev().map(|(_, child)| *child));
+    }
+    order
+}
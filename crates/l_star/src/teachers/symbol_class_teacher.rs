@@ -0,0 +1,131 @@
+use crate::teacher::{EquivalenceAnswer, Teacher, TeacherError};
+use crate::automaton::{Automaton, DfaState, SymbolClass};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+
+/// Lets a learner work over symbol classes while `Inner` answers for concrete symbols. The
+/// learner's alphabet is `classes`, which should be disjoint. A membership query asks `Inner`
+/// about one representative symbol per class: the smallest one other than `T::default()`,
+/// which teachers take for padding. A hypothesis is expanded into an automaton with an edge
+/// for every symbol of every class before `Inner` checks it, so that is only practical for
+/// classes of modest size; a concrete counterexample is mapped back to the classes containing
+/// its symbols.
+pub struct SymbolClassTeacher<T, Inner> {
+    inner: Inner,
+    classes: Vec<SymbolClass<T>>,
+}
+
+impl<T, Inner> SymbolClassTeacher<T, Inner>
+where
+    T: Ord + Copy + Hash + Debug + Default,
+    RangeInclusive<T>: Iterator<Item = T>,
+    Inner: Teacher<T>,
+{
+    pub fn new(classes: impl IntoIterator<Item = SymbolClass<T>>, inner: Inner) -> Self {
+        SymbolClassTeacher {
+            inner,
+            classes: classes.into_iter().filter(|class| !class.is_empty()).collect(),
+        }
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// The alphabet to learn over.
+    pub fn classes(&self) -> &[SymbolClass<T>] {
+        &self.classes
+    }
+
+    // The symbol asked about for `class`; `None` for the learner's padding, the empty class.
+    fn representative(class: &SymbolClass<T>) -> Option<T> {
+        class.ranges().iter().flat_map(|(lo, hi)| *lo..=*hi).find(|symbol| *symbol != T::default())
+    }
+
+    fn class_of(&self, symbol: &T) -> Result<SymbolClass<T>, TeacherError> {
+        self.classes.iter()
+            .find(|class| class.contains(symbol))
+            .cloned()
+            .ok_or_else(|| TeacherError::Other(format!("counterexample symbol {:?} is in no class", symbol)))
+    }
+
+    // Every edge on a class becomes one edge per symbol of the class.
+    fn expand(automaton: &Automaton<Vec<SymbolClass<T>>, SymbolClass<T>>) -> Automaton<Vec<T>, T> {
+        let state = |state_id: &Vec<SymbolClass<T>>| DfaState::new(state_id.clone(), automaton.get_state(state_id).is_some_and(|state| state.is_accepting()));
+        let initial = automaton.get_initial_state().expect("hypotheses have their initial state").get_state_id();
+        let mut expanded = Automaton::new(state(initial));
+        for (state_id, dfa_state) in automaton.get_states() {
+            expanded.add_state(state(state_id));
+            for (class, target) in dfa_state.transitions() {
+                for symbol in class.ranges().iter().flat_map(|(lo, hi)| *lo..=*hi) {
+                    expanded.add_transition(&state(state_id), &state(target), &symbol);
+                }
+            }
+        }
+        expanded.with_access_words()
+    }
+}
+
+impl<T, Inner> Teacher<SymbolClass<T>> for SymbolClassTeacher<T, Inner>
+where
+    T: Ord + Copy + Hash + Debug + Default,
+    RangeInclusive<T>: Iterator<Item = T>,
+    Inner: Teacher<T>,
+{
+
+    fn membership_query(&self, states: &[SymbolClass<T>]) -> bool {
+        self.try_membership_query(states).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<SymbolClass<T>>, SymbolClass<T>>) -> EquivalenceAnswer<SymbolClass<T>> {
+        self.try_equivalence_query(automaton).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_membership_query(&self, states: &[SymbolClass<T>]) -> Result<bool, TeacherError> {
+        let word: Vec<T> = states.iter().filter_map(Self::representative).collect();
+        self.inner.try_membership_query(&word)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<SymbolClass<T>>, SymbolClass<T>>) -> Result<EquivalenceAnswer<SymbolClass<T>>, TeacherError> {
+        match self.inner.try_equivalence_query(Self::expand(&automaton))? {
+            Ok(()) => Ok(Ok(())),
+            Err(counterexample) => Ok(Err(counterexample.iter().map(|symbol| self.class_of(symbol)).collect::<Result<_, _>>()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::Alphabet;
+    use crate::automaton::{ByteClass, CharClass};
+    use crate::learner::Learner;
+    use crate::teachers::dfa_teacher::DfaTeacher;
+    use crate::teachers::fn_teacher::FnTeacher;
+
+    #[test]
+    fn membership_asks_one_symbol_per_class() {
+        let teacher = SymbolClassTeacher::new([CharClass::range('a', 'z')], FnTeacher::new(|word: &[char]| word == ['a', 'a']));
+        assert!(teacher.membership_query(&[CharClass::default(), CharClass::range('a', 'z'), CharClass::range('a', 'z')]));
+
+        // Zero is the padding byte, so a class starting there is asked about by its next byte.
+        let teacher = SymbolClassTeacher::new([ByteClass::range(0, 9)], FnTeacher::new(|word: &[u8]| word == [1]));
+        assert!(teacher.membership_query(&[ByteClass::range(0, 9)]));
+    }
+
+    #[test]
+    fn learns_identifiers_over_three_classes() {
+        let characters = Alphabet::new(('a'..='z').chain('0'..='9').chain(['_']));
+        let target = DfaTeacher::from_regex("^[a-z][a-z0-9_]*$", &characters).unwrap();
+        let teacher = SymbolClassTeacher::new([CharClass::range('a', 'z'), CharClass::range('0', '9'), CharClass::single('_')], target);
+        let classes = Alphabet::new(teacher.classes().to_vec());
+
+        let learned = Learner::new(classes, Box::new(teacher)).learn().unwrap();
+        assert_eq!(learned.state_count(), 3);
+        assert!(learned.accepts_symbols(&['q', '7', '_', 'z']));
+        assert!(!learned.accepts_symbols(&['7', 'q']));
+        assert!(!learned.accepts_symbols(&[]));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::teacher::{EquivalenceAnswer, EquivalenceBatch, Teacher, TeacherError};
+use crate::automaton::Automaton;
+use crate::random::SplitMix64;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::thread;
+use std::time::{Duration, Instant};
+
+
+/// Time a `ThrottledTeacher` has spent sleeping.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThrottleStats {
+    /// Calls passed on to the inner teacher; a batch counts once.
+    pub calls: usize,
+    /// Spent keeping calls the minimum interval apart.
+    pub interval_wait: Duration,
+    /// Spent on the injected latency.
+    pub injected_delay: Duration,
+}
+
+impl ThrottleStats {
+    pub fn total_wait(&self) -> Duration {
+        self.interval_wait + self.injected_delay
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Delay {
+    None,
+    Fixed(Duration),
+    Uniform { min: Duration, max: Duration, rng: SplitMix64 },
+}
+
+struct ThrottleState {
+    last_call: Option<Instant>,
+    delay: Delay,
+    stats: ThrottleStats,
+}
+
+/// Passes every query on to `Inner`, keeping calls at least a minimum interval apart and
+/// sleeping for an injected latency before each, to stand in for a slow system under learning.
+/// A batch query is a single call, so batching pays the interval and the latency once per
+/// batch instead of once per word. Put a `CachingTeacher` outside it to only throttle the
+/// queries that reach the system, or a `CountingTeacher` inside to count what does.
+pub struct ThrottledTeacher<T, Inner> {
+    inner: Inner,
+    min_interval: Duration,
+    state: RefCell<ThrottleState>,
+    _symbols: PhantomData<T>,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> ThrottledTeacher<T, Inner> {
+    pub fn new(inner: Inner) -> Self {
+        ThrottledTeacher {
+            inner,
+            min_interval: Duration::ZERO,
+            state: RefCell::new(ThrottleState { last_call: None, delay: Delay::None, stats: ThrottleStats::default() }),
+            _symbols: PhantomData,
+        }
+    }
+
+    /// Minimum time between the starts of two calls.
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    pub fn with_fixed_delay(self, delay: Duration) -> Self {
+        self.state.borrow_mut().delay = Delay::Fixed(delay);
+        self
+    }
+
+    /// Delays drawn uniformly from `min..=max`; the same seed gives the same delays.
+    pub fn with_random_delay(self, min: Duration, max: Duration, seed: u64) -> Self {
+        assert!(min <= max, "minimum delay {:?} is above the maximum {:?}", min, max);
+        self.state.borrow_mut().delay = Delay::Uniform { min, max, rng: SplitMix64::new(seed) };
+        self
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    pub fn stats(&self) -> ThrottleStats {
+        self.state.borrow().stats.clone()
+    }
+
+    pub fn reset(&self) {
+        self.state.borrow_mut().stats = ThrottleStats::default();
+    }
+
+    fn throttle(&self) {
+        let mut state = self.state.borrow_mut();
+        if let Some(last_call) = state.last_call {
+            let wait = self.min_interval.saturating_sub(last_call.elapsed());
+            if !wait.is_zero() {
+                thread::sleep(wait);
+                state.stats.interval_wait += wait;
+            }
+        }
+        state.last_call = Some(Instant::now());
+        let delay = match &mut state.delay {
+            Delay::None => Duration::ZERO,
+            Delay::Fixed(delay) => *delay,
+            Delay::Uniform { min, max, rng } => *min + (*max - *min).mul_f64(rng.next_f64()),
+        };
+        if !delay.is_zero() {
+            thread::sleep(delay);
+            state.stats.injected_delay += delay;
+        }
+        state.stats.calls += 1;
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default, Inner: Teacher<T>> Teacher<T> for ThrottledTeacher<T, Inner> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        self.throttle();
+        self.inner.membership_query(states)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        self.throttle();
+        self.inner.equivalence_query(automaton)
+    }
+
+    fn try_membership_query(&self, states: &[T]) -> Result<bool, TeacherError> {
+        self.throttle();
+        self.inner.try_membership_query(states)
+    }
+
+    fn try_membership_query_batch(&self, words: &[Vec<T>]) -> Result<Vec<bool>, TeacherError> {
+        self.throttle();
+        self.inner.try_membership_query_batch(words)
+    }
+
+    fn try_equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceAnswer<T>, TeacherError> {
+        self.throttle();
+        self.inner.try_equivalence_query(automaton)
+    }
+
+    fn try_equivalence_query_batch(&self, automaton: Automaton<Vec<T>, T>) -> Result<EquivalenceBatch<T>, TeacherError> {
+        self.throttle();
+        self.inner.try_equivalence_query_batch(automaton)
+    }
+}
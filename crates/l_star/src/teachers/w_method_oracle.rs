@@ -0,0 +1,119 @@
+use crate::teacher::{EquivalenceAnswer, Membership, Teacher};
+use crate::alphabet::Alphabet;
+use crate::automaton::{Automaton, DenseAutomaton};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/*
+The W-method (Chow; Vasilevskii): with P the state cover of the hypothesis, W a characterizing
+set and m the number of extra states, the test suite P · A^{≤ m+1} · (W ∪ {ε}) tells the
+hypothesis apart from every target with at most m more states that differs from it. Tests are
+run with the shorter middle parts first, so that counterexamples come out short.
+ */
+/// An equivalence oracle for black-box targets that is exact under an assumption: if the
+/// target has at most `max_extra_states` more states than the hypothesis (counting the
+/// implicit sink), a hypothesis passing the W-method test suite is correct. The suite grows
+/// as `|A|^(max_extra_states + 1)`.
+pub struct WMethodOracle<T> {
+    membership: Membership<T>,
+    alphabet: Alphabet<T>,
+    max_extra_states: usize,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> WMethodOracle<T> {
+    /// Answers membership queries with `membership`. Defaults to no extra states, i.e. a
+    /// target no larger than the hypothesis.
+    pub fn from_fn(alphabet: Alphabet<T>, membership: impl Fn(&[T]) -> bool + 'static) -> Self {
+        WMethodOracle {
+            membership: Box::new(membership),
+            alphabet,
+            max_extra_states: 0,
+        }
+    }
+
+    /// Answers membership queries with `teacher`, replacing its equivalence queries.
+    pub fn from_teacher(alphabet: Alphabet<T>, teacher: impl Teacher<T> + 'static) -> Self {
+        Self::from_fn(alphabet, move |word| teacher.membership_query(word))
+    }
+
+    pub fn with_max_extra_states(mut self, max_extra_states: usize) -> Self {
+        self.max_extra_states = max_extra_states;
+        self
+    }
+
+    /// The words a hypothesis is tested on, without duplicates, in the order they are asked.
+    pub fn test_suite(&self, automaton: &Automaton<Vec<T>, T>) -> Vec<Vec<T>> {
+        let hypothesis = DenseAutomaton::from_automaton(automaton, &self.alphabet);
+        let state_cover = hypothesis.state_cover();
+        let mut suffixes = vec![Vec::new()];
+        suffixes.extend(hypothesis.characterizing_set());
+
+        let mut suite = TestSuite::default();
+        for middles in middle_parts(&self.alphabet, self.max_extra_states + 1) {
+            for access in &state_cover {
+                for middle in &middles {
+                    for suffix in &suffixes {
+                        suite.push(access.iter().chain(middle).chain(suffix).cloned().collect());
+                    }
+                }
+            }
+        }
+        suite.words
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> Teacher<T> for WMethodOracle<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        (self.membership)(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        for word in self.test_suite(&automaton) {
+            if automaton.accepts(&word) != self.membership_query(&word) {
+                return Err(word);
+            }
+        }
+        Ok(())
+    }
+}
+
+
+// Test words in order of first appearance, each once.
+pub(crate) struct TestSuite<T> {
+    pub(crate) words: Vec<Vec<T>>,
+    seen: HashSet<Vec<T>>,
+}
+
+impl<T> Default for TestSuite<T> {
+    fn default() -> Self {
+        TestSuite { words: Vec::new(), seen: HashSet::new() }
+    }
+}
+
+impl<T: Eq + Hash + Clone> TestSuite<T> {
+    pub(crate) fn push(&mut self, word: Vec<T>) {
+        if self.seen.insert(word.clone()) {
+            self.words.push(word);
+        }
+    }
+}
+
+// All words over `alphabet` of length 0 to `max_len`, one layer per length.
+pub(crate) fn middle_parts<T: Eq + Hash + Clone>(alphabet: &Alphabet<T>, max_len: usize) -> Vec<Vec<Vec<T>>> {
+    let mut layers: Vec<Vec<Vec<T>>> = vec![vec![Vec::new()]];
+    for _ in 0..max_len {
+        let next = layers[layers.len() - 1].iter()
+            .flat_map(|middle| alphabet.iter().map(move |a| {
+                let mut next = middle.clone();
+                next.push(a.clone());
+                next
+            }))
+            .collect();
+        layers.push(next);
+    }
+    layers
+}
@@ -0,0 +1,152 @@
+use crate::teacher::{EquivalenceAnswer, Membership, Teacher};
+use crate::alphabet::Alphabet;
+use crate::automaton::{Automaton, DenseAutomaton};
+use crate::teachers::w_method_oracle::{middle_parts, TestSuite};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+
+/*
+The partial W-method (Fujiwara et al.): with P the state cover, W a characterizing set, W_s the
+identification set of state s and m the number of extra states,
+
+    phase 1:  P · A^{≤ m} · (W ∪ {ε})
+    phase 2:  u · (W_δ(u) ∪ {ε})  for every u in P · A^{m+1}
+
+Only phase 1 needs all of W, so the suite is usually much smaller than the W-method's, with the
+same guarantee. A word whose run leaves the hypothesis through a missing transition gets all
+of W.
+ */
+/// Like `WMethodOracle`, exact if the target has at most `max_extra_states` more states than
+/// the hypothesis, but testing with per-state identification sets where it can.
+pub struct WpMethodOracle<T> {
+    membership: Membership<T>,
+    alphabet: Alphabet<T>,
+    max_extra_states: usize,
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> WpMethodOracle<T> {
+    /// Answers membership queries with `membership`. Defaults to no extra states, i.e. a
+    /// target no larger than the hypothesis.
+    pub fn from_fn(alphabet: Alphabet<T>, membership: impl Fn(&[T]) -> bool + 'static) -> Self {
+        WpMethodOracle {
+            membership: Box::new(membership),
+            alphabet,
+            max_extra_states: 0,
+        }
+    }
+
+    /// Answers membership queries with `teacher`, replacing its equivalence queries.
+    pub fn from_teacher(alphabet: Alphabet<T>, teacher: impl Teacher<T> + 'static) -> Self {
+        Self::from_fn(alphabet, move |word| teacher.membership_query(word))
+    }
+
+    pub fn with_max_extra_states(mut self, max_extra_states: usize) -> Self {
+        self.max_extra_states = max_extra_states;
+        self
+    }
+
+    /// The words a hypothesis is tested on, without duplicates, in the order they are asked.
+    pub fn test_suite(&self, automaton: &Automaton<Vec<T>, T>) -> Vec<Vec<T>> {
+        let hypothesis = DenseAutomaton::from_automaton(automaton, &self.alphabet);
+        let state_cover = hypothesis.state_cover();
+        let characterizing_set = hypothesis.characterizing_set();
+        let identification_sets = hypothesis.identification_sets(&characterizing_set);
+
+        let mut suite = TestSuite::default();
+        let mut layers = middle_parts(&self.alphabet, self.max_extra_states + 1);
+        let last = layers.pop().unwrap_or_default();
+        for middles in &layers {
+            for access in &state_cover {
+                for middle in middles {
+                    let prefix: Vec<T> = access.iter().chain(middle).cloned().collect();
+                    suite.push(prefix.clone());
+                    for suffix in &characterizing_set {
+                        suite.push(prefix.iter().chain(suffix).cloned().collect());
+                    }
+                }
+            }
+        }
+        for access in &state_cover {
+            for middle in &last {
+                let prefix: Vec<T> = access.iter().chain(middle).cloned().collect();
+                suite.push(prefix.clone());
+                let suffixes = match hypothesis.run(&prefix) {
+                    Some(state) => &identification_sets[state],
+                    None => &characterizing_set,
+                };
+                for suffix in suffixes {
+                    suite.push(prefix.iter().chain(suffix).cloned().collect());
+                }
+            }
+        }
+        suite.words
+    }
+}
+
+impl<T: Eq + Hash + Clone + Debug + Default + 'static> Teacher<T> for WpMethodOracle<T> {
+
+    fn membership_query(&self, states: &[T]) -> bool {
+        let word: Vec<T> = states.iter().filter(|symbol| **symbol != T::default()).cloned().collect();
+        (self.membership)(&word)
+    }
+
+    fn equivalence_query(&self, automaton: Automaton<Vec<T>, T>) -> EquivalenceAnswer<T> {
+        for word in self.test_suite(&automaton) {
+            if automaton.accepts(&word) != self.membership_query(&word) {
+                return Err(word);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::DfaState;
+    use crate::teachers::w_method_oracle::WMethodOracle;
+
+    // Words whose number of `a`s is a multiple of 8, with an 8-state cycle on `a`. `fault`
+    // sends the `a` edge out of that state back to the start instead.
+    fn counter(fault: Option<usize>) -> Automaton<Vec<char>, char> {
+        let state = |count: usize| DfaState::new(vec!['a'; count], count == 0);
+        let mut automaton = Automaton::new(state(0));
+        for count in 0..8 {
+            let next = if fault == Some(count) { 0 } else { (count + 1) % 8 };
+            automaton.add_transition(&state(count), &state(next), &'a');
+            automaton.add_transition(&state(count), &state(count), &'b');
+        }
+        automaton
+    }
+
+    fn target(word: &[char]) -> bool {
+        word.iter().filter(|symbol| **symbol == 'a').count() % 8 == 0
+    }
+
+    fn alphabet() -> Alphabet<char> {
+        Alphabet::new(['a', 'b'])
+    }
+
+    #[test]
+    fn suite_is_smaller_than_the_w_methods() {
+        let wp = WpMethodOracle::from_fn(alphabet(), target).with_max_extra_states(1);
+        let w = WMethodOracle::from_fn(alphabet(), target).with_max_extra_states(1);
+        let hypothesis = counter(None);
+        assert!(wp.test_suite(&hypothesis).len() < w.test_suite(&hypothesis).len());
+    }
+
+    #[test]
+    fn catches_an_injected_fault() {
+        let wp = WpMethodOracle::from_fn(alphabet(), target).with_max_extra_states(1);
+        assert_eq!(wp.equivalence_query(counter(None)), Ok(()));
+        let counterexample = wp.equivalence_query(counter(Some(5))).unwrap_err();
+        assert_ne!(counter(Some(5)).accepts(&counterexample), target(&counterexample));
+    }
+
+    #[test]
+    fn membership_strips_padding() {
+        let wp = WpMethodOracle::from_fn(alphabet(), |word: &[char]| word.is_empty());
+        assert!(wp.membership_query(&[char::default()]));
+    }
+}